@@ -0,0 +1,19 @@
+#![no_main]
+
+// The compiler shall report a syntax error for every
+// malformed input instead of panicking.
+
+use std::rc::Rc;
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+    static INTERPRETER: Rc<moss::Interpreter> = moss::Interpreter::new();
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        INTERPRETER.with(|i| {
+            let _ = i.compile(s, "fuzz");
+        });
+    }
+});
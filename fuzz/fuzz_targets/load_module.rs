@@ -0,0 +1,18 @@
+#![no_main]
+
+// Loading and running a malformed binary module shall fail without
+// panicking. The interpreter has the default capabilities, thus the
+// program cannot write files or execute commands.
+
+use std::rc::Rc;
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+    static INTERPRETER: Rc<moss::Interpreter> = moss::Interpreter::new();
+}
+
+fuzz_target!(|data: &[u8]| {
+    INTERPRETER.with(|i| {
+        let _ = i.eval_binary(data, "fuzz");
+    });
+});
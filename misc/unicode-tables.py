@@ -0,0 +1,223 @@
+#!/usr/bin/env python3
+
+# Generates the data tables of the module unicode from the Unicode
+# Character Database, as compiled into Python's unicodedata module.
+# The few properties unicodedata does not provide are listed below,
+# taken from PropList.txt, GraphemeBreakProperty.txt and emoji-data.txt
+# of the same Unicode version.
+#
+# Usage: python3 misc/unicode-tables.py > src/modules/unicode/tables.rs
+
+import unicodedata as ud
+
+VERSION = "14.0.0"
+
+# PropList.txt, Other_Grapheme_Extend
+OTHER_GRAPHEME_EXTEND = [
+    (0x09BE,0x09BE), (0x09D7,0x09D7), (0x0B3E,0x0B3E), (0x0B57,0x0B57),
+    (0x0BBE,0x0BBE), (0x0BD7,0x0BD7), (0x0CC2,0x0CC2), (0x0CD5,0x0CD6),
+    (0x0D3E,0x0D3E), (0x0D57,0x0D57), (0x0DCF,0x0DCF), (0x0DDF,0x0DDF),
+    (0x1B35,0x1B35), (0x200C,0x200C), (0x302E,0x302F), (0xFF9E,0xFF9F),
+    (0x1133E,0x1133E), (0x11357,0x11357), (0x114B0,0x114B0),
+    (0x114BD,0x114BD), (0x115AF,0x115AF), (0x11930,0x11930),
+    (0x1D165,0x1D165), (0x1D16E,0x1D172), (0xE0020,0xE007F)
+]
+
+# GraphemeBreakProperty.txt, Prepend
+PREPEND = [
+    (0x0600,0x0605), (0x06DD,0x06DD), (0x070F,0x070F), (0x0890,0x0891),
+    (0x08E2,0x08E2), (0x0D4E,0x0D4E), (0x110BD,0x110BD),
+    (0x110CD,0x110CD), (0x111C2,0x111C3), (0x1193F,0x1193F),
+    (0x11941,0x11941), (0x11A3A,0x11A3A), (0x11A84,0x11A89),
+    (0x11D46,0x11D46)
+]
+
+# GraphemeBreakProperty.txt, characters of general category Mc
+# that are not SpacingMark, and characters of other categories
+# that are.
+NOT_SPACING_MARK = [
+    (0x102B,0x102C), (0x1038,0x1038), (0x1062,0x1064), (0x1067,0x106D),
+    (0x1083,0x1083), (0x1087,0x108C), (0x108F,0x108F), (0x109A,0x109C),
+    (0x1A61,0x1A61), (0x1A63,0x1A64), (0xAA7B,0xAA7B), (0xAA7D,0xAA7D),
+    (0x11720,0x11721)
+]
+SPACING_MARK = [(0x0E33,0x0E33), (0x0EB3,0x0EB3)]
+
+# emoji-data.txt, Emoji_Modifier
+EMOJI_MODIFIER = [(0x1F3FB,0x1F3FF)]
+
+# emoji-data.txt, Extended_Pictographic
+EXTENDED_PICTOGRAPHIC = [
+    (0x00A9,0x00A9), (0x00AE,0x00AE), (0x203C,0x203C), (0x2049,0x2049),
+    (0x2122,0x2122), (0x2139,0x2139), (0x2194,0x2199), (0x21A9,0x21AA),
+    (0x231A,0x231B), (0x2328,0x2328), (0x2388,0x2388), (0x23CF,0x23CF),
+    (0x23E9,0x23F3), (0x23F8,0x23FA), (0x24C2,0x24C2), (0x25AA,0x25AB),
+    (0x25B6,0x25B6), (0x25C0,0x25C0), (0x25FB,0x25FE), (0x2600,0x2605),
+    (0x2607,0x2612), (0x2614,0x2685), (0x2690,0x2705), (0x2708,0x2712),
+    (0x2714,0x2714), (0x2716,0x2716), (0x271D,0x271D), (0x2721,0x2721),
+    (0x2728,0x2728), (0x2733,0x2734), (0x2744,0x2744), (0x2747,0x2747),
+    (0x274C,0x274C), (0x274E,0x274E), (0x2753,0x2755), (0x2757,0x2757),
+    (0x2763,0x2767), (0x2795,0x2797), (0x27A1,0x27A1), (0x27B0,0x27B0),
+    (0x27BF,0x27BF), (0x2934,0x2935), (0x2B05,0x2B07), (0x2B1B,0x2B1C),
+    (0x2B50,0x2B50), (0x2B55,0x2B55), (0x3030,0x3030), (0x303D,0x303D),
+    (0x3297,0x3297), (0x3299,0x3299), (0x1F000,0x1F0FF),
+    (0x1F10D,0x1F10F), (0x1F12F,0x1F12F), (0x1F16C,0x1F171),
+    (0x1F17E,0x1F17F), (0x1F18E,0x1F18E), (0x1F191,0x1F19A),
+    (0x1F1AD,0x1F1E5), (0x1F201,0x1F20F), (0x1F21A,0x1F21A),
+    (0x1F22F,0x1F22F), (0x1F232,0x1F23A), (0x1F23C,0x1F23F),
+    (0x1F249,0x1F3FA), (0x1F400,0x1F53D), (0x1F546,0x1F64F),
+    (0x1F680,0x1F6FF), (0x1F774,0x1F77F), (0x1F7D5,0x1F7FF),
+    (0x1F80C,0x1F80F), (0x1F848,0x1F84F), (0x1F85A,0x1F85F),
+    (0x1F888,0x1F88F), (0x1F8AE,0x1F8FF), (0x1F90C,0x1F93A),
+    (0x1F93C,0x1F945), (0x1F947,0x1FAFF), (0x1FC00,0x1FFFD)
+]
+
+# Hangul_Syllable_Type of the conjoining jamo. The syllables
+# AC00..D7A3 are handled algorithmically.
+HANGUL_L = [(0x1100,0x115F), (0xA960,0xA97C)]
+HANGUL_V = [(0x1160,0x11A7), (0xD7B0,0xD7C6)]
+HANGUL_T = [(0x11A8,0x11FF), (0xD7CB,0xD7FB)]
+
+def is_hangul_syllable(c):
+    return 0xAC00 <= c <= 0xD7A3
+
+def code_points():
+    for c in range(0x110000):
+        if 0xD800 <= c <= 0xDFFF: continue
+        yield c
+
+def member(ranges, c):
+    return any(a <= c <= b for (a,b) in ranges)
+
+def esc(c):
+    return "'\\u{%x}'" % c
+
+def esc_str(s):
+    return "&[" + ",".join(esc(ord(x)) for x in s) + "]"
+
+def ranges_of(f):
+    acc = []
+    for c in code_points():
+        x = f(c)
+        if x is None: continue
+        if acc and acc[-1][1] == c-1 and acc[-1][2] == x:
+            acc[-1][1] = c
+        else:
+            acc.append([c,c,x])
+    return acc
+
+def emit(name, ty, items):
+    print("pub static {}: &[{}] = &[".format(name, ty))
+    line = "   "
+    for item in items:
+        if len(line)+len(item)+2 > 78:
+            print(line)
+            line = "   "
+        line += " " + item + ","
+    if line.strip():
+        print(line)
+    print("];")
+    print()
+
+def combining_class(c):
+    x = ud.combining(chr(c))
+    return x if x != 0 else None
+
+def canonical(c):
+    if is_hangul_syllable(c): return None
+    s = ud.normalize("NFD", chr(c))
+    return s if s != chr(c) else None
+
+def compatibility(c):
+    if is_hangul_syllable(c): return None
+    s = ud.normalize("NFKD", chr(c))
+    return s if s != ud.normalize("NFD", chr(c)) else None
+
+def composition(c):
+    if is_hangul_syllable(c): return None
+    d = ud.decomposition(chr(c))
+    if d == "" or d.startswith("<"): return None
+    a = [int(x,16) for x in d.split()]
+    if len(a) != 2 or ud.combining(chr(a[0])) != 0: return None
+    if ud.normalize("NFC", chr(c)) != chr(c): return None
+    return (a[0], a[1])
+
+def case_folding(c):
+    s = chr(c).casefold()
+    return s if s != chr(c) else None
+
+def grapheme_extend(c):
+    cat = ud.category(chr(c))
+    return cat in ("Mn","Me") or member(OTHER_GRAPHEME_EXTEND,c)
+
+def grapheme_break(c):
+    cat = ud.category(chr(c))
+    if c == 0x0D: return "CR"
+    if c == 0x0A: return "LF"
+    if c == 0x200D: return "ZWJ"
+    if member(PREPEND,c): return "Prepend"
+    if cat in ("Zl","Zp","Cc") or (cat == "Cf" and c != 0x200C):
+        return "Control"
+    if grapheme_extend(c) or member(EMOJI_MODIFIER,c): return "Extend"
+    if 0x1F1E6 <= c <= 0x1F1FF: return "RegionalIndicator"
+    if member(SPACING_MARK,c): return "SpacingMark"
+    if cat == "Mc" and not member(NOT_SPACING_MARK,c): return "SpacingMark"
+    if member(HANGUL_L,c): return "L"
+    if member(HANGUL_V,c): return "V"
+    if member(HANGUL_T,c): return "T"
+    return None
+
+def width(c):
+    cat = ud.category(chr(c))
+    if c == 0x00AD: return None
+    if cat in ("Mn","Me","Cf","Cc") or c == 0x200B: return 0
+    if member(HANGUL_V,c) or member(HANGUL_T,c): return 0
+    if ud.east_asian_width(chr(c)) in ("W","F"): return 2
+    return None
+
+def main():
+    assert ud.unidata_version == VERSION, ud.unidata_version
+    print("// Generated by misc/unicode-tables.py from the Unicode")
+    print("// Character Database {}. Do not edit.".format(VERSION))
+    print()
+    print("use super::GraphemeBreak;")
+    print("use super::GraphemeBreak::*;")
+    print()
+    print("pub const VERSION: &str = \"{}\";".format(VERSION))
+    print()
+
+    emit("COMBINING_CLASS", "(char,char,u8)",
+        ["({},{},{})".format(esc(a),esc(b),x)
+         for (a,b,x) in ranges_of(combining_class)])
+
+    emit("CANONICAL", "(char,&[char])",
+        ["({},{})".format(esc(c),esc_str(s))
+         for c in code_points() for s in [canonical(c)] if s])
+
+    emit("COMPATIBILITY", "(char,&[char])",
+        ["({},{})".format(esc(c),esc_str(s))
+         for c in code_points() for s in [compatibility(c)] if s])
+
+    pairs = sorted((p,c) for c in code_points()
+        for p in [composition(c)] if p)
+    emit("COMPOSITION", "(char,char,char)",
+        ["({},{},{})".format(esc(a),esc(b),esc(c))
+         for ((a,b),c) in pairs])
+
+    emit("CASE_FOLDING", "(char,&[char])",
+        ["({},{})".format(esc(c),esc_str(s))
+         for c in code_points() for s in [case_folding(c)] if s])
+
+    emit("GRAPHEME_BREAK", "(char,char,GraphemeBreak)",
+        ["({},{},{})".format(esc(a),esc(b),x)
+         for (a,b,x) in ranges_of(grapheme_break)])
+
+    emit("EXTENDED_PICTOGRAPHIC", "(char,char)",
+        ["({},{})".format(esc(a),esc(b))
+         for (a,b) in EXTENDED_PICTOGRAPHIC])
+
+    emit("WIDTH", "(char,char,u8)",
+        ["({},{},{})".format(esc(a),esc(b),x)
+         for (a,b,x) in ranges_of(width)])
+
+main()
@@ -30,6 +30,7 @@ source code
 
 use std::rc::Rc;
 use std::mem::replace;
+use std::collections::HashMap;
 use std::str::{Chars, FromStr};
 use std::char;
 
@@ -160,32 +161,55 @@ static KEYWORDS: &[KeywordsElement] = &[
     KeywordsElement {s: "yield",   t: &SymbolType::Keyword, v: &Symbol::Yield}
 ];
 
+pub fn keywords() -> impl Iterator<Item=&'static str> {
+    KEYWORDS.iter().map(|x| x.s)
+}
+
 pub struct SyntaxError {
     line: usize, col: usize,
     file: String, s: String
 }
 
 pub enum EnumError{
-    Syntax(SyntaxError)
+    Syntax(SyntaxError),
+
+    // All errors of a source, after the parser has recovered
+    // from each of them at the next statement.
+    Several(Vec<SyntaxError>)
 }
 type Error = Box<EnumError>;
 
-pub fn format_syntax_error(e: &SyntaxError) -> String {
-    format!("Line {}, col {} ({}):\nSyntax error: {}",
-        e.line, e.col, e.file, e.s)
+// The offending source line with a caret under the column.
+fn excerpt(source: &str, line: usize, col: usize) -> Option<String> {
+    let text = source.lines().nth(line.checked_sub(1)?)?;
+    if text.trim().is_empty() {return None;}
+    let indent: String = text.chars().take(col.saturating_sub(1))
+        .map(|c| if c == '\t' {'\t'} else {' '}).collect();
+    let number = line.to_string();
+    Some(format!("{} | {}\n{} | {}^", number, text, " ".repeat(number.len()), indent))
+}
+
+pub fn format_syntax_error(e: &SyntaxError, source: &str) -> String {
+    let mut s = format!("Line {}, col {} ({}):\nSyntax error: {}",
+        e.line, e.col, e.file, e.s);
+    if let Some(x) = excerpt(source, e.line, e.col) {
+        s.push('\n');
+        s.push_str(&x);
+    }
+    s
 }
 
-pub fn format_error(e: &Error) -> String {
+pub fn format_error(e: &Error, source: &str) -> String {
     match **e {
-        EnumError::Syntax(ref e) =>  format_syntax_error(e)
+        EnumError::Syntax(ref e) => format_syntax_error(e, source),
+        EnumError::Several(ref a) => {
+            let v: Vec<String> = a.iter().map(|e| format_syntax_error(e, source)).collect();
+            format!("{}\n\n{} syntax errors.", v.join("\n\n"), a.len())
+        }
     }
 }
 
 #[allow(dead_code)]
-fn compiler_error() -> ! {
-    unreachable!("compiler error");
-}
-
 fn is_keyword(id: &str) -> Option<&'static KeywordsElement> {
     for keyword in KEYWORDS {
         if keyword.s == id  {return Some(keyword);}
@@ -193,6 +217,20 @@ fn is_keyword(id: &str) -> Option<&'static KeywordsElement> {
     None
 }
 
+// Whether a numeric literal as delimited by the scanner is well formed.
+fn valid_number(chars: &[char], s: &str, token_type: SymbolType) -> bool {
+    if token_type != SymbolType::Int {
+        return s.parse::<f64>().is_ok();
+    }
+    let (radix, digits) = match chars {
+        ['0', 'x', rest @ ..] => (16, rest),
+        ['0', 'o', rest @ ..] => (8, rest),
+        ['0', 'b', rest @ ..] => (2, rest),
+        _ => (10, chars)
+    };
+    !digits.is_empty() && digits.iter().all(|c| c.is_digit(radix))
+}
+
 fn int_from_str(chars: &[char], s: &str) -> Result<i32,()> {
     let value = if chars.len() > 2 && chars[0] == '0' {
         if chars[1] == 'x' {
@@ -213,8 +251,36 @@ fn int_from_str(chars: &[char], s: &str) -> Result<i32,()> {
     }
 }
 
+// The source text of each token and the comments in between,
+// which the compiler does not need, but the formatter does.
+pub struct Trivia {
+    text: Vec<String>,
+    comments: Vec<Comment>
+}
+
+struct Comment {
+    // Index of the token that follows the comment.
+    index: usize,
+    text: String
+}
+
 pub fn scan(s: &str, line_start: usize, file: &str, new_line_start: bool)
     -> Result<Vec<Token>,Error>
+{
+    scan_source(s, line_start, file, new_line_start, None)
+}
+
+pub fn scan_with_trivia(s: &str, file: &str)
+    -> Result<(Vec<Token>,Trivia),Error>
+{
+    let mut trivia = Trivia {text: Vec::new(), comments: Vec::new()};
+    let v = scan_source(s, 1, file, false, Some(&mut trivia))?;
+    Ok((v, trivia))
+}
+
+fn scan_source(s: &str, line_start: usize, file: &str, new_line_start: bool,
+    mut trivia: Option<&mut Trivia>
+) -> Result<Vec<Token>,Error>
 {
     let mut acc: Vec<Token> = Vec::new();
     if new_line_start {
@@ -233,6 +299,8 @@ pub fn scan(s: &str, line_start: usize, file: &str, new_line_start: bool)
     let n = a.len();
     while i < n {
         let c = a[i];
+        let start = i;
+        let count = acc.len();
         if c.is_digit(10) {
             let j = i;
             hcol = col;
@@ -267,6 +335,12 @@ pub fn scan(s: &str, line_start: usize, file: &str, new_line_start: bool)
             }
             let chars = &a[j..i];
             let number: &String = &chars.iter().cloned().collect();
+            if !valid_number(chars, number, token_type) {
+                return Err(Box::new(EnumError::Syntax(SyntaxError {
+                    line, col: hcol, file: String::from(file),
+                    s: format!("invalid number '{}'.", number)
+                })));
+            }
             if token_type == SymbolType::Int {
                 match int_from_str(chars,number) {
                     Ok(x) => {
@@ -426,6 +500,10 @@ pub fn scan(s: &str, line_start: usize, file: &str, new_line_start: bool)
                             }
                             i += 1;
                         }
+                        if let Some(ref mut trivia) = trivia {
+                            trivia.comments.push(Comment {index: acc.len(),
+                                text: a[start..i].iter().collect()});
+                        }
                     } else if i+1 < n && a[i+1] == '/' {
                         if i+2 < n && a[i+2] == '=' {
                             acc.push(Token::aoperator(line,col,Symbol::AIdiv));
@@ -585,6 +663,10 @@ pub fn scan(s: &str, line_start: usize, file: &str, new_line_start: bool)
                 },
                 '#' => {
                     while i < n && a[i] != '\n' {i += 1; col += 1;}
+                    if let Some(ref mut trivia) = trivia {
+                        trivia.comments.push(Comment {index: acc.len(),
+                            text: a[start..i].iter().collect()});
+                    }
                     acc.push(Token {token_type: SymbolType::Separator,
                         value: Symbol::Newline, line, col,
                         item: Item::None});
@@ -598,12 +680,22 @@ pub fn scan(s: &str, line_start: usize, file: &str, new_line_start: bool)
                 }
             }
         }
+        if let Some(ref mut trivia) = trivia {
+            // Only '[..' yields two tokens, the first one is implicit.
+            for k in count..acc.len() {
+                trivia.text.push(if k+1 < acc.len() {String::new()}
+                    else {a[start..i.min(n)].iter().collect()});
+            }
+        }
     }
     acc.push(Token {
         token_type: SymbolType::Separator,
         value: Symbol::Terminal,
         line, col, item: Item::None
     });
+    if let Some(trivia) = trivia {
+        trivia.text.push(String::new());
+    }
     Ok(acc)
 }
 
@@ -694,19 +786,19 @@ fn print_token(x: &Token){
                 Item::Int(i) => {
                     print!("[{}]", i);
                 },
-                _ => compiler_error()
+                _ => print!("[?]")
             }
         },
         SymbolType::Imag => {
             print!("[{}i]", match x.item {
                 Item::String(ref s) => s,
-                _ => compiler_error()
+                _ => "?"
             });
         },
         SymbolType::String => {
             print!("[\"{}\"]", match x.item {
                 Item::String(ref s) => s,
-                _ => compiler_error()
+                _ => "?"
             });
         },
         SymbolType::Operator | SymbolType::Separator |
@@ -734,21 +826,19 @@ fn print_ast(t: &AST, indent: usize){
             match t.info {
                 Info::Int(x) => {println!("{}", x);},
                 Info::Long => {
-                    println!("long {}", match t.s {
-                        Some(ref s) => s, None => compiler_error()
-                    });
+                    println!("long {}", t.s.as_deref().unwrap_or("?"));
                 },
-                _ => compiler_error()
+                _ => println!("?")
             };
         },
         SymbolType::Identifier | SymbolType::Float => {
-            println!("{}", match t.s {Some(ref s) => s, None => compiler_error()});
+            println!("{}", t.s.as_deref().unwrap_or("?"));
         },
         SymbolType::Imag => {
-            println!("{}i", match t.s {Some(ref s) => s, None => compiler_error()});
+            println!("{}i", t.s.as_deref().unwrap_or("?"));
         },
         SymbolType::String => {
-            println!("\"{}\"", match t.s {Some(ref s) => s, None => compiler_error()});
+            println!("\"{}\"", t.s.as_deref().unwrap_or("?"));
         },
         SymbolType::Operator | SymbolType::Separator |
         SymbolType::Keyword  | SymbolType::Bool | SymbolType::Assignment => {
@@ -761,7 +851,7 @@ fn print_ast(t: &AST, indent: usize){
                 println!("{}", symbol_to_string(t.value));
             }
         },
-        _ => {compiler_error();}
+        _ => {println!("?");}
     }
     if let Some(ref a) = t.a {
         for x in a.iter() {
@@ -774,7 +864,11 @@ fn scan_line(line_start: usize, h: &mut system::History, new_line_start: bool)
 -> Result<Vec<Token>,Error>
 {
     let input = match system::getline_history("| ",h) {
-        Ok(x) => x, _ => panic!()
+        Ok(x) => x,
+        Err(_) => return Err(Box::new(EnumError::Syntax(SyntaxError {
+            line: line_start, col: 1, file: String::from("command line"),
+            s: String::from("unexpected end of input.")
+        })))
     };
     h.append(&input);
     scan(&input, line_start, "command line", new_line_start)
@@ -806,12 +900,14 @@ mod var_tab {
     pub struct VarInfo {
         pub s: String,
         pub var_type: VarType,
+        pub cell: bool,
         index: usize
     }
     pub struct VarTab {
         pub context: Option<Box<VarTab>>,
         list: Vec<VarInfo>,
         fn_id: Option<String>,
+        cells: Vec<String>,
         count_local: usize,
         count_arg: usize,
         count_context: usize,
@@ -822,15 +918,37 @@ mod var_tab {
         pub fn new(id: Option<String>) -> VarTab {
             VarTab {
                 context: None, list: Vec::new(), fn_id: id,
+                cells: Vec::new(),
                 count_local: 0, count_arg: 0, count_context: 0,
                 count_global: 0, count_optional_arg: 0
             }
         }
+        pub fn set_cells(&mut self, cells: Vec<String>) {
+            self.cells = cells;
+        }
+        pub fn cells(&self) -> &[String] {
+            &self.cells
+        }
+        // The cells of the enclosing functions.
+        pub fn context_cells(&self) -> Vec<String> {
+            let mut acc = Vec::new();
+            let mut context = &self.context;
+            while let Some(ref vtab) = *context {
+                acc.extend(vtab.cells.iter().cloned());
+                context = &vtab.context;
+            }
+            acc
+        }
+        fn is_cell(&self, identifier: &str) -> bool {
+            self.cells.iter().any(|x| x == identifier)
+        }
         pub fn push_argument(&mut self, identifier: String) {
+            let cell = self.is_cell(&identifier);
             self.list.push(VarInfo {
                 s: identifier,
                 index: self.count_arg,
-                var_type: VarType::Argument
+                var_type: VarType::Argument,
+                cell
             });
             self.count_arg += 1;
         }
@@ -838,23 +956,27 @@ mod var_tab {
             self.list.push(VarInfo {
                 s: identifier,
                 index: self.count_global,
-                var_type: VarType::Global
+                var_type: VarType::Global,
+                cell: false
             });
             self.count_global += 1;
         }
         pub fn push_local(&mut self, identifier: String) {
+            let cell = self.is_cell(&identifier);
             self.list.push(VarInfo{
                 s: identifier,
                 index: self.count_local,
-                var_type: VarType::Local
+                var_type: VarType::Local,
+                cell
             });
             self.count_local += 1;
         }
-        pub fn push_context(&mut self, identifier: String) {
+        pub fn push_context(&mut self, identifier: String, cell: bool) {
             self.list.push(VarInfo {
                 s: identifier,
                 index: self.count_context,
-                var_type: VarType::Context
+                var_type: VarType::Context,
+                cell
             });
             self.count_context += 1;
         }
@@ -862,24 +984,27 @@ mod var_tab {
             self.push_argument(identifier);
             self.count_optional_arg += 1;
         }
-        pub fn index_type(&mut self, id: &str) -> Option<(usize,VarType)> {
+
+        // The flag tells whether the variable is a shared cell
+        // that has to be dereferenced on access.
+        pub fn index_type(&mut self, id: &str) -> Option<(usize,VarType,bool)> {
             if let Some(ref s) = self.fn_id {
-                if id == s {return Some((0,VarType::FnId));}
+                if id == s {return Some((0,VarType::FnId,false));}
             }
             {
                 for info in &self.list {
                     if info.s == id {
-                        return Some((info.index, info.var_type));
+                        return Some((info.index, info.var_type, info.cell));
                     }
                 }
             }
             if let Some(ref mut context) = self.context {
-                if let Some((_,var_type)) = context.index_type(id) {
+                if let Some((_,var_type,cell)) = context.index_type(id) {
                     if var_type == VarType::Global {
                         return None;
                     }
-                    self.push_context(id.to_string());
-                    Some((self.count_context - 1, VarType::Context))
+                    self.push_context(id.to_string(),cell);
+                    Some((self.count_context - 1, VarType::Context, cell))
                 } else {
                     None
                 }
@@ -905,6 +1030,139 @@ mod var_tab {
 
 use var_tab::{VarType, VarTab};
 
+fn push_names(t: &AST, v: &mut Vec<String>) {
+    if t.symbol_type == SymbolType::Identifier {
+        if let Some(ref s) = t.s {
+            if !v.contains(s) {v.push(s.clone());}
+        }
+    } else if let Some(ref a) = t.a {
+        if t.value == Symbol::List {
+            for x in a.iter() {push_names(x, v);}
+        } else if t.value == Symbol::Map {
+            for x in a.iter().step_by(2) {push_names(x, v);}
+        }
+    }
+}
+
+fn argument_names(t: &AST, v: &mut Vec<String>) {
+    if let Some(ref a) = t.a {
+        for x in a.iter() {
+            if x.value == Symbol::Assignment {
+                push_names(&ast_argv(x)[0], v);
+            } else {
+                push_names(x, v);
+            }
+        }
+    }
+}
+
+fn is_assignment(t: &AST) -> bool {
+    (t.symbol_type == SymbolType::Operator && t.value == Symbol::Assignment)
+    || t.symbol_type == SymbolType::Assignment
+}
+
+// Collects the variables bound by the body of a function,
+// not looking into nested functions.
+fn bound_variables(t: &AST, v: &mut Vec<String>, globals: &mut Vec<String>) {
+    let a = match t.a {Some(ref a) => a, None => return};
+    if t.symbol_type == SymbolType::Keyword {
+        match t.value {
+            Symbol::Fn => return,
+            Symbol::Global => {
+                for x in a.iter() {push_names(x, globals);}
+                return;
+            },
+            Symbol::For | Symbol::Catch => push_names(&a[0], v),
+            _ => {}
+        }
+    } else if is_assignment(t) {
+        push_names(&a[0], v);
+    }
+    for x in a.iter() {
+        bound_variables(x, v, globals);
+    }
+}
+
+// Collects the variables assigned by nested functions. Loop
+// variables and exception variables are not taken into account,
+// as a comprehension should not overwrite a variable of the
+// enclosing function.
+fn nested_assignments(t: &AST, nested: bool,
+    shadow: &mut Vec<String>, v: &mut Vec<String>
+) {
+    let a = match t.a {Some(ref a) => a, None => return};
+    if t.symbol_type == SymbolType::Keyword && t.value == Symbol::Fn {
+        let n = shadow.len();
+        shadow.push("self".to_string());
+        argument_names(&a[0], shadow);
+        nested_assignments(&a[1], true, shadow, v);
+        shadow.truncate(n);
+        return;
+    }
+    if nested {
+        if t.symbol_type == SymbolType::Keyword && t.value == Symbol::Global {
+            for x in a.iter() {push_names(x, shadow);}
+        } else if is_assignment(t) {
+            let mut names = Vec::new();
+            push_names(&a[0], &mut names);
+            for s in names {
+                if !shadow.contains(&s) && !v.contains(&s) {v.push(s);}
+            }
+        }
+    }
+    for x in a.iter() {
+        nested_assignments(x, nested, shadow, v);
+    }
+}
+
+// The variables of a function that are assigned by a nested
+// function. These are stored in cells shared by reference.
+// An assignment to a cell of an enclosing function is not
+// a binding, unless an argument shadows the cell.
+fn cell_variables(args: &AST, body: &AST, context_cells: &[String]) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut bound = Vec::new();
+    let mut globals = Vec::new();
+    argument_names(args, &mut arguments);
+    bound_variables(body, &mut bound, &mut globals);
+    bound.retain(|s| !context_cells.contains(s));
+    bound.extend(arguments);
+    let mut assigned = Vec::new();
+    nested_assignments(body, false, &mut Vec::new(), &mut assigned);
+    assigned.retain(|s| bound.contains(s) && !globals.contains(s));
+    assigned
+}
+
+// A function body that starts with a string literal followed
+// by further statements is documented by this string.
+fn docstring(body: &AST) -> Option<&AST> {
+    if body.symbol_type != SymbolType::Keyword || body.value != Symbol::Block {
+        return None;
+    }
+    let a = ast_argv(body);
+    if a.len() > 1 && a[0].value == Symbol::Statement {
+        let x = &ast_argv(&a[0])[0];
+        if x.symbol_type == SymbolType::String {return Some(x);}
+    }
+    None
+}
+
+// Removes the indentation that continuation lines of a
+// multiline docstring share.
+fn dedent(s: &str) -> String {
+    let mut lines = s.trim().lines();
+    let first = lines.next().unwrap_or("");
+    let rest: Vec<&str> = lines.collect();
+    let indent = rest.iter().filter(|x| !x.trim().is_empty())
+        .map(|x| x.len() - x.trim_start().len()).min().unwrap_or(0);
+    let mut acc = String::from(first.trim());
+    for line in rest {
+        acc.push('\n');
+        acc.push_str(if line.len() >= indent {&line[indent..]} else {line.trim_start()});
+    }
+    acc
+}
+
 pub struct JmpInfo {
     start: usize,
     breaks: Vec<usize>
@@ -940,6 +1198,10 @@ mod pool {
 
 use pool::Pool;
 
+pub mod outline;
+pub mod formatter;
+pub mod lint;
+
 pub struct Compilation<'a> {
     mode_cmd: bool,
     syntax_nesting: usize,
@@ -955,9 +1217,20 @@ pub struct Compilation<'a> {
     jmp_stack: Vec<JmpInfo>,
     coroutine: bool,
     for_nesting: usize,
-    debug_mode: bool
+    debug_mode: bool,
+
+    // The syntax errors the parser has recovered from.
+    errors: Vec<SyntaxError>,
+
+    // The position of the 'end' of each function body and
+    // begin-block, by the position of its first token.
+    ends: HashMap<(usize, usize), (usize, usize)>
 }
 
+// The nesting counters of the parser, restored when
+// it recovers from a syntax error.
+type ParserState = (usize, usize, bool, usize);
+
 struct TokenIterator {
     pub a: Rc<[Token]>,
     pub index: usize
@@ -1013,6 +1286,15 @@ impl TokenIterator {
     }
 }
 
+// The words 'enum' and 'record' are not reserved, they only
+// start a declaration if followed by an identifier.
+fn is_declaration(a: &[Token], index: usize, keyword: &str) -> bool {
+    let t = &a[index];
+    t.token_type == SymbolType::Identifier &&
+    matches!(t.item, Item::String(ref s) if s == keyword) &&
+    index+1 < a.len() && a[index+1].token_type == SymbolType::Identifier
+}
+
 fn operator(value: Symbol, a: Box<[Rc<AST>]>, line: usize, col: usize)
 -> Rc<AST>
 {
@@ -1102,6 +1384,23 @@ fn ast_node(line: usize, col: usize,
 
 impl<'a> Compilation<'a>{
 
+fn new(mode_cmd: bool, history: &'a mut system::History, file: &'a str,
+    debug_mode: bool
+) -> Self
+{
+    Compilation {
+        mode_cmd,
+        syntax_nesting: 0, parens: 0, statement: !mode_cmd,
+        history, file,
+        pool: Pool::new(),
+        bv_blocks: Vec::new(),
+        fn_indices: Vec::new(), vtab: VarTab::new(None),
+        function_nesting: 0, jmp_stack: Vec::new(),
+        coroutine: false, for_nesting: 0, debug_mode,
+        errors: Vec::new(), ends: HashMap::new()
+    }
+}
+
 #[inline(never)]
 fn syntax_error(&self, line: usize, col: usize, s: &str) -> Error{
     Box::new(EnumError::Syntax(SyntaxError {line, col,
@@ -1113,6 +1412,8 @@ fn unexpected_token(&mut self, t: &Token) -> Error{
         SymbolType::Identifier => String::from("unexpected token: identifier."),
         SymbolType::String => String::from("unexpected token: string literal."),
         SymbolType::Int => String::from("unexpected token: integer literal."),
+        _ if t.value == Symbol::Assignment => String::from(
+            "unexpected token: '='. Did you mean '=='?"),
         _ => format!("unexpected token: '{}'.",symbol_to_string(t.value))
     };
     Box::new(EnumError::Syntax(SyntaxError {
@@ -1319,10 +1620,123 @@ fn class_statement(&mut self, i: &mut TokenIterator, t0: &Token)
     self.expect_assignment(i)?;
     let map = self.expression(i)?;
     let fid = identifier("class", t0.line, t0.col);
-    let app = apply(t0.line, t0.col, Box::new([fid, name, parent, map]));
+
+    // A string literal as the first element of the slot map
+    // is the docstring of the class.
+    let a: &[Rc<AST>] = match map.a {Some(ref a) => a, None => &[]};
+    let app = if map.value == Symbol::Map && a.len() >= 2
+        && a[0].symbol_type == SymbolType::String
+        && a[1].symbol_type == SymbolType::None
+    {
+        let doc = a[0].clone();
+        let map = Rc::new(AST {line: map.line, col: map.col,
+            symbol_type: map.symbol_type, value: Symbol::Map,
+            info: Info::None, s: None, a: Some(a[2..].into())
+        });
+        apply(t0.line, t0.col, Box::new([fid, name, parent, map, doc]))
+    } else {
+        apply(t0.line, t0.col, Box::new([fid, name, parent, map]))
+    };
     Ok(assignment(t0.line, t0.col, id, app))
 }
 
+// (x, y, ...), the field names become string literals.
+fn field_list(&mut self, i: &mut TokenIterator, fields: &mut Vec<Rc<AST>>)
+-> Result<(),Error>
+{
+    i.index += 1;
+    loop {
+        let field = self.identifier(i)?;
+        i.index += 1;
+        let s = match field.s {Some(ref s) => s.clone(), None => unreachable!()};
+        fields.push(string(s, field.line, field.col));
+        let p = i.next_token(self)?;
+        let t = &p[i.index];
+        if t.value == Symbol::Comma {
+            i.index += 1;
+        } else if t.value == Symbol::PRight {
+            i.index += 1;
+            return Ok(());
+        } else {
+            return Err(self.syntax_error(t.line, t.col,
+                "expected ',' or ')'."));
+        }
+    }
+}
+
+// record Point(x, y)
+fn record_statement(&mut self, i: &mut TokenIterator, t0: &Token)
+-> ResultAST
+{
+    i.index += 1;
+    let id = self.identifier(i)?;
+    i.index += 1;
+    let name = match id.s {Some(ref s) => s.clone(), None => unreachable!()};
+    let (line, col) = (t0.line, t0.col);
+    let p = i.next_token(self)?;
+    let t = &p[i.index];
+    if t.value != Symbol::PLeft {
+        return Err(self.syntax_error(t.line, t.col, "expected '('."));
+    }
+    let mut fields = Vec::new();
+    self.field_list(i, &mut fields)?;
+    let fid = identifier("record_type", line, col);
+    let list = operator(Symbol::List, fields.into_boxed_slice(), line, col);
+    let app = apply(line, col, Box::new([fid, string(name, line, col), list]));
+    Ok(assignment(line, col, id, app))
+}
+
+// enum Shape
+//    Circle(r), Rect(w,h)
+//    Empty
+// end
+fn enum_statement(&mut self, i: &mut TokenIterator, t0: &Token)
+-> ResultAST
+{
+    i.index += 1;
+    let id = self.identifier(i)?;
+    i.index += 1;
+    let name = match id.s {Some(ref s) => s.clone(), None => unreachable!()};
+    let (line, col) = (t0.line, t0.col);
+    let mut variants: Vec<Rc<AST>> = Vec::new();
+    let mut bindings: Vec<Rc<AST>> = Vec::new();
+    loop {
+        let p = i.next_token(self)?;
+        let t = &p[i.index];
+        if t.value == Symbol::End {
+            i.index += 1;
+            break;
+        }
+        let vid = self.identifier(i)?;
+        i.index += 1;
+        let vname = match vid.s {Some(ref s) => s.clone(), None => unreachable!()};
+        let mut fields = vec![string(vname.clone(), vid.line, vid.col)];
+        let p = i.next_token_optional(self)?;
+        if p[i.index].value == Symbol::PLeft {
+            self.field_list(i, &mut fields)?;
+        }
+        variants.push(operator(Symbol::List, fields.into_boxed_slice(),
+            vid.line, vid.col));
+        bindings.push(self.qualification_assignment(&vname,
+            identifier(&name, line, col), &vname, vid.line, vid.col));
+        let p = i.next_token_optional(self)?;
+        let t = &p[i.index];
+        if t.value == Symbol::Comma {
+            i.index += 1;
+        }
+    }
+    let fid = identifier("enum", line, col);
+    let list = operator(Symbol::List, variants.into_boxed_slice(), line, col);
+    let app = apply(line, col, Box::new([fid,
+        string(name.clone(), line, col), list]));
+    let mut v = vec![assignment(line, col, id, app)];
+    v.append(&mut bindings);
+    Ok(Rc::new(AST{line, col,
+        symbol_type: SymbolType::Keyword,
+        value: Symbol::Block, info: Info::None,
+        s: None, a: Some(v.into_boxed_slice())}))
+}
+
 fn arguments_list(&mut self, i: &mut TokenIterator, t0: &Token, terminator: Symbol)
 -> ResultAST
 {
@@ -1408,7 +1822,7 @@ fn concise_function_literal(&mut self, i: &mut TokenIterator, t0: &Token)
         info: Info::None, s: None, a: Some(Box::new([args,x]))}))
 }
 
-fn function_body(&mut self, i: &mut TokenIterator, coroutine: bool)
+fn function_body(&mut self, i: &mut TokenIterator, t0: &Token, coroutine: bool)
 -> ResultAST
 {
     let statement = self.statement;
@@ -1425,8 +1839,9 @@ fn function_body(&mut self, i: &mut TokenIterator, coroutine: bool)
     let p = i.next_token_optional(self)?;
     let t = &p[i.index];
     if t.value != Symbol::End {
-        return Err(self.syntax_error(t.line, t.col, "expected 'end'."));
+        return Err(self.expected_end(t, t0.value, t0.line));
     }
+    self.ends.insert((t0.line, t0.col), (t.line, t.col));
     self.parens = parens;
     self.syntax_nesting -= 1;
     i.index += 1;
@@ -1468,7 +1883,7 @@ fn function_literal(&mut self, i: &mut TokenIterator, t0: &Token)
     };
     let args = self.arguments_list(i,t0,Symbol::Vline)?;
 
-    let body = self.function_body(i,coroutine)?;
+    let body = self.function_body(i,t0,coroutine)?;
     Ok(Rc::new(AST {line: t0.line, col: t0.col,
         symbol_type: SymbolType::Keyword, value: Symbol::Fn,
         info, s: id, a: Some(Box::new([args,body]))}))
@@ -1480,9 +1895,9 @@ fn function_statement(&mut self, i: &mut TokenIterator, t0: &Token)
     let (info,coroutine) = self.coroutine_info(i)?;
     let p = i.next_token(self)?;
     let t = &p[i.index];
-    let id = if t.token_type == SymbolType::Identifier {
+    let (id, id_line, id_col) = if t.token_type == SymbolType::Identifier {
         i.index += 1;
-        t.item.assert_string().clone()
+        (t.item.assert_string().clone(), t.line, t.col)
     } else {
         return Err(self.syntax_error(t.line, t.col, "expected identifier."));
     };
@@ -1496,9 +1911,9 @@ fn function_statement(&mut self, i: &mut TokenIterator, t0: &Token)
 
     let args = self.arguments_list(i,t0,Symbol::PRight)?;
 
-    let body = self.function_body(i,coroutine)?;
+    let body = self.function_body(i,t0,coroutine)?;
 
-    let lhs = identifier(&id,t.line,t.col);
+    let lhs = identifier(&id,id_line,id_col);
     let y = Rc::new(AST {line: t0.line, col: t0.col,
         symbol_type: SymbolType::Keyword, value: Symbol::Fn,
         info, s: Some(id), a: Some(Box::new([args,body]))
@@ -1596,8 +2011,9 @@ fn block(&mut self, i: &mut TokenIterator, t0: &Token) -> ResultAST {
     let p = i.next_token_optional(self)?;
     let t = &p[i.index];
     if t.value != Symbol::End {
-        return Err(self.syntax_error(t.line, t.col, "expected 'end'."));
+        return Err(self.expected_end(t, t0.value, t0.line));
     }
+    self.ends.insert((t0.line, t0.col), (t.line, t.col));
     self.parens = parens;
     self.syntax_nesting -= 1;
     i.index += 1;
@@ -2167,6 +2583,13 @@ fn assignment(&mut self, i: &mut TokenIterator) -> ResultAST {
     }
 }
 
+// A common mistake is '=' instead of '==' in a condition.
+fn expected_after_condition(&self, t: &Token, keyword: Symbol) -> Error {
+    let hint = if t.value == Symbol::Assignment {" Did you mean '=='?"} else {""};
+    self.syntax_error(t.line, t.col, &format!("expected '{}' or a line break.{}",
+        symbol_to_string(keyword), hint))
+}
+
 fn while_statement(&mut self, i: &mut TokenIterator) -> ResultAST {
     let condition = self.expression(i)?;
     let p = i.next_any_token(self)?;
@@ -2174,7 +2597,7 @@ fn while_statement(&mut self, i: &mut TokenIterator) -> ResultAST {
     if t.value == Symbol::Do || t.value == Symbol::Newline {
         i.index += 1;
     } else {
-        return Err(self.syntax_error(t.line, t.col, "expected 'do' or a line break."));
+        return Err(self.expected_after_condition(t, Symbol::Do));
     }
     let body = self.statements(i,Value::None)?;
     Ok(Rc::new(AST {line: t.line, col: t.col,
@@ -2225,8 +2648,7 @@ fn for_statement(&mut self, i: &mut TokenIterator) -> ResultAST {
     if t.value == Symbol::Do || t.value == Symbol::Newline {
         i.index += 1;
     } else {
-        return Err(self.syntax_error(t.line, t.col,
-            "expected 'do' or a line break."));
+        return Err(self.expected_after_condition(t, Symbol::Do));
     }
     let body = self.statements(i, Value::None)?;
     Ok(Rc::new(AST{line: t.line, col: t.col,
@@ -2245,9 +2667,7 @@ fn if_statement(&mut self, i: &mut TokenIterator, t0: &Token)
     if t.value == Symbol::Then || t.value == Symbol::Newline {
         i.index += 1;
     } else {
-        return Err(self.syntax_error(t.line, t.col,
-            "expected 'then' or a line break."
-        ));
+        return Err(self.expected_after_condition(t, Symbol::Then));
     }
     let body = self.statements(i,Value::None)?;
     v.push(condition);
@@ -2263,8 +2683,7 @@ fn if_statement(&mut self, i: &mut TokenIterator, t0: &Token)
             if t.value == Symbol::Then || t.value == Symbol::Newline {
                 i.index += 1;
             } else {
-                return Err(self.syntax_error(t.line, t.col,
-                    "expected 'then' or a line break."));
+                return Err(self.expected_after_condition(t, Symbol::Then));
             }
             let body = self.statements(i,Value::None)?;
             v.push(condition);
@@ -2660,115 +3079,15 @@ fn statements(&mut self, i: &mut TokenIterator, last_value: Value)
         } else if t.value == Symbol::Terminal {
             break;
         }
-        let value = t.value;
-        if t.token_type == SymbolType::Keyword {
-            if value == Symbol::While {
-                i.index += 1;
-                let statement = self.statement;
-                self.statement = true;
-                self.syntax_nesting += 1;
-                let x = self.while_statement(i)?;
-                self.syntax_nesting -= 1;
-                self.statement = statement;
-                v.push(x);
-                let p = i.next_token_optional(self)?;
-                let t = &p[i.index];
-                if t.value != Symbol::End {
-                    return Err(self.syntax_error(t.line, t.col, "expected 'end'."));
-                }
-                i.index += 1;
-                self.end_of(i,Symbol::While)?;
-            } else if value == Symbol::For {
-                i.index += 1;
-                let statement = self.statement;
-                self.statement = true;
-                self.syntax_nesting += 1;
-                let x = self.for_statement(i)?;
-                self.syntax_nesting -= 1;
-                self.statement = statement;
-                v.push(x);
-                let p = i.next_token_optional(self)?;
-                let t = &p[i.index];
-                if t.value != Symbol::End {
-                    return Err(self.syntax_error(t.line, t.col, "expected 'end'."));
-                }
-                i.index+=1;
-                self.end_of(i,Symbol::For)?;
-            } else if value == Symbol::If {
-                i.index += 1;
-                let statement = self.statement;
-                self.statement = true;
-                self.syntax_nesting += 1;
-                let x = self.if_statement(i,t)?;
-                self.syntax_nesting -= 1;
-                self.statement = statement;
-                v.push(x);
-                let p = i.next_token_optional(self)?;
-                let t = &p[i.index];
-                if t.value != Symbol::End {
-                    return Err(self.syntax_error(t.line, t.col, "expected 'end'."));
-                }
-                i.index += 1;
-                self.end_of(i, Symbol::If)?;
-            } else if value == Symbol::End || value == Symbol::Elif ||
-                value == Symbol::Else || value == Symbol::Catch
-            {
-                break;
-            } else if value == Symbol::Return {
-                i.index += 1;
-                let x = self.return_statement(i,t,Symbol::Return)?;
-                v.push(x);
-            } else if value == Symbol::Function {
-                i.index += 1;
-                let x = self.function_statement(i,t)?;
-                v.push(x);
-            } else if value == Symbol::Break {
-                i.index += 1;
-                let x = atomic_literal(t.line,t.col,Symbol::Break);
-                v.push(x);
-            } else if value == Symbol::Continue {
-                i.index += 1;
-                let x = atomic_literal(t.line,t.col,Symbol::Continue);
-                v.push(x);
-            } else if value == Symbol::Yield {
-                i.index+= 1;
-                let x = self.return_statement(i,t,Symbol::Yield)?;
-                v.push(x);
-            } else if value == Symbol::Use {
-                i.index += 1;
-                let x = self.use_statement(i,t)?;
-                v.push(x);
-            } else if value == Symbol::Global {
-                i.index += 1;
-                let x = self.global_statement(i,t)?;
-                v.push(x);
-            } else if value == Symbol::Raise {
-                i.index += 1;
-                let x = self.raise_statement(i,t)?;
-                v.push(x);
-            } else if value == Symbol::Try {
-                i.index += 1;
-                let statement = self.statement;
-                self.statement = true;
-                self.syntax_nesting+=1;
-                let x = self.try_catch_statement(i,t)?;
-                self.syntax_nesting-=1;
-                self.statement = statement;
-                v.push(x);
-            } else if value == Symbol::Assert {
-                i.index += 1;
-                let x = self.assert_statement(i,t)?;
-                v.push(x);
-            } else if value == Symbol::Class {
-                let x = self.class_statement(i,t)?;
-                v.push(x);
-            } else {
-                let x = self.assignment(i)?;
-                v.push(x);
-            }
-        } else {
-            let x = self.assignment(i)?;
-            v.push(x);
+        if t.token_type == SymbolType::Keyword && matches!(t.value,
+            Symbol::End | Symbol::Elif | Symbol::Else | Symbol::Catch)
+        {
+            break;
+        }
+        let start = i.index;
+        let state = self.parser_state();
+        if let Err(e) = self.statement(i, &mut v) {
+            self.recover(i, start, e, state)?;
         }
         let p = i.next_any_token(self)?;
         let t = &p[i.index];
@@ -2781,7 +3100,9 @@ fn statements(&mut self, i: &mut TokenIterator, last_value: Value)
         } else if value == Symbol::Semicolon || value == Symbol::Newline {
             i.index += 1;
         } else {
-            return Err(self.unexpected_token(&t));
+            let e = self.unexpected_token(t);
+            let (start, state) = (i.index, self.parser_state());
+            self.recover(i, start, e, state)?;
         }
     }
     if last_value != Value::None {
@@ -2810,17 +3131,244 @@ fn statements(&mut self, i: &mut TokenIterator, last_value: Value)
     }
 }
 
-fn ast(&mut self, i: &mut TokenIterator, value: Value) -> ResultAST {
-    let y = self.statements(i, value)?;
+// Parses a statement and appends it to v.
+fn statement(&mut self, i: &mut TokenIterator, v: &mut Vec<Rc<AST>>)
+-> Result<(),Error>
+{
     let p = i.next_any_token(self)?;
     let t = &p[i.index];
-    if t.value == Symbol::Terminal {
+    let line = t.line;
+    let value = t.value;
+    if t.token_type == SymbolType::Keyword {
+        if value == Symbol::While {
+            i.index += 1;
+            let statement = self.statement;
+            self.statement = true;
+            self.syntax_nesting += 1;
+            let x = self.while_statement(i)?;
+            self.syntax_nesting -= 1;
+            self.statement = statement;
+            v.push(x);
+            let p = i.next_token_optional(self)?;
+            let t = &p[i.index];
+            if t.value != Symbol::End {
+                return Err(self.expected_end(t, value, line));
+            }
+            i.index += 1;
+            self.end_of(i,Symbol::While)?;
+        } else if value == Symbol::For {
+            i.index += 1;
+            let statement = self.statement;
+            self.statement = true;
+            self.syntax_nesting += 1;
+            let x = self.for_statement(i)?;
+            self.syntax_nesting -= 1;
+            self.statement = statement;
+            v.push(x);
+            let p = i.next_token_optional(self)?;
+            let t = &p[i.index];
+            if t.value != Symbol::End {
+                return Err(self.expected_end(t, value, line));
+            }
+            i.index+=1;
+            self.end_of(i,Symbol::For)?;
+        } else if value == Symbol::If {
+            i.index += 1;
+            let statement = self.statement;
+            self.statement = true;
+            self.syntax_nesting += 1;
+            let x = self.if_statement(i,t)?;
+            self.syntax_nesting -= 1;
+            self.statement = statement;
+            v.push(x);
+            let p = i.next_token_optional(self)?;
+            let t = &p[i.index];
+            if t.value != Symbol::End {
+                return Err(self.expected_end(t, value, line));
+            }
+            i.index += 1;
+            self.end_of(i, Symbol::If)?;
+        } else if value == Symbol::Return {
+            i.index += 1;
+            let x = self.return_statement(i,t,Symbol::Return)?;
+            v.push(x);
+        } else if value == Symbol::Function {
+            i.index += 1;
+            let x = self.function_statement(i,t)?;
+            v.push(x);
+        } else if value == Symbol::Break {
+            i.index += 1;
+            let x = atomic_literal(t.line,t.col,Symbol::Break);
+            v.push(x);
+        } else if value == Symbol::Continue {
+            i.index += 1;
+            let x = atomic_literal(t.line,t.col,Symbol::Continue);
+            v.push(x);
+        } else if value == Symbol::Yield {
+            i.index+= 1;
+            let x = self.return_statement(i,t,Symbol::Yield)?;
+            v.push(x);
+        } else if value == Symbol::Use {
+            i.index += 1;
+            let x = self.use_statement(i,t)?;
+            v.push(x);
+        } else if value == Symbol::Global {
+            i.index += 1;
+            let x = self.global_statement(i,t)?;
+            v.push(x);
+        } else if value == Symbol::Raise {
+            i.index += 1;
+            let x = self.raise_statement(i,t)?;
+            v.push(x);
+        } else if value == Symbol::Try {
+            i.index += 1;
+            let statement = self.statement;
+            self.statement = true;
+            self.syntax_nesting+=1;
+            let x = self.try_catch_statement(i,t)?;
+            self.syntax_nesting-=1;
+            self.statement = statement;
+            v.push(x);
+        } else if value == Symbol::Assert {
+            i.index += 1;
+            let x = self.assert_statement(i,t)?;
+            v.push(x);
+        } else if value == Symbol::Class {
+            let x = self.class_statement(i,t)?;
+            v.push(x);
+        } else {
+            let x = self.assignment(i)?;
+            v.push(x);
+        }
+    } else if is_declaration(&p, i.index, "enum") {
+        self.syntax_nesting += 1;
+        let x = self.enum_statement(i,t)?;
+        self.syntax_nesting -= 1;
+        v.push(x);
+    } else if is_declaration(&p, i.index, "record") {
+        let x = self.record_statement(i,t)?;
+        v.push(x);
+    } else {
+        let x = self.assignment(i)?;
+        v.push(x);
+    }
+    Ok(())
+}
+
+fn expected_end(&self, t: &Token, keyword: Symbol, line: usize) -> Error {
+    let s = if t.value == Symbol::Terminal {
+        format!("expected 'end' of '{}' from line {}, but the input ends here. Is an 'end' missing?",
+            symbol_to_string(keyword), line)
+    } else {
+        format!("expected 'end' of '{}' from line {}.", symbol_to_string(keyword), line)
+    };
+    self.syntax_error(t.line, t.col, &s)
+}
+
+fn parser_state(&self) -> ParserState {
+    (self.syntax_nesting, self.parens, self.statement, self.function_nesting)
+}
+
+// Skips a statement after a syntax error, together with the
+// blocks it opens, so that parsing resumes at the next statement.
+fn skip_statement(&self, i: &mut TokenIterator) {
+    let a = i.a.clone();
+    let mut blocks: usize = 0;
+    let mut brackets: usize = 0;
+    let mut start = true;
+    let mut first = true;
+    while i.index < a.len() {
+        let t = &a[i.index];
+        let value = t.value;
+        if value == Symbol::Terminal {break;}
+        let keyword = t.token_type == SymbolType::Keyword;
+        let boundary = matches!(value, Symbol::Newline | Symbol::Semicolon |
+            Symbol::PRight | Symbol::BRight | Symbol::CRight) ||
+            keyword && matches!(value, Symbol::End | Symbol::Elif | Symbol::Else | Symbol::Catch);
+        if !first && brackets == 0 && blocks == 0 && boundary {break;}
+        match value {
+            Symbol::PLeft | Symbol::BLeft | Symbol::CLeft => brackets += 1,
+            Symbol::PRight | Symbol::BRight | Symbol::CRight => {
+                brackets = brackets.saturating_sub(1);
+            },
+            Symbol::Fn | Symbol::Function | Symbol::Begin if keyword && brackets == 0
+                && (i.index == 0 || a[i.index-1].value != Symbol::Of) => blocks += 1,
+            Symbol::While | Symbol::For | Symbol::If | Symbol::Try
+                if keyword && brackets == 0 && start => blocks += 1,
+            Symbol::End if keyword && brackets == 0 => {
+                blocks = blocks.saturating_sub(1);
+            },
+            _ => {}
+        }
+        start = matches!(value, Symbol::Newline | Symbol::Semicolon |
+            Symbol::Then | Symbol::Do | Symbol::Else);
+        first = false;
+        i.index += 1;
+    }
+}
+
+// Records a syntax error of the statement beginning at start
+// and skips the statement. In interactive mode, or after too
+// many errors, parsing stops with the errors found so far.
+fn recover(&mut self, i: &mut TokenIterator, start: usize, e: Error,
+    state: ParserState
+) -> Result<(),Error>
+{
+    const MAX_ERRORS: usize = 20;
+    if self.mode_cmd || matches!(*e, EnumError::Several(_)) {
+        return Err(e);
+    }
+    let EnumError::Syntax(e) = *e else {unreachable!()};
+    if !self.errors.last().is_some_and(|x| x.line == e.line && x.col == e.col) {
+        self.errors.push(e);
+    }
+    if self.errors.len() >= MAX_ERRORS {
+        return Err(self.collected_errors());
+    }
+    (self.syntax_nesting, self.parens, self.statement, self.function_nesting) = state;
+    i.index = start;
+    self.skip_statement(i);
+    Ok(())
+}
+
+fn collected_errors(&mut self) -> Error {
+    let mut a = std::mem::take(&mut self.errors);
+    Box::new(if a.len() == 1 {
+        EnumError::Syntax(a.remove(0))
+    } else {
+        EnumError::Several(a)
+    })
+}
+
+fn ast(&mut self, i: &mut TokenIterator, value: Value) -> ResultAST {
+    let y = self.ast_recovered(i, value)?;
+    if self.errors.is_empty() {
         Ok(y)
     } else {
-        Err(self.syntax_error(t.line, t.col, "unexpected token."))
+        Err(self.collected_errors())
     }
 }
 
+// The syntax tree of the statements that could be parsed. The
+// errors recovered from are left in self.errors.
+fn ast_recovered(&mut self, i: &mut TokenIterator, value: Value) -> ResultAST {
+    let y = self.statements(i, value)?;
+    loop {
+        let p = i.next_any_token(self)?;
+        let t = &p[i.index];
+        if t.value == Symbol::Terminal {break;}
+        let e = if t.value == Symbol::End {
+            self.syntax_error(t.line, t.col, "unexpected 'end'. Is there an 'end' too many?")
+        } else {
+            self.syntax_error(t.line, t.col, "unexpected token.")
+        };
+        let (start, state) = (i.index, self.parser_state());
+        self.recover(i, start, e, state)?;
+        self.statements(i, Value::None)?;
+    }
+    Ok(y)
+}
+
 fn compile_operator(&mut self, bv: &mut Vec<u32>,
     t: &Rc<AST>, byte_code: u8
 ) -> Result<(),Error>
@@ -3007,6 +3555,8 @@ fn compile_try_catch(&mut self, bv: &mut Vec<u32>, t: &Rc<AST>)
 
     push_bc(bv, bc::OP, t.line, t.col);
     push_bc(bv, bc::GETEXC, t.line, t.col);
+    let index4 = bv.len();
+    push_i32(bv, DUMMY_IADDRESS);
     let c = ast_argv(&a[1]);
     self.compile_assignment(bv, &c[0], c[0].line, c[0].col)?;
     if c.len()==2 {
@@ -3028,6 +3578,7 @@ fn compile_try_catch(&mut self, bv: &mut Vec<u32>, t: &Rc<AST>)
     }
 
     write_pic_address(bv, index2);
+    write_pic_address(bv, index4);
     Ok(())
 }
 
@@ -3140,6 +3691,15 @@ fn compile_fn(&mut self, bv: &mut Vec<u32>, t: &Rc<AST>)
     let coroutine = self.coroutine;
     self.coroutine = matches!(t.info, Info::Coroutine);
 
+    // Variables assigned by nested functions are shared
+    // by reference. Coroutines keep their variables in the
+    // function context, thus they are not considered here.
+    if !self.coroutine {
+        let context_cells = self.vtab.context_cells();
+        self.vtab.set_cells(cell_variables(&a[0], &a[1], &context_cells));
+        self.cell_locals(&mut bv2, &a[0], t.line, t.col);
+    }
+
     // Compile the arguments.
     self.function_nesting+=1;
     self.arguments(&mut bv2,&a[0],selfarg)?;
@@ -3226,6 +3786,18 @@ fn compile_fn(&mut self, bv: &mut Vec<u32>, t: &Rc<AST>)
     // number of local variables
     push_u32(bv, var_count as u32);
 
+    if let Some(doc) = docstring(&a[1]) {
+        let s = match doc.s {Some(ref s) => s, None => unreachable!()};
+        let text = self.string_literal(s, doc.line, doc.col)?;
+        let name = match t.s {Some(ref s) => s.as_str(), None => ""};
+        let signature = outline::signature("", name, &a[0]);
+        let text = format!("{}\n{}", signature.trim_start(), dedent(&text));
+        let index = self.pool.get_index(&text);
+        push_bc(bv, bc::OP, t.line, t.col);
+        push_bc(bv, bc::DOC, t.line, t.col);
+        push_u32(bv, index as u32);
+    }
+
     // Append the code block to the buffer of code blocks.
     self.bv_blocks.append(&mut bv2);
 
@@ -3321,6 +3893,7 @@ fn arguments(&mut self, bv: &mut Vec<u32>, t: &AST, selfarg: bool)
         if x.symbol_type == SymbolType::Identifier {
             if let Some(ref s) = x.s {
                 self.vtab.push_argument(s.clone());
+                self.cell_argument(bv, s, x.line, x.col);
             } else {
                 unreachable!();
             }
@@ -3335,6 +3908,7 @@ fn arguments(&mut self, bv: &mut Vec<u32>, t: &AST, selfarg: bool)
             if u[0].symbol_type == SymbolType::Identifier {
                 if let Some(ref s) = u[0].s {
                     self.vtab.push_optional_argument(s.clone());
+                    self.cell_argument(bv, s, x.line, x.col);
                     self.compile_default_argument(bv,x)?;
                 } else {
                     unreachable!();
@@ -3351,10 +3925,45 @@ fn arguments(&mut self, bv: &mut Vec<u32>, t: &AST, selfarg: bool)
     Ok(())
 }
 
+fn cell_argument(&mut self, bv: &mut Vec<u32>, id: &str,
+    line: usize, col: usize
+) {
+    if let Some((index,VarType::Argument,true)) = self.vtab.index_type(id) {
+        push_bc(bv, bc::LOAD_ARG, line, col);
+        push_u32(bv, index as u32);
+        push_bc(bv, bc::CELL, line, col);
+        push_bc(bv, bc::STORE_ARG, line, col);
+        push_u32(bv, index as u32);
+    }
+}
+
+// Local variables that are shared with closures are
+// created as cells at the start of the function.
+fn cell_locals(&mut self, bv: &mut Vec<u32>, args: &AST,
+    line: usize, col: usize
+) {
+    let mut arguments = vec!["self".to_string()];
+    if let Some(ref a) = args.a {
+        for x in a.iter() {
+            let x = if x.value == Symbol::Assignment {&ast_argv(x)[0]} else {x};
+            if let Some(ref s) = x.s {arguments.push(s.clone());}
+        }
+    }
+    let cells: Vec<String> = self.vtab.cells().iter()
+        .filter(|s| !arguments.contains(s)).cloned().collect();
+    for s in cells {
+        push_bc(bv, bc::NULL, line, col);
+        push_bc(bv, bc::CELL, line, col);
+        push_bc(bv, bc::STORE_LOCAL, line, col);
+        push_u32(bv, self.vtab.count_local() as u32);
+        self.vtab.push_local(s);
+    }
+}
+
 fn compile_variable(&mut self, bv: &mut Vec<u32>, t: &AST) {
     let key = match t.s {Some(ref x) => x, None => unreachable!()};
     match self.vtab.index_type(key) {
-        Some((index, var_type)) => {
+        Some((index, var_type, cell)) => {
             match var_type {
                 VarType::Argument => {
                     push_bc(bv, bc::LOAD_ARG, t.line, t.col);
@@ -3377,6 +3986,9 @@ fn compile_variable(&mut self, bv: &mut Vec<u32>, t: &AST) {
                     push_bc(bv, bc::FNSELF, t.line, t.col);
                 }
             }
+            if cell {
+                push_bc(bv, bc::CELL_GET, t.line, t.col);
+            }
         },
         None => {
             let index = self.pool.get_index(key);
@@ -3393,7 +4005,18 @@ fn compile_assignment(&mut self, bv: &mut Vec<u32>, t: &AST,
     let key = match t.s {Some(ref x) => x, None => unreachable!()};
     if self.function_nesting > 0 {
         match self.vtab.index_type(key) {
-            Some((index,var_type)) => {
+            Some((index,var_type,true)) => {
+                let op = match var_type {
+                    VarType::Local => bc::LOAD_LOCAL,
+                    VarType::Argument => bc::LOAD_ARG,
+                    VarType::Context => bc::LOAD_CONTEXT,
+                    _ => unreachable!()
+                };
+                push_bc(bv, op, line, col);
+                push_u32(bv, index as u32);
+                push_bc(bv, bc::CELL_SET, line, col);
+            },
+            Some((index,var_type,false)) => {
                 match var_type {
                     VarType::Local => {
                         push_bc(bv, bc::STORE_LOCAL, line, col);
@@ -3423,7 +4046,7 @@ fn compile_assignment(&mut self, bv: &mut Vec<u32>, t: &AST,
                 if self.coroutine {
                     push_bc(bv, bc::STORE_CONTEXT, line, col);
                     push_u32(bv, self.vtab.count_context() as u32);
-                    self.vtab.push_context(key.clone());
+                    self.vtab.push_context(key.clone(),false);
                 } else {
                     push_bc(bv, bc::STORE_LOCAL, line, col);
                     push_u32(bv, self.vtab.count_local() as u32);
@@ -3461,11 +4084,12 @@ fn compile_compound_assignment(
         self.compile_ast(bv,&op)?;
         self.compile_assignment(bv, &a[0], t.line, t.col)?;
     } else {
-        let args_left = ast_argv(&a[0]);
-        self.compile_ast(bv,&args_left[0])?;
-        self.compile_ast(bv,&args_left[1])?;
-        self.compile_ast(bv,&a[1])?;
-
+        let subscript = a[0].value == Symbol::Index || a[0].value == Symbol::Dot;
+        if !subscript || ast_argv(&a[0]).len() != 2 {
+            return Err(self.syntax_error(a[0].line,a[0].col,
+                "cannot compound assign to this expression."
+            ));
+        }
         let op_code = match t.value {
             Symbol::APlus => bc::ADD,
             Symbol::AMinus => bc::SUB,
@@ -3475,18 +4099,19 @@ fn compile_compound_assignment(
             Symbol::AMod => bc::MOD,
             Symbol::AAmp => bc::BAND,
             Symbol::AVline => bc::BOR,
-            _ => unreachable!()
+            _ => return Err(self.syntax_error(t.line, t.col,
+                "this compound assignment is not supported for slots and elements."))
         };
+        let args_left = ast_argv(&a[0]);
+        self.compile_ast(bv,&args_left[0])?;
+        self.compile_ast(bv,&args_left[1])?;
+        self.compile_ast(bv,&a[1])?;
 
         push_bc(bv, bc::AOP, a[0].line, a[0].col);
         if a[0].value == Symbol::Index {
             push_bc(bv, bc::AOP_INDEX, a[0].line, a[0].col);
-        } else if a[0].value == Symbol::Dot {
-            push_bc(bv, bc::DOT, a[0].line, a[0].col);
         } else {
-            return Err(self.syntax_error(a[0].line,a[0].col,
-                "cannot compound assign to this expression."
-            ));
+            push_bc(bv, bc::DOT, a[0].line, a[0].col);
         }
         push_bc(bv, op_code, a[0].line, a[0].col);
     }
@@ -3501,7 +4126,9 @@ fn closure(&mut self, bv: &mut Vec<u32>, t: &AST){
     };
     for x in list {
         if x.var_type == VarType::Context {
-            if let Some((index,var_type)) = context.index_type(&x.s) {
+            // A cell is passed as is, so that the closure
+            // shares it with the enclosing function.
+            if let Some((index,var_type,_)) = context.index_type(&x.s) {
                 match var_type {
                     VarType::Local => {
                         push_bc(bv,bc::LOAD_LOCAL,t.line,t.col);
@@ -3630,6 +4257,13 @@ fn compile_string(&mut self, bv: &mut Vec<u32>, t: &AST)
     Ok(())
 }
 
+fn float_literal(&self, t: &AST) -> Result<f64,Error> {
+    match t.s.as_ref().map(|x| x.parse::<f64>()) {
+        Some(Ok(x)) => Ok(x),
+        _ => Err(self.syntax_error(t.line, t.col, "invalid number."))
+    }
+}
+
 fn compile_long(&mut self, bv: &mut Vec<u32>, t: &AST)
 -> Result<(),Error>
 {
@@ -3794,11 +4428,11 @@ fn compile_ast(&mut self, bv: &mut Vec<u32>, t: &Rc<AST>)
         };
     } else if t.symbol_type == SymbolType::Float {
         push_bc(bv, bc::FLOAT, t.line, t.col);
-        let x: f64 = match t.s {Some(ref x) => x.parse().unwrap(), None => unreachable!()};
+        let x = self.float_literal(t)?;
         push_u64(bv,x.to_bits());
     } else if t.symbol_type == SymbolType::Imag {
         push_bc(bv, bc::IMAG, t.line, t.col);
-        let x: f64 = match t.s {Some(ref x) => x.parse().unwrap(), None => unreachable!()};
+        let x = self.float_literal(t)?;
         push_u64(bv,x.to_bits());
     } else if t.symbol_type == SymbolType::Keyword {
         let value = t.value;
@@ -3981,10 +4615,21 @@ fn load_u64(a: &[u32]) -> u64 {
 }
 
 fn asm_listing(a: &[u32]) -> String {
+    asm_listing_from(a, 0, false)
+}
+
+// Listing of a single function body, that begins at address
+// and ends before the separator of the next code block.
+pub fn function_listing(a: &[u32], address: usize) -> String {
+    asm_listing_from(a, address, true)
+}
+
+fn asm_listing_from(a: &[u32], start: usize, single: bool) -> String {
     let mut acc = String::from("Adr | Line:Col| Operation\n");
-    let mut i = 0;
+    let mut i = start;
     while i < a.len() {
         let op = a[i] as u8;
+        if single && op == bc::FNSEP {break;}
         let line = ((a[i]>>8) & 0xffff) as u16;
         let col = (a[i]>>24) as u8;
         if op != bc::FNSEP {
@@ -4227,16 +4872,25 @@ fn asm_listing(a: &[u32]) -> String {
                     acc.push_str("try end\n");
                     i += BCSIZE;
                 } else if op==bc::GETEXC {
-                    acc.push_str("get exception\n");
-                    i += BCSIZE;
+                    let x = load_i32(&a[BCSIZE+i..BCSIZE+i+1]);
+                    let u = format!("get exception, end {}\n",i as i32+x);
+                    acc.push_str(&u);
+                    i += BCASIZE;
                 } else if op==bc::CRAISE {
                     acc.push_str("raise further\n");
                     i += BCSIZE;
+                } else if op==bc::DOC {
+                    let index = load_u32(&a[BCSIZE+i..BCSIZE+i+1]);
+                    acc.push_str(&format!("doc [{}]\n", index));
+                    i += BCASIZE;
                 } else {
                     unreachable!("op ??");
                 }
             },
             bc::APPLY => {acc.push_str("apply\n"); i += BCSIZE;}
+            bc::CELL => {acc.push_str("cell\n"); i += BCSIZE;},
+            bc::CELL_GET => {acc.push_str("cell get\n"); i += BCSIZE;},
+            bc::CELL_SET => {acc.push_str("cell set\n"); i += BCSIZE;},
             bc::HALT => {acc.push_str("halt\n"); i += BCSIZE;},
             _ => {unreachable!();}
         }
@@ -4320,16 +4974,7 @@ fn compile_token_vector(v: Vec<Token>, mode_cmd: bool, value: Value,
             false
         }
     };
-    let mut compilation = Compilation {
-        mode_cmd,
-        syntax_nesting: 0, parens: 0, statement: !mode_cmd,
-        history, file: id,
-        pool: Pool::new(),
-        bv_blocks: Vec::new(),
-        fn_indices: Vec::new(), vtab: VarTab::new(None),
-        function_nesting: 0, jmp_stack: Vec::new(),
-        coroutine: false, for_nesting: 0, debug_mode
-    };
+    let mut compilation = Compilation::new(mode_cmd, history, id, debug_mode);
     let mut i = TokenIterator{index: 0, a: Rc::from(v)};
     let y = compilation.ast(&mut i, value)?;
     // print_ast(&y,2);
@@ -4359,5 +5004,7 @@ pub fn compile(s: &str, id: &str, mode_cmd: bool, value: Value,
 ) -> Result<Rc<Module>,Box<EnumError>>
 {
     let v = scan(s,1,id,false)?;
-    compile_token_vector(v, mode_cmd, value, history, id, rte)
+    let module = compile_token_vector(v, mode_cmd, value, history, id, rte)?;
+    rte.sources.borrow_mut().insert(id.to_string(), Rc::from(s));
+    Ok(module)
 }
@@ -0,0 +1,452 @@
+
+// Formatter for Moss sources. It works on the token stream, so the
+// line structure of the source is kept, while indentation, the
+// spacing between tokens and overlong argument lists are normalized.
+
+use super::{
+    Token, Trivia, Symbol, SymbolType, Item, SyntaxError,
+    scan_with_trivia, format_error, format_syntax_error
+};
+use super::outline;
+
+const INDENT: &str = "   ";
+const MAX_WIDTH: usize = 80;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Frame {
+    Bracket(Symbol, usize),
+    Block(Symbol, usize),
+
+    // A begin-block around a whole module, which is not indented.
+    Module
+}
+
+impl Frame {
+    fn level(self) -> usize {
+        match self {
+            Frame::Bracket(_, level) | Frame::Block(_, level) => level,
+            Frame::Module => 0
+        }
+    }
+}
+
+struct Formatter<'a> {
+    v: &'a [Token],
+    trivia: &'a Trivia,
+    stack: Vec<Frame>,
+
+    // Inside the parameters of |x, y| ...
+    params: bool,
+
+    // The bars that delimit parameters, as opposed to the operator.
+    bars: Vec<bool>
+}
+
+fn item_text(t: &Token) -> &str {
+    match t.item {Item::String(ref s) => s, _ => ""}
+}
+
+fn is_operand_end(t: &Token) -> bool {
+    matches!(t.token_type,
+        SymbolType::Identifier | SymbolType::Int | SymbolType::Float |
+        SymbolType::Imag | SymbolType::String | SymbolType::Bool
+    ) || matches!(t.value,
+        Symbol::PRight | Symbol::BRight | Symbol::CRight |
+        Symbol::Null | Symbol::End
+    )
+}
+
+fn is_word(t: &Token) -> bool {
+    matches!(t.token_type, SymbolType::Keyword | SymbolType::Bool) ||
+    matches!(t.value,
+        Symbol::And | Symbol::Or | Symbol::Not | Symbol::In |
+        Symbol::Is | Symbol::Notin | Symbol::Isin
+    )
+}
+
+fn closing(value: Symbol) -> Option<Symbol> {
+    match value {
+        Symbol::PRight => Some(Symbol::PLeft),
+        Symbol::BRight => Some(Symbol::BLeft),
+        Symbol::CRight => Some(Symbol::CLeft),
+        _ => None
+    }
+}
+
+// Pieces of an output line: the text of a token and
+// whether it is separated from its predecessor by a space.
+struct Piece {
+    text: String,
+    space: bool,
+    value: Symbol,
+    comment: bool
+}
+
+impl<'a> Formatter<'a> {
+    fn text(&self, k: usize) -> &'a str {
+        let t = &self.v[k];
+        match t.value {
+            Symbol::Notin => "not in",
+            Symbol::Isin => "is in",
+            _ => &self.trivia.text[k]
+        }
+    }
+
+    // Tokens without source text, e.g. the null in '[..'.
+    fn implicit(&self, k: usize) -> bool {
+        self.trivia.text[k].is_empty() && self.v[k].value != Symbol::Terminal
+    }
+
+    fn previous(&self, k: usize) -> Option<usize> {
+        (0..k).rev().find(|&j| !self.implicit(j))
+    }
+
+    fn next(&self, k: usize) -> Option<usize> {
+        (k+1..self.v.len()).find(|&j| !self.implicit(j))
+    }
+
+    // Whether the source separates the tokens j and k by whitespace.
+    fn gap(&self, j: usize, k: usize) -> bool {
+        let (a, b) = (&self.v[j], &self.v[k]);
+        let text = &self.trivia.text[j];
+        a.line != b.line || text.contains('\n') ||
+        b.col > a.col + text.chars().count()
+    }
+
+    fn is_unary(&self, k: usize) -> bool {
+        matches!(self.v[k].value, Symbol::Minus | Symbol::Tilde | Symbol::Ast | Symbol::Not) &&
+        !matches!(self.previous(k), Some(j) if is_operand_end(&self.v[j]))
+    }
+
+    fn is_binary(&self, k: usize) -> bool {
+        let t = &self.v[k];
+        (t.token_type == SymbolType::Operator || t.token_type == SymbolType::Assignment)
+        && !is_word(t) && t.value != Symbol::Dot && !self.bars[k]
+        && !self.is_unary(k)
+    }
+
+    // The keyword of 'fn' or 'function', or the star of 'fn*'.
+    fn is_fn_keyword(&self, j: usize) -> bool {
+        match self.v[j].value {
+            Symbol::Fn | Symbol::Function => true,
+            Symbol::Ast => self.previous(j).is_some_and(|i|
+                matches!(self.v[i].value, Symbol::Fn | Symbol::Function)),
+            _ => false
+        }
+    }
+
+    fn opens_lambda(&self, k: usize) -> bool {
+        if self.v[k].value != Symbol::Vline || self.params {return false;}
+        match self.previous(k) {
+            None => true,
+            Some(j) => {
+                let p = &self.v[j];
+                if self.is_fn_keyword(j) {return true;}
+                if p.token_type == SymbolType::Identifier {
+                    return self.previous(j).is_some_and(|i| self.is_fn_keyword(i));
+                }
+                !is_operand_end(p)
+            }
+        }
+    }
+
+    fn starts_statement(&self, k: usize) -> bool {
+        match self.previous(k) {
+            Some(j) => matches!(self.v[j].value, Symbol::Newline | Symbol::Semicolon),
+            None => true
+        }
+    }
+
+    // A begin-block at the start of the module, with contents
+    // not indented in the source, is kept that way.
+    fn flush_begin(&self, k: usize) -> bool {
+        self.starts_statement(k) && (k+1..self.v.len()).find(|&j|
+            !self.implicit(j) && self.v[j].value != Symbol::Newline
+        ).is_some_and(|j| self.v[j].col == 1)
+    }
+
+    fn opens_block(&self, k: usize) -> bool {
+        let t = &self.v[k];
+        let previous = self.previous(k).map(|j| &self.v[j]);
+        if previous.is_some_and(|p| p.value == Symbol::Of) {return false;}
+        match t.value {
+            Symbol::Fn | Symbol::Function | Symbol::Begin |
+            Symbol::While | Symbol::Try => t.token_type == SymbolType::Keyword,
+            Symbol::If | Symbol::For => !previous.is_some_and(is_operand_end),
+            _ => t.token_type == SymbolType::Identifier && item_text(t) == "enum"
+                && self.next(k).is_some_and(|j| self.v[j].token_type == SymbolType::Identifier)
+                && self.starts_statement(k)
+        }
+    }
+
+    // The source column of the first token of the line k0..k1,
+    // if the line continues a statement outside of brackets,
+    // e.g. after the trailing comma of a long 'use'.
+    fn continuation(&self, k0: usize, k1: usize) -> Option<usize> {
+        if let Some(Frame::Bracket(..)) = self.stack.last() {return None;}
+        let k = (k0..k1).find(|&k| !self.implicit(k))?;
+        let j = self.previous(k).filter(|&j| self.v[j].value == Symbol::Newline)?;
+        let i = self.previous(j)?;
+        let t = &self.v[i];
+        if t.value == Symbol::Comma || matches!(t.value, Symbol::And | Symbol::Or)
+            || self.is_binary(i)
+        {
+            Some(self.v[k].col)
+        } else {
+            None
+        }
+    }
+
+    fn level(&self, first: Option<&Token>) -> usize {
+        let top = match self.stack.last() {Some(top) => *top, None => return 0};
+        let dedent = match (first.map(|t| t.value), top) {
+            (_, Frame::Module) => true,
+            (Some(value), Frame::Bracket(left, _)) => closing(value) == Some(left),
+            (Some(Symbol::End), Frame::Block(..)) => true,
+            (Some(Symbol::Elif), Frame::Block(Symbol::If, _)) => true,
+            (Some(Symbol::Else), Frame::Block(Symbol::If, _)) => true,
+            (Some(Symbol::Catch), Frame::Block(Symbol::Try, _)) => true,
+            _ => false
+        };
+        if dedent {top.level()} else {top.level() + 1}
+    }
+
+    fn update(&mut self, k: usize, level: usize) {
+        let t = &self.v[k];
+        match t.value {
+            Symbol::PLeft | Symbol::BLeft | Symbol::CLeft => {
+                self.stack.push(Frame::Bracket(t.value, level));
+            },
+            Symbol::PRight | Symbol::BRight | Symbol::CRight => {
+                let left = closing(t.value);
+                while let Some(frame) = self.stack.pop() {
+                    if let Frame::Bracket(value, _) = frame {
+                        if Some(value) == left {break;}
+                    }
+                }
+            },
+            Symbol::End if t.token_type == SymbolType::Keyword => {
+                if let Some(Frame::Block(..) | Frame::Module) = self.stack.last() {
+                    self.stack.pop();
+                }
+            },
+            Symbol::Begin if self.stack.is_empty() && self.flush_begin(k) => {
+                self.stack.push(Frame::Module);
+            },
+            _ => {
+                if self.opens_block(k) {
+                    self.stack.push(Frame::Block(t.value, level));
+                }
+            }
+        }
+    }
+
+    // Whether there is a space between the tokens j and k.
+    fn space(&self, j: usize, k: usize) -> bool {
+        let (p, t) = (&self.v[j], &self.v[k]);
+        if matches!(t.value, Symbol::Comma | Symbol::Semicolon | Symbol::Colon |
+            Symbol::PRight | Symbol::BRight | Symbol::CRight | Symbol::Dot)
+        || matches!(p.value, Symbol::PLeft | Symbol::BLeft | Symbol::CLeft | Symbol::Dot)
+        {
+            return false;
+        }
+        if matches!(p.value, Symbol::Comma | Symbol::Semicolon | Symbol::Colon) {
+            return self.gap(j, k);
+        }
+        if t.value == Symbol::Ast && self.is_fn_keyword(k) {
+            return false;
+        }
+        if self.is_fn_keyword(j) && p.value == Symbol::Ast {
+            return t.token_type == SymbolType::Identifier;
+        }
+        if self.is_unary(j) {
+            return p.value == Symbol::Not && (t.value != Symbol::PLeft || self.gap(j, k));
+        }
+        if self.bars[j] && self.params {
+            // The opening bar of the parameter list.
+            return false;
+        }
+        if self.bars[k] {
+            return !(self.params || self.is_fn_keyword(j) ||
+                p.token_type == SymbolType::Identifier);
+        }
+        if self.is_binary(k) || self.is_binary(j) {
+            // Spaced on both sides if the source has a space
+            // on either side.
+            let i = if self.is_binary(k) {k} else {j};
+            let before = self.previous(i).is_some_and(|h| self.gap(h, i));
+            let after = self.next(i).is_some_and(|h| self.gap(i, h));
+            return before || after;
+        }
+        if p.value == Symbol::Table || (t.value == Symbol::CLeft && is_operand_end(p)) {
+            return self.gap(j, k);
+        }
+        if matches!(t.value, Symbol::PLeft | Symbol::BLeft) {
+            return (!is_operand_end(p) || p.value == Symbol::End) && self.gap(j, k);
+        }
+        true
+    }
+
+    fn comments(&self, k: usize) -> impl Iterator<Item=&'a str> {
+        self.trivia.comments.iter().filter(move |c| c.index == k)
+            .map(|c| c.text.trim_end())
+    }
+
+    fn line(&mut self, k0: usize, k1: usize) -> (usize, Vec<Piece>) {
+        let first = (k0..k1).find(|&k| !self.implicit(k)).map(|k| &self.v[k]);
+        let level = self.level(first);
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut last: Option<usize> = None;
+        for k in k0..=k1 {
+            for c in self.comments(k) {
+                pieces.push(Piece {text: c.to_string(), space: !pieces.is_empty(),
+                    value: Symbol::None, comment: true});
+            }
+            if k == k1 || self.implicit(k) {continue;}
+            self.bars[k] = self.v[k].value == Symbol::Vline &&
+                (self.params || self.opens_lambda(k));
+            let space = match last {
+                Some(j) => self.space(j, k) || pieces.last().is_some_and(|p| p.comment),
+                None => false
+            };
+            pieces.push(Piece {text: self.text(k).to_string(), space,
+                value: self.v[k].value, comment: false});
+            if self.bars[k] {
+                self.params = !self.params;
+            }
+            self.update(k, level);
+            last = Some(k);
+        }
+        (level, pieces)
+    }
+}
+
+fn width(s: &str) -> usize {
+    s.chars().count()
+}
+
+fn join(pieces: &[Piece]) -> String {
+    let mut s = String::new();
+    for p in pieces {
+        if p.space {s.push(' ');}
+        s.push_str(&p.text);
+    }
+    s
+}
+
+// Breaks the first parenthesized list of the line that has
+// several elements, filling the continuation lines greedily.
+fn break_line(indent: &str, pieces: &[Piece]) -> Option<String> {
+    let start = pieces.iter().position(|p| p.value == Symbol::PLeft)?;
+    let mut depth = 0;
+    let mut breaks: Vec<usize> = Vec::new();
+    let mut end = None;
+    for (k, p) in pieces.iter().enumerate().skip(start) {
+        match p.value {
+            Symbol::PLeft | Symbol::BLeft | Symbol::CLeft => depth += 1,
+            Symbol::PRight | Symbol::BRight | Symbol::CRight => {
+                depth -= 1;
+                if depth == 0 {end = Some(k); break;}
+            },
+            Symbol::Comma if depth == 1 => breaks.push(k + 1),
+            _ => {}
+        }
+    }
+    if end.is_none() || breaks.is_empty() {return None;}
+    let continuation = format!("{}{}", indent, INDENT);
+    let mut s = format!("{}{}", indent, join(&pieces[..start+1]));
+    let mut segments = vec![start+1];
+    segments.extend(breaks.iter().cloned());
+    segments.push(pieces.len());
+    let mut column = width(&s);
+    for w in segments.windows(2) {
+        let text = join(&pieces[w[0]..w[1]]);
+        if column + width(&text) > MAX_WIDTH && w[0] != start+1 {
+            s.push('\n');
+            s.push_str(&continuation);
+            let text = text.trim_start();
+            s.push_str(text);
+            column = width(&continuation) + width(text);
+        } else {
+            s.push_str(&text);
+            column += width(&text);
+        }
+    }
+    Some(s)
+}
+
+fn significant(v: &[Token], trivia: &Trivia) -> Vec<String> {
+    v.iter().zip(trivia.text.iter())
+        .filter(|(t, _)| t.value != Symbol::Newline)
+        .map(|(_, s)| s.clone()).collect()
+}
+
+fn syntax_error(line: usize, col: usize, file: &str, s: String, source: &str) -> String {
+    format_syntax_error(&SyntaxError {line, col, file: file.to_string(), s}, source)
+}
+
+// Returns the formatted source. Sources with syntax
+// errors are refused, the error is returned instead.
+pub fn format(s: &str, id: &str) -> Result<String, String> {
+    if let Some(d) = outline::check(s, id) {
+        return Err(syntax_error(d.line, d.col, id, d.text, s));
+    }
+    let (v, trivia) = scan_with_trivia(s, id).map_err(|e| format_error(&e, s))?;
+    let mut f = Formatter {v: &v, trivia: &trivia, stack: Vec::new(), params: false,
+        bars: vec![false; v.len()]};
+    let mut out = String::new();
+    let mut blank = 0;
+
+    // The source column and the indentation of the line
+    // that begins the current statement.
+    let mut head = (1, String::new());
+    let mut k0 = 0;
+    while k0 < v.len() {
+        let k1 = (k0..v.len()).find(|&k|
+            matches!(v[k].value, Symbol::Newline | Symbol::Terminal)
+        ).unwrap_or(v.len() - 1);
+        let continuation = f.continuation(k0, k1);
+        let (level, pieces) = f.line(k0, k1);
+        if pieces.is_empty() {
+            if v[k1].value != Symbol::Terminal && !out.is_empty() {
+                blank += 1;
+            }
+        } else {
+            for _ in 0..blank.min(2) {out.push('\n');}
+            blank = 0;
+            // A continuation line keeps its indentation
+            // relative to the beginning of the statement.
+            let indent = match continuation {
+                Some(col) if col > head.0 => format!("{}{}", head.1, " ".repeat(col - head.0)),
+                Some(_) => format!("{}{}", head.1, INDENT),
+                None => {
+                    let indent = INDENT.repeat(level);
+                    if let Some(k) = (k0..k1).find(|&k| !f.implicit(k)) {
+                        head = (v[k].col, indent.clone());
+                    }
+                    indent
+                }
+            };
+            let line = format!("{}{}", indent, join(&pieces));
+            if width(&line) > MAX_WIDTH {
+                match break_line(&indent, &pieces) {
+                    Some(line) => out.push_str(&line),
+                    None => out.push_str(&line)
+                }
+            } else {
+                out.push_str(&line);
+            }
+            out.push('\n');
+        }
+        k0 = k1 + 1;
+    }
+
+    // Spacing and line breaks within brackets must not
+    // change the meaning, which is checked here.
+    let (w, wtrivia) = scan_with_trivia(&out, id).map_err(|e| format_error(&e, &out))?;
+    if significant(&v, &trivia) != significant(&w, &wtrivia) || outline::check(&out, id).is_some() {
+        return Err(syntax_error(1, 1, id,
+            String::from("formatting would change the program, source left unchanged."), ""));
+    }
+    Ok(out)
+}
@@ -0,0 +1,438 @@
+
+// A linter for Moss sources. The syntax tree is walked in the
+// order the compiler would walk it, and variables are resolved
+// by the compiler's own table of variables, thus a name is
+// undefined for the linter exactly if the compiled program
+// would look it up in the global variables in vain.
+
+use std::rc::Rc;
+use std::mem::replace;
+use std::collections::HashSet;
+
+use super::{
+    AST, Symbol, SymbolType, Info, ast_argv, is_assignment,
+    bound_variables, cell_variables, argument_names
+};
+use super::var_tab::{VarTab, VarType};
+use super::outline::{self, Diagnostic};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Binding {
+    Parameter, Local,
+
+    // Loop variables and exception variables, which are
+    // frequently unused on purpose.
+    Loop
+}
+
+struct Declaration {
+    name: String,
+    line: usize,
+    col: usize,
+    binding: Binding
+}
+
+// The bookkeeping for a function body. The first one
+// belongs to the main program.
+#[derive(Default)]
+struct Function {
+    declarations: Vec<Declaration>,
+    used: HashSet<String>,
+    imports: Vec<String>
+}
+
+struct Linter<'a> {
+    vtab: VarTab,
+    functions: Vec<Function>,
+    globals: Vec<String>,
+    known: &'a [String],
+
+    // Set if the global variables are extended at runtime by
+    // 'use m: *', which makes undefined names undecidable.
+    dynamic: bool,
+    diagnostics: Vec<Diagnostic>
+}
+
+fn children(t: &AST) -> &[Rc<AST>] {
+    match t.a {Some(ref a) => a, None => &[]}
+}
+
+fn name_of(t: &AST) -> &str {
+    match t.s {Some(ref s) => s, None => ""}
+}
+
+fn is_keyword(t: &AST, value: Symbol) -> bool {
+    t.symbol_type == SymbolType::Keyword && t.value == value
+}
+
+// The position of the leftmost token of a subtree.
+fn start(t: &AST) -> (usize, usize) {
+    children(t).iter().map(|x| start(x)).fold((t.line, t.col), |p, q| p.min(q))
+}
+
+fn is_jump(t: &AST) -> bool {
+    t.symbol_type == SymbolType::Keyword && matches!(t.value,
+        Symbol::Return | Symbol::Raise | Symbol::Break | Symbol::Continue)
+}
+
+// The parser closes a function body with the value that is
+// returned if the control flow reaches its end. This value
+// is placed at the position of the body.
+fn is_implicit_value(block: &AST, t: &AST) -> bool {
+    t.line == block.line && t.col == block.col
+    && t.symbol_type == SymbolType::Keyword
+    && (matches!(t.value, Symbol::Null | Symbol::Empty)
+        || (t.value == Symbol::Yield && children(t).is_empty()))
+}
+
+// The statements 'use m' and 'use m: x' are turned into
+// m = load("m") and x = m.x, the latter placed entirely
+// at the position of the name. Names taken from an object
+// by 'use(obj): x' are not considered, they are just local
+// copies.
+fn is_import(id: &AST, value: &AST, modules: &[String]) -> bool {
+    let a = children(value);
+    if value.value == Symbol::Application {
+        a.len() == 2 && name_of(&a[0]) == "load"
+        && a[1].symbol_type == SymbolType::String
+    } else if value.symbol_type == SymbolType::Operator && value.value == Symbol::Dot {
+        value.line == id.line && value.col == id.col && a.len() == 2
+        && a[0].symbol_type == SymbolType::Identifier
+        && modules.iter().any(|s| s == name_of(&a[0]))
+    } else {
+        false
+    }
+}
+
+fn declared_globals(t: &AST, v: &mut Vec<String>) {
+    if is_keyword(t, Symbol::Global) {
+        for x in children(t) {
+            if x.symbol_type == SymbolType::Identifier && !v.contains(&name_of(x).to_string()) {
+                v.push(name_of(x).to_string());
+            }
+        }
+    }
+    for x in children(t) {declared_globals(x, v);}
+}
+
+fn calls_gtab(t: &AST) -> bool {
+    let a = children(t);
+    (t.value == Symbol::Application && !a.is_empty()
+        && a[0].symbol_type == SymbolType::Identifier && name_of(&a[0]) == "gtab")
+    || a.iter().any(|x| calls_gtab(x))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let above = row[j+1];
+            row[j+1] = if x == *y {diagonal} else {1 + diagonal.min(above).min(row[j])};
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+impl<'a> Linter<'a> {
+    fn warn(&mut self, t: &AST, text: String) {
+        self.diagnostics.push(Diagnostic {line: t.line, col: t.col, text});
+    }
+
+    fn function(&mut self) -> &mut Function {
+        let n = self.functions.len();
+        &mut self.functions[n-1]
+    }
+
+    // Marks a variable as used in the function that owns it,
+    // following closure bindings outwards.
+    fn mark_used(&mut self, id: &str) {
+        let mut level = self.functions.len() - 1;
+        let mut tab = &self.vtab;
+        loop {
+            match tab.list().iter().find(|x| x.s == id).map(|x| x.var_type) {
+                Some(VarType::Local) | Some(VarType::Argument) => {
+                    self.functions[level].used.insert(id.to_string());
+                    return;
+                },
+                Some(VarType::Context) => {},
+                _ => return
+            }
+            tab = match tab.context {Some(ref context) => context, None => return};
+            level -= 1;
+        }
+    }
+
+    fn suggestion(&self, id: &str) -> Option<String> {
+        let mut candidates: Vec<&str> = Vec::new();
+        let mut tab = Some(&self.vtab);
+        while let Some(t) = tab {
+            candidates.extend(t.list().iter().map(|x| x.s.as_str()));
+            tab = t.context.as_deref();
+        }
+        candidates.extend(self.globals.iter().map(|s| s.as_str()));
+        candidates.extend(self.known.iter().map(|s| s.as_str()));
+        let limit = (id.chars().count()/3).max(1);
+        let mut best: Option<(usize, &str)> = None;
+        for s in candidates {
+            if s.starts_with('_') && !id.starts_with('_') {continue;}
+            let d = edit_distance(id, s);
+            if d <= limit && !matches!(best, Some((d0, _)) if d0 <= d) {
+                best = Some((d, s));
+            }
+        }
+        best.map(|(_, s)| s.to_string())
+    }
+
+    fn global_variable(&mut self, t: &AST) {
+        let id = name_of(t);
+        if self.dynamic || self.globals.iter().any(|s| s == id)
+            || self.known.iter().any(|s| s == id)
+        {
+            return;
+        }
+        let text = match self.suggestion(id) {
+            Some(s) => format!("undefined variable '{}', did you mean '{}'?", id, s),
+            None => format!("undefined variable '{}'.", id)
+        };
+        self.warn(t, text);
+    }
+
+    fn variable(&mut self, t: &AST) {
+        let id = name_of(t);
+        if self.functions.len() == 1 {
+            self.global_variable(t);
+            return;
+        }
+        match self.vtab.index_type(id) {
+            Some((_, VarType::FnId, _)) => {},
+            Some((_, VarType::Global, _)) | None => self.global_variable(t),
+            Some(_) => self.mark_used(id)
+        }
+    }
+
+    fn declare(&mut self, t: &AST, binding: Binding) {
+        let name = name_of(t).to_string();
+        self.function().declarations.push(Declaration {
+            name, line: t.line, col: t.col, binding
+        });
+    }
+
+    fn check_import(&mut self, t: &AST, imports: usize, import: bool) {
+        let id = name_of(t);
+        if !import && self.functions[imports].imports.iter().any(|s| s == id) {
+            self.warn(t, format!("assignment to '{}', which is bound by 'use'.", id));
+        }
+    }
+
+    fn assignment(&mut self, t: &AST, value: Option<&AST>, binding: Binding) {
+        let id = name_of(t).to_string();
+        let modules = self.functions.iter().flat_map(|f| f.imports.iter())
+            .cloned().collect::<Vec<String>>();
+        let import = value.is_some_and(|x| is_import(t, x, &modules));
+        if self.functions.len() == 1 {
+            self.check_import(t, 0, import);
+            if import {self.functions[0].imports.push(id);}
+            return;
+        }
+        let current = self.functions.len() - 1;
+        match self.vtab.index_type(&id) {
+            Some((_, VarType::FnId, _)) => {},
+            Some((_, VarType::Global, _)) => self.check_import(t, 0, import),
+            Some(_) => self.check_import(t, current, import),
+            None => {
+                self.vtab.push_local(id.clone());
+                self.declare(t, binding);
+                if import {self.function().imports.push(id);}
+            }
+        }
+    }
+
+    fn left_hand_side(&mut self, t: &AST, value: Option<&AST>, binding: Binding) {
+        if t.symbol_type == SymbolType::Identifier {
+            self.assignment(t, value, binding);
+        } else if t.value == Symbol::List {
+            for x in children(t) {
+                self.left_hand_side(x, None, binding);
+            }
+        } else if t.value == Symbol::Map {
+            // The keys are names, given as strings if a default
+            // value follows.
+            for x in children(t).chunks(2) {
+                if x.len() == 2 && x[1].symbol_type != SymbolType::None {
+                    self.visit(&x[1]);
+                }
+                self.assignment(&x[0], None, binding);
+            }
+        } else {
+            self.visit(t);
+        }
+    }
+
+    fn unreachable_code(&mut self, t: &AST) {
+        let a = children(t);
+        if let Some(k) = a.iter().position(|x| is_jump(x)) {
+            if k+1 < a.len() && !a[k+1..].iter().all(|x| is_implicit_value(t, x)) {
+                let (line, col) = start(&a[k+1]);
+                self.diagnostics.push(Diagnostic {line, col,
+                    text: String::from("unreachable code.")});
+            }
+        }
+    }
+
+    fn null_comparison(&mut self, t: &AST) {
+        if children(t).iter().any(|x| is_keyword(x, Symbol::Null)) {
+            let text = if t.value == Symbol::Eq {
+                "comparison with null, use 'is null'."
+            } else {
+                "comparison with null, use 'is not null'."
+            };
+            self.warn(t, String::from(text));
+        }
+    }
+
+    fn unused_variables(&mut self, f: &Function) {
+        for d in &f.declarations {
+            if d.binding == Binding::Loop || d.name.starts_with('_')
+                || f.used.contains(&d.name)
+            {
+                continue;
+            }
+            let kind = if d.binding == Binding::Parameter {"parameter"} else {"variable"};
+            self.diagnostics.push(Diagnostic {line: d.line, col: d.col,
+                text: format!("unused {} '{}'.", kind, d.name)});
+        }
+    }
+
+    fn fn_literal(&mut self, t: &AST) {
+        let a = ast_argv(t);
+        let selfarg = matches!(a[0].info, Info::Argv {selfarg: true, ..});
+
+        let vtab = replace(&mut self.vtab, VarTab::new(t.s.clone()));
+        self.vtab.context = Some(Box::new(vtab));
+        self.functions.push(Function::default());
+
+        if !matches!(t.info, Info::Coroutine) {
+            let mut arguments = vec!["self".to_string()];
+            argument_names(&a[0], &mut arguments);
+            let cells = cell_variables(&a[0], &a[1], &self.vtab.context_cells());
+            self.vtab.set_cells(cells.clone());
+            for s in cells {
+                if !arguments.contains(&s) {self.vtab.push_local(s);}
+            }
+        }
+        if !selfarg {
+            self.vtab.push_argument("self".to_string());
+        }
+        for (i, x) in children(&a[0]).iter().enumerate() {
+            if x.symbol_type == SymbolType::Identifier {
+                self.vtab.push_argument(name_of(x).to_string());
+                if !(selfarg && i == 0) {self.declare(x, Binding::Parameter);}
+            } else if x.value == Symbol::List || x.value == Symbol::Map {
+                self.vtab.push_argument(format!("_t{}_", i));
+                self.left_hand_side(x, None, Binding::Parameter);
+            } else if x.value == Symbol::Assignment {
+                let u = ast_argv(x);
+                self.vtab.push_argument(name_of(&u[0]).to_string());
+                self.declare(&u[0], Binding::Parameter);
+                self.visit(&u[1]);
+            }
+        }
+        self.visit(&a[1]);
+
+        if let Some(f) = self.functions.pop() {
+            self.unused_variables(&f);
+        }
+        if let Some(context) = self.vtab.context.take() {
+            self.vtab = *context;
+        }
+    }
+
+    fn visit(&mut self, t: &AST) {
+        let a = children(t);
+        if t.symbol_type == SymbolType::Identifier {
+            self.variable(t);
+        } else if t.symbol_type == SymbolType::Assignment {
+            // Compound assignment, the target is read first.
+            self.visit(&a[0]);
+            self.visit(&a[1]);
+            if a[0].symbol_type == SymbolType::Identifier {
+                self.assignment(&a[0], None, Binding::Local);
+            }
+        } else if is_assignment(t) {
+            self.visit(&a[1]);
+            self.left_hand_side(&a[0], Some(&a[1]), Binding::Local);
+        } else if t.symbol_type == SymbolType::Operator
+            && (t.value == Symbol::Eq || t.value == Symbol::Ne)
+        {
+            self.null_comparison(t);
+            for x in a {self.visit(x);}
+        } else if t.symbol_type == SymbolType::Keyword {
+            match t.value {
+                Symbol::Fn => self.fn_literal(t),
+                Symbol::For => {
+                    self.visit(&a[1]);
+                    self.left_hand_side(&a[0], None, Binding::Loop);
+                    self.visit(&a[2]);
+                },
+                Symbol::Try => {
+                    self.visit(&a[0]);
+                    let c = ast_argv(&a[1]);
+                    self.left_hand_side(&c[0], None, Binding::Loop);
+                    for x in &c[1..] {self.visit(x);}
+                },
+                Symbol::Global => {
+                    for x in a {
+                        self.vtab.push_global(name_of(x).to_string());
+                    }
+                },
+                Symbol::Block => {
+                    self.unreachable_code(t);
+                    for x in a {self.visit(x);}
+                },
+                _ => {
+                    for x in a {self.visit(x);}
+                }
+            }
+        } else {
+            for x in a {self.visit(x);}
+        }
+    }
+}
+
+// Reports undefined and misspelled global variables, unused
+// local variables and parameters, assignments to names bound
+// by 'use', unreachable statements and comparisons with null
+// by '=='. The names in 'known' are the predefined variables.
+// A program that does not compile results in an error.
+pub fn lint(s: &str, id: &str, known: &[String])
+-> Result<Vec<Diagnostic>, Diagnostic>
+{
+    if let Some(d) = outline::check(s, id) {
+        return Err(d);
+    }
+    let t = outline::parse(s, id).map_err(|e| outline::diagnostic(&e))?;
+
+    let mut globals = Vec::new();
+    let mut declared = Vec::new();
+    bound_variables(&t, &mut globals, &mut declared);
+    declared_globals(&t, &mut declared);
+    for s in declared {
+        if !globals.contains(&s) {globals.push(s);}
+    }
+
+    let mut linter = Linter {
+        vtab: VarTab::new(None),
+        functions: vec![Function::default()],
+        globals, known,
+        dynamic: calls_gtab(&t),
+        diagnostics: Vec::new()
+    };
+    linter.visit(&t);
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.col));
+    Ok(diagnostics)
+}
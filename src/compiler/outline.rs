@@ -0,0 +1,564 @@
+
+// Static analysis of Moss sources for editor tooling: syntax
+// diagnostics, the definitions of a module and the lookup of the
+// definition of a name. Only the parser runs, nothing is executed.
+// A source with syntax errors is analysed as far as the parser
+// could recover from them.
+
+use std::rc::Rc;
+use std::fs;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{
+    AST, Symbol, SymbolType, Info, Item, Value, Error, EnumError,
+    Compilation, TokenIterator, scan, ast_argv
+};
+use crate::system;
+
+pub struct Diagnostic {
+    pub line: usize,
+    pub col: usize,
+    pub text: String
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Function, Method, Class, Record, Enum, Module, Variable,
+
+    // A begin-block, with its private definitions as children.
+    Block
+}
+
+#[derive(Clone)]
+pub struct Definition {
+    pub name: String,
+    pub kind: Kind,
+    pub line: usize,
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub detail: String,
+    pub children: Vec<Definition>,
+
+    // For names bound by 'use': the module and the member.
+    import: Option<(String, Option<String>)>
+}
+
+pub struct Location {
+    // None refers to the analysed source itself.
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub col: usize
+}
+
+fn diagnostics(e: &EnumError) -> Vec<Diagnostic> {
+    let a = match *e {
+        EnumError::Syntax(ref e) => std::slice::from_ref(e),
+        EnumError::Several(ref a) => &a[..]
+    };
+    a.iter().map(|e| Diagnostic {line: e.line, col: e.col, text: e.s.clone()}).collect()
+}
+
+// The first error, in order of appearance.
+pub(super) fn diagnostic(e: &EnumError) -> Diagnostic {
+    diagnostics(e).swap_remove(0)
+}
+
+pub(super) fn parse(s: &str, id: &str) -> Result<Rc<AST>, Error> {
+    let v = scan(s, 1, id, false)?;
+    let mut history = system::History::new();
+    let mut c = Compilation::new(false, &mut history, id, false);
+    let mut i = TokenIterator {index: 0, a: Rc::from(v)};
+    c.ast(&mut i, Value::Optional)
+}
+
+// The position of the 'end' of a block by the position of its
+// first token.
+type Ends = HashMap<(usize, usize), (usize, usize)>;
+
+// The syntax tree of the statements the parser could recover,
+// or None if it had to give up.
+fn parse_recovered(s: &str, id: &str) -> Option<(Rc<AST>, Ends)> {
+    let v = scan(s, 1, id, false).ok()?;
+    let mut history = system::History::new();
+    let mut c = Compilation::new(false, &mut history, id, false);
+    let mut i = TokenIterator {index: 0, a: Rc::from(v)};
+    let t = c.ast_recovered(&mut i, Value::Optional).ok()?;
+    Some((t, c.ends))
+}
+
+// The first error the compiler reports, if any.
+pub fn check(s: &str, id: &str) -> Option<Diagnostic> {
+    check_all(s, id).into_iter().next()
+}
+
+// All syntax errors, as the parser resumes at the next
+// statement after an error. Errors found while compiling
+// the syntax tree are reported only for a valid syntax.
+pub fn check_all(s: &str, id: &str) -> Vec<Diagnostic> {
+    let v = match scan(s, 1, id, false) {
+        Ok(v) => v, Err(e) => return diagnostics(&e)
+    };
+    let mut history = system::History::new();
+    let mut c = Compilation::new(false, &mut history, id, false);
+    let mut i = TokenIterator {index: 0, a: Rc::from(v)};
+    let result = match c.ast(&mut i, Value::Optional) {
+        Ok(t) => c.compile_ast(&mut Vec::new(), &t),
+        Err(e) => Err(e)
+    };
+    match result {
+        Ok(()) => Vec::new(),
+        Err(e) => diagnostics(&e)
+    }
+}
+
+fn is_identifier(t: &AST) -> bool {
+    t.symbol_type == SymbolType::Identifier
+}
+
+fn name_of(t: &AST) -> &str {
+    match t.s {Some(ref s) => s, None => ""}
+}
+
+fn children(t: &AST) -> &[Rc<AST>] {
+    match t.a {Some(ref a) => a, None => &[]}
+}
+
+// The position behind the last node of a subtree, behind
+// the 'end' of a function.
+fn extent(t: &AST, ends: &Ends) -> (usize, usize) {
+    if t.value == Symbol::Fn {
+        if let Some(&(line, col)) = ends.get(&(t.line, t.col)) {
+            return (line, col + "end".len());
+        }
+    }
+    let mut end = (t.line, t.col + name_of(t).chars().count());
+    for x in children(t) {
+        let e = extent(x, ends);
+        if e > end {end = e;}
+    }
+    end
+}
+
+fn literal_text(t: &AST) -> String {
+    match t.symbol_type {
+        SymbolType::Identifier => name_of(t).to_string(),
+        SymbolType::String => format!("\"{}\"", name_of(t)),
+        SymbolType::Int => match t.info {
+            Info::Int(x) => x.to_string(),
+            _ => name_of(t).to_string()
+        },
+        SymbolType::Float | SymbolType::Imag => name_of(t).to_string(),
+        _ => match t.value {
+            Symbol::Null => String::from("null"),
+            Symbol::True => String::from("true"),
+            Symbol::False => String::from("false"),
+            _ => String::from("...")
+        }
+    }
+}
+
+pub(super) fn signature(keyword: &str, name: &str, args: &AST) -> String {
+    let (selfarg, variadic) = match args.info {
+        Info::Argv {selfarg, variadic} => (selfarg, variadic),
+        _ => (false, false)
+    };
+    let a = children(args);
+    let mut acc = String::new();
+    for (k, x) in a.iter().enumerate() {
+        if k > 0 {acc.push_str(if selfarg && k == 1 {"; "} else {", "});}
+        if variadic && k+1 == a.len() {acc.push('*');}
+        if x.value == Symbol::Assignment {
+            let b = ast_argv(x);
+            acc.push_str(&format!("{}={}", name_of(&b[0]), literal_text(&b[1])));
+        } else {
+            acc.push_str(name_of(x));
+        }
+    }
+    if name.is_empty() {
+        format!("fn |{}|", acc)
+    } else {
+        format!("{} {}({})", keyword, name, acc)
+    }
+}
+
+fn callee(t: &AST) -> Option<&str> {
+    if t.value == Symbol::Application {
+        let a = children(t);
+        if !a.is_empty() && is_identifier(&a[0]) {
+            return Some(name_of(&a[0]));
+        }
+    }
+    None
+}
+
+// A begin-block is parsed into the call of a function
+// without name and arguments.
+fn is_block(t: &AST) -> bool {
+    t.value == Symbol::Application && match children(t) {
+        [f] => f.value == Symbol::Fn && f.s.is_none() && (f.line, f.col) == (t.line, t.col)
+            && children(&ast_argv(f)[0]).is_empty(),
+        _ => false
+    }
+}
+
+// The variables of a function body, or of the module if the
+// function is the main program.
+struct Scope<'a> {
+    defs: Vec<Definition>,
+    globals: Vec<String>,
+    ends: &'a Ends
+}
+
+impl<'a> Scope<'a> {
+    fn get(&self, name: &str) -> Option<&Definition> {
+        self.defs.iter().find(|d| d.name == name)
+    }
+    fn define(&mut self, d: Definition) {
+        if self.get(&d.name).is_none() {
+            self.defs.push(d);
+        }
+    }
+    fn variable(&mut self, t: &AST, detail: &str) {
+        if is_identifier(t) {
+            let name = name_of(t);
+            self.define(Definition {
+                name: name.to_string(), kind: Kind::Variable,
+                line: t.line, col: t.col,
+                end_line: t.line, end_col: t.col + name.chars().count(),
+                detail: format!("{} {}", detail, name),
+                children: Vec::new(), import: None
+            });
+        } else if t.value == Symbol::List || t.value == Symbol::Map {
+            for x in children(t) {
+                self.variable(x, detail);
+            }
+        }
+    }
+
+    fn assignment(&mut self, lhs: &AST, rhs: &AST) {
+        if !is_identifier(lhs) {
+            self.variable(lhs, "variable");
+            return;
+        }
+        let name = name_of(lhs).to_string();
+        let mut d = Definition {
+            name: name.clone(), kind: Kind::Variable,
+            line: lhs.line, col: lhs.col,
+            end_line: lhs.line, end_col: lhs.col + name.chars().count(),
+            detail: format!("variable {}", name),
+            children: Vec::new(), import: None
+        };
+        if rhs.value == Symbol::Fn {
+            let a = ast_argv(rhs);
+            d.kind = Kind::Function;
+            d.detail = signature("function", &name, &a[0]);
+            d.children = nested_definitions(&a[0], &a[1], self.ends);
+            let (line, col) = extent(rhs, self.ends);
+            d.end_line = line; d.end_col = col;
+        } else if let Some(f) = callee(rhs) {
+            let a = ast_argv(rhs);
+            match f {
+                "class" if a.len() == 4 || a.len() == 5 => {
+                    d.kind = Kind::Class;
+                    d.detail = format!("class {}", name);
+                    d.children = methods(&a[3], self.ends);
+                },
+                "record_type" if a.len() == 3 => {
+                    let fields: Vec<&str> = children(&a[2]).iter()
+                        .map(|x| name_of(x)).collect();
+                    d.kind = Kind::Record;
+                    d.detail = format!("record {}({})", name, fields.join(", "));
+                },
+                "enum" if a.len() == 3 => {
+                    d.kind = Kind::Enum;
+                    d.detail = format!("enum {}", name);
+                },
+                "load" if a.len() == 2 && a[1].symbol_type == SymbolType::String => {
+                    let module = name_of(&a[1]).to_string();
+                    d.kind = Kind::Module;
+                    d.detail = format!("use {}", module);
+                    d.import = Some((module, None));
+                },
+                _ => {}
+            }
+            if d.kind != Kind::Variable {
+                let (line, col) = extent(rhs, self.ends);
+                d.end_line = line; d.end_col = col;
+            }
+        } else if rhs.value == Symbol::Dot {
+            let a = ast_argv(rhs);
+            if is_identifier(&a[0]) && a[1].symbol_type == SymbolType::String {
+                let module = match self.get(name_of(&a[0])) {
+                    Some(m) if m.kind == Kind::Module => m.import.clone(),
+                    _ => None
+                };
+                if let Some((module, None)) = module {
+                    let member = name_of(&a[1]).to_string();
+                    d.detail = format!("use {}: {}", module, member);
+                    d.import = Some((module, Some(member)));
+                }
+            }
+        }
+        self.define(d);
+    }
+
+    // A begin-block is shown with the definitions it keeps to
+    // itself, the ones it exports are definitions of the module.
+    fn block(&mut self, t: &AST) {
+        let f = &children(t)[0];
+        let a = ast_argv(f);
+        let scope = Scope::new(Some(&a[0]), &a[1], self.ends);
+        let (end_line, end_col) = extent(f, self.ends);
+        self.defs.push(Definition {
+            name: String::from("begin"), kind: Kind::Block,
+            line: t.line, col: t.col, end_line, end_col,
+            detail: String::from("begin"),
+            children: scope.defs.iter().filter(|d|
+                d.kind != Kind::Variable && !scope.globals.contains(&d.name)
+            ).cloned().collect(),
+            import: None
+        });
+    }
+
+    fn visit(&mut self, t: &AST) {
+        match t.value {
+            Symbol::Fn => {},
+            Symbol::Application if is_block(t) => self.block(t),
+            Symbol::Assignment if t.symbol_type != SymbolType::Keyword => {
+                let a = ast_argv(t);
+                self.assignment(&a[0], &a[1]);
+                self.visit(&a[1]);
+            },
+            Symbol::Global => {
+                for x in children(t) {
+                    if is_identifier(x) {
+                        self.globals.push(name_of(x).to_string());
+                    }
+                }
+            },
+            Symbol::For | Symbol::Catch => {
+                let a = children(t);
+                if let Some(x) = a.first() {
+                    self.variable(x, "variable");
+                }
+                for x in a.iter().skip(1) {
+                    self.visit(x);
+                }
+            },
+            _ => {
+                for x in children(t) {
+                    self.visit(x);
+                }
+            }
+        }
+    }
+
+    fn new(args: Option<&AST>, body: &AST, ends: &'a Ends) -> Scope<'a> {
+        let mut scope = Scope {defs: Vec::new(), globals: Vec::new(), ends};
+        if let Some(args) = args {
+            for x in children(args) {
+                let x = if x.value == Symbol::Assignment {&ast_argv(x)[0]} else {x};
+                scope.variable(x, "argument");
+            }
+        }
+        scope.visit(body);
+        scope
+    }
+}
+
+fn nested_definitions(args: &AST, body: &AST, ends: &Ends) -> Vec<Definition> {
+    Scope::new(Some(args), body, ends).defs.into_iter()
+        .filter(|d| d.kind != Kind::Variable).collect()
+}
+
+fn methods(map: &AST, ends: &Ends) -> Vec<Definition> {
+    let mut acc = Vec::new();
+    if map.value != Symbol::Map {return acc;}
+    for pair in children(map).chunks(2) {
+        if pair.len() == 2 && pair[0].symbol_type == SymbolType::String
+            && pair[1].value == Symbol::Fn
+        {
+            let name = name_of(&pair[0]);
+            let a = ast_argv(&pair[1]);
+            let (end_line, end_col) = extent(&pair[1], ends);
+            acc.push(Definition {
+                name: name.to_string(), kind: Kind::Method,
+                line: pair[1].line, col: pair[1].col, end_line, end_col,
+                detail: signature("function", name, &a[0]),
+                children: Vec::new(), import: None
+            });
+        }
+    }
+    acc
+}
+
+// Names declared global inside functions, like the public
+// names of begin-blocks, are definitions of the module.
+fn exported(t: &AST, root: &mut Scope) {
+    if t.value == Symbol::Fn {
+        let a = ast_argv(t);
+        let scope = Scope::new(Some(&a[0]), &a[1], root.ends);
+        for d in scope.defs {
+            if scope.globals.contains(&d.name) {
+                root.define(d);
+            }
+        }
+    }
+    for x in children(t) {
+        exported(x, root);
+    }
+}
+
+fn module_scope<'a>(t: &AST, ends: &'a Ends) -> Scope<'a> {
+    let mut root = Scope::new(None, t, ends);
+    exported(t, &mut root);
+    root
+}
+
+// The definitions of a module, in order of appearance. Parts
+// with syntax errors are left out, the first error is returned
+// only if the parser could not recover from it.
+pub fn outline(s: &str, id: &str) -> Result<Vec<Definition>, Diagnostic> {
+    match parse_recovered(s, id) {
+        Some((t, ends)) => {
+            let mut defs = module_scope(&t, &ends).defs;
+            defs.sort_by_key(|d| (d.line, d.col));
+            Ok(defs)
+        },
+        None => Err(check(s, id).unwrap_or(Diagnostic {
+            line: 1, col: 1, text: String::from("could not parse the source.")
+        }))
+    }
+}
+
+// The docstring of a module: a string literal as its first statement.
+pub fn module_doc(s: &str, id: &str) -> Option<String> {
+    let t = parse(s, id).ok()?;
+    super::docstring(&t).map(|x| super::dedent(name_of(x)))
+}
+
+fn lookup(stack: &[Scope], name: &str) -> Option<Definition> {
+    for scope in stack.iter().rev() {
+        if scope.globals.iter().any(|x| x == name) {
+            return stack[0].get(name).cloned();
+        }
+        if let Some(d) = scope.get(name) {
+            return Some(d.clone());
+        }
+    }
+    None
+}
+
+// Searches the occurrence of the identifier at (line, col) and
+// resolves it in the scopes enclosing it. Returns None if there
+// is no such occurrence.
+fn resolve(t: &AST, line: usize, col: usize, stack: &mut Vec<Scope>)
+-> Option<Option<Definition>>
+{
+    if is_identifier(t) && t.line == line && t.col == col {
+        return Some(lookup(stack, name_of(t)));
+    }
+    if t.value == Symbol::Fn {
+        let a = ast_argv(t);
+        let ends = stack[0].ends;
+        stack.push(Scope::new(Some(&a[0]), &a[1], ends));
+        let y = resolve(&a[0], line, col, stack)
+            .or_else(|| resolve(&a[1], line, col, stack));
+        stack.pop();
+        return y;
+    }
+    for x in children(t) {
+        if let Some(y) = resolve(x, line, col, stack) {
+            return Some(y);
+        }
+    }
+    None
+}
+
+// The identifier token that covers the position.
+fn identifier_at(s: &str, id: &str, line: usize, col: usize)
+-> Option<(String, usize, usize)>
+{
+    let v = scan(s, 1, id, false).ok()?;
+    for t in v.iter() {
+        if t.token_type == SymbolType::Identifier && t.line == line {
+            if let Item::String(ref name) = t.item {
+                if t.col <= col && col <= t.col + name.chars().count() {
+                    return Some((name.clone(), t.line, t.col));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn definition_of(s: &str, id: &str, line: usize, col: usize)
+-> Option<Definition>
+{
+    let (name, line, col) = identifier_at(s, id, line, col)?;
+    let (t, ends) = parse_recovered(s, id)?;
+    let root = module_scope(&t, &ends);
+    let mut stack = vec![root];
+    match resolve(&t, line, col, &mut stack) {
+        Some(d) => d,
+        None => stack[0].get(&name).cloned()
+    }
+}
+
+fn module_path(dir: &Path, module: &str) -> Option<PathBuf> {
+    let file = format!("{}.moss", module.replace('.', "/"));
+    let mut paths = vec![dir.join(&file)];
+    if let Ok(home) = std::env::var("HOME") {
+        paths.push(PathBuf::from(home).join(".moss").join(&file));
+    }
+    paths.into_iter().find(|p| p.is_file())
+}
+
+fn imported(dir: &Path, module: &str, member: &Option<String>)
+-> Option<(PathBuf, Option<Definition>)>
+{
+    let path = module_path(dir, module)?;
+    let d = match member {
+        Some(member) => {
+            let s = fs::read_to_string(&path).ok()?;
+            let id = path.to_string_lossy();
+            outline(&s, &id).ok()?.into_iter().find(|d| &d.name == member)
+        },
+        None => None
+    };
+    Some((path, d))
+}
+
+// The definition of the name at (line, col). Names bound by 'use'
+// lead into the module file, which is searched in the directory
+// of the source and in ~/.moss.
+pub fn definition(s: &str, path: &Path, line: usize, col: usize)
+-> Option<Location>
+{
+    let id = path.to_string_lossy();
+    let d = definition_of(s, &id, line, col)?;
+    if let Some((ref module, ref member)) = d.import {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        if let Some((file, md)) = imported(dir, module, member) {
+            let (line, col) = match md {Some(md) => (md.line, md.col), None => (1, 1)};
+            return Some(Location {file: Some(file), line, col});
+        }
+    }
+    Some(Location {file: None, line: d.line, col: d.col})
+}
+
+// A short description of the name at (line, col), the signature
+// in case of a function.
+pub fn hover(s: &str, path: &Path, line: usize, col: usize) -> Option<String> {
+    let id = path.to_string_lossy();
+    let d = definition_of(s, &id, line, col)?;
+    if let Some((ref module, Some(ref member))) = d.import {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        if let Some((_, Some(md))) = imported(dir, module, &Some(member.clone())) {
+            return Some(md.detail);
+        }
+    }
+    Some(d.detail)
+}
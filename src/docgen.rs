@@ -0,0 +1,234 @@
+
+// The documentation generator behind 'moss -doc'. The module is
+// loaded to obtain its public names. The documentation of a function
+// is its docstring, the signature is taken from the compiled function
+// or, if undocumented, from the source. The order of the entries is
+// the order of definition in the source.
+
+use std::io::Read;
+
+use crate::object::{Object, Function, downcast};
+use crate::class::{Class, Table};
+use crate::compiler::outline::{self, Definition, Kind};
+use crate::system::open_module_file;
+use crate::vm::Env;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Html, Markdown
+}
+
+struct Entry {
+    signature: String,
+    text: String,
+    members: Vec<Entry>
+}
+
+fn module_source(env: &mut Env, id: &str) -> Option<String> {
+    let path = env.rte().path.clone();
+    let search_paths = &path.borrow().v;
+    let (mut f, binary, _) = open_module_file(search_paths, id).ok()?;
+    if binary {return None;}
+    let mut s = String::new();
+    f.read_to_string(&mut s).ok()?;
+    Some(s)
+}
+
+fn load_module(env: &mut Env, id: &str) -> Result<Object, String> {
+    let load = env.rte().gtab.borrow().m.get(&Object::from("load")).cloned();
+    let load = match load {
+        Some(f) => f,
+        None => return Err(String::from("Error: load is not defined."))
+    };
+    env.call(&load, &Object::Null, &[Object::from(id)])
+        .map_err(|e| env.exception_to_string(&e))
+}
+
+// The names in order of definition, followed by the remaining
+// names in alphabetical order.
+fn ordered_names(keys: Vec<String>, defs: &[Definition]) -> Vec<String> {
+    let mut names: Vec<String> = defs.iter().map(|d| d.name.clone())
+        .filter(|name| keys.contains(name)).collect();
+    let mut rest: Vec<String> = keys.into_iter()
+        .filter(|name| !names.contains(name)).collect();
+    rest.sort();
+    names.extend(rest);
+    names
+}
+
+fn string_keys(m: &crate::object::Map) -> Vec<String> {
+    m.m.keys().filter_map(|key| match *key {
+        Object::String(ref s) => Some(s.to_string()), _ => None
+    }).filter(|name| !name.starts_with('_')).collect()
+}
+
+fn function_entry(name: &str, f: &Function, def: Option<&Definition>) -> Entry {
+    if let Object::String(ref s) = f.doc {
+        let s = s.to_string();
+        let (signature, text) = s.split_once('\n').unwrap_or((&s, ""));
+        return Entry {
+            signature: signature.trim_start_matches("function ").to_string(),
+            text: text.trim().to_string(), members: Vec::new()
+        };
+    }
+    let signature = match def {
+        Some(d) if d.detail.starts_with("function ") =>
+            d.detail["function ".len()..].to_string(),
+        _ if f.argc_max == 0 => format!("{}()", name),
+        _ => format!("{}(...)", name)
+    };
+    Entry {signature, text: String::new(), members: Vec::new()}
+}
+
+fn class_entry(name: &str, c: &Class, def: Option<&Definition>) -> Entry {
+    let text = match c.doc {
+        Object::String(ref s) => s.to_string(),
+        _ => String::new()
+    };
+    let map = c.map.borrow();
+    let methods = def.map(|d| &d.children[..]).unwrap_or(&[]);
+    let mut members = Vec::new();
+    for method in ordered_names(string_keys(&map), methods) {
+        if let Some(Object::Function(ref f)) = map.m.get(&Object::from(method.as_str())) {
+            let def = methods.iter().find(|d| d.name == method);
+            let mut entry = function_entry(&method, f, def);
+            entry.signature = format!("{}.{}", name, entry.signature);
+            members.push(entry);
+        }
+    }
+    Entry {signature: format!("class {}", name), text, members}
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// Paragraphs are separated by blank lines. An indented paragraph,
+// or one starting with the prompt "> ", is an example, shown as code.
+fn paragraphs(text: &str) -> Vec<(bool, String)> {
+    let mut acc: Vec<(bool, String)> = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().chain(std::iter::once("")) {
+        if !line.trim().is_empty() {
+            lines.push(line);
+            continue;
+        }
+        if lines.is_empty() {continue;}
+        let code = lines[0].starts_with("> ") || lines.iter().all(|x| x.starts_with(' '));
+        let indent = if code && lines.iter().all(|x| x.starts_with(' ')) {
+            lines.iter().map(|x| x.len() - x.trim_start().len()).min().unwrap_or(0)
+        } else {0};
+        let s: Vec<&str> = lines.iter().map(|x| &x[indent..]).collect();
+        acc.push((code, s.join("\n")));
+        lines.clear();
+    }
+    acc
+}
+
+fn html_text(acc: &mut String, text: &str) {
+    for (k, (code, s)) in paragraphs(text).into_iter().enumerate() {
+        if code {
+            acc.push_str(&format!("\n<pre class=\"moss\">\n{}\n</pre>\n", escape(&s)));
+        } else {
+            if k > 0 {acc.push_str("\n<p>");}
+            acc.push_str(&escape(&s));
+        }
+    }
+    acc.push('\n');
+}
+
+fn html_entries(acc: &mut String, entries: &[Entry]) {
+    for entry in entries {
+        acc.push_str(&format!("<dt>{}\n<dd>", escape(&entry.signature)));
+        html_text(acc, &entry.text);
+        acc.push('\n');
+        html_entries(acc, &entry.members);
+    }
+}
+
+fn html(id: &str, doc: &str, entries: &[Entry]) -> String {
+    let title = format!("Module {}", escape(id));
+    let mut acc = format!(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n",
+        "  <meta charset=\"UTF-8\">\n",
+        "  <title>{}</title>\n",
+        "  <link href=\"../css/main.css\" rel=\"stylesheet\">\n",
+        "  <script src=\"../js/moss-syntax.js\"></script>\n",
+        "</head>\n<body>\n\n",
+        "<table class=\"navigation\">\n",
+        "<tr><td><b>&uarr;</b> <a href=\"toc.htm\">Up</a>\n",
+        "</table>\n",
+        "<h1>{}</h1>\n"), title, title);
+    if !doc.is_empty() {
+        acc.push_str("<p>");
+        html_text(&mut acc, doc);
+    }
+    acc.push_str("\n<dl class=\"code\">\n");
+    html_entries(&mut acc, entries);
+    acc.push_str("</dl>\n\n</body>\n</html>\n");
+    acc
+}
+
+fn markdown_text(acc: &mut String, text: &str) {
+    for (code, s) in paragraphs(text) {
+        if code {
+            acc.push_str(&format!("```moss\n{}\n```\n\n", s));
+        } else {
+            acc.push_str(&format!("{}\n\n", s));
+        }
+    }
+}
+
+fn markdown_entries(acc: &mut String, entries: &[Entry], level: usize) {
+    for entry in entries {
+        acc.push_str(&format!("{} `{}`\n\n", "#".repeat(level), entry.signature));
+        markdown_text(acc, &entry.text);
+        markdown_entries(acc, &entry.members, level + 1);
+    }
+}
+
+fn markdown(id: &str, doc: &str, entries: &[Entry]) -> String {
+    let mut acc = format!("# Module {}\n\n", id);
+    markdown_text(&mut acc, doc);
+    markdown_entries(&mut acc, entries, 3);
+    acc
+}
+
+// Generates the documentation page of the module id,
+// e.g. "math.ode", in the given format.
+pub fn generate(env: &mut Env, id: &str, format: Format) -> Result<String, String> {
+    let path = id.replace('.', "/");
+    let module = load_module(env, &path)?;
+    let map = match downcast::<Table>(&module) {
+        Some(t) => t.map.clone(),
+        None => return Err(format!("Error: module '{}' is not a table.", id))
+    };
+    let s = module_source(env, &path);
+    let defs = s.as_ref().and_then(|s| outline::outline(s, &path).ok()).unwrap_or_default();
+    let doc = s.as_ref().and_then(|s| outline::module_doc(s, &path)).unwrap_or_default();
+
+    let keys = string_keys(&map.borrow());
+    let mut entries = Vec::new();
+    for name in ordered_names(keys, &defs) {
+        let def = defs.iter().find(|d| d.name == name);
+        if def.is_some_and(|d| d.kind == Kind::Module) {continue;}
+        let value = match map.borrow().m.get(&Object::from(name.as_str())) {
+            Some(x) => x.clone(), None => continue
+        };
+        let entry = if let Object::Function(ref f) = value {
+            function_entry(&name, f, def)
+        } else if let Some(c) = downcast::<Class>(&value) {
+            class_entry(&name, c, def)
+        } else if downcast::<Table>(&value).is_some_and(|t| matches!(t.prototype, Object::Null)) {
+            // A module imported by 'use'.
+            continue;
+        } else {
+            Entry {signature: name.clone(), text: String::new(), members: Vec::new()}
+        };
+        entries.push(entry);
+    }
+    Ok(match format {
+        Format::Html => html(id, &doc, &entries),
+        Format::Markdown => markdown(id, &doc, &entries)
+    })
+}
@@ -8,11 +8,12 @@ use std::f64::NAN;
 use std::io::Read;
 
 use crate::vm::{
-    RTE, Env, op_lt, interface_index, interface_types_set
+    RTE, Env, op_lt, interface_index, interface_types_set,
+    CallCC
 };
 use crate::object::{
     Object, Table, Map, List, CharString,
-    FnResult, Function, EnumFunction, Info,
+    FnResult, Function, EnumFunction, Info, Exception,
     VARIADIC, float, new_module, downcast
 };
 use crate::rand::Rand;
@@ -24,7 +25,10 @@ use crate::long::{Long, pow_mod};
 use crate::class::{table_get};
 use crate::iterable::new_iterator;
 use crate::map::map_extend;
-use crate::class::{Class,class_new};
+use crate::class::{
+    Class, VariantType, Abstract, unimplemented_abstract,
+    class_new, class_mro, enum_new, record_new, property_new, super_new
+};
 use crate::range::Range;
 use crate::data::{Bytes,base16};
 
@@ -264,17 +268,18 @@ fn len(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
 }
 
 fn load_file(env: &mut Env, id: &str) -> FnResult {
-    if !env.rte().read_access(id) {
-        return env.std_exception(&format!(
-            "Error in load(id): Could not open file id=='{}': permission denied.",
-            id));
-    }
     let path = env.rte().path.clone();
     let search_paths = &path.borrow().v;
-    let (mut f,binary) = match open_module_file(search_paths, id) {
+    let (mut f,binary,file_path) = match open_module_file(search_paths, id) {
         Ok(value) => value,
-        Err(e) => return env.std_exception(&e)
+        Err(e) => return env.import_error(&e, id)
     };
+    let file_path = file_path.to_string_lossy();
+    if !env.rte().read_access(&file_path) {
+        return env.permission_error(&format!(
+            "Error in load(id): Could not open file id=='{}': permission denied.\n\
+             Note: path = '{}'.", id, file_path), &file_path);
+    }
 
     let module = new_module(id);
     env.rte().clear_at_exit(module.map.clone());
@@ -284,9 +289,10 @@ fn load_file(env: &mut Env, id: &str) -> FnResult {
         eval_module(env, module.map.clone(), &mut f, id)
     } else {
         let mut s = String::new();
-        if f.read_to_string(&mut s).is_err() {
-            return env.std_exception(&format!(
-                "Error in load: could not read file '{}.moss'.", id));
+        if let Err(e) = f.read_to_string(&mut s) {
+            return env.os_error(&format!(
+                "Error in load: could not read file '{}.moss'.", id),
+                e.raw_os_error(), &format!("{}.moss", id));
         }
         env.rte().main_module.set(false);
         env.eval_string(&s, id, module.map.clone(), Value::None)
@@ -329,6 +335,7 @@ fn load(env: &mut Env, id: Rc<CharString>, hot_plug: bool) -> FnResult{
         "sysfn" => crate::sysfn::load_sysfn(env.rte()),
         "time"  => crate::time::load_time(),
         "data" => crate::data::load_data(env),
+        "unicode" => crate::unicode::load_unicode(),
 
         #[cfg(feature = "graphics")]
         "graphics" => crate::graphics::load_graphics(),
@@ -367,7 +374,8 @@ fn fiter(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
 
 fn record(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
-        1 => {}, n => return env.argc_error(n,1,1,"record")
+        1 => {},
+        n => return env.argc_error(n,1,1,"record")
     }
     if let Some(t) = downcast::<Table>(&argv[0]) {
         Ok(Object::Map(t.map.clone()))
@@ -375,6 +383,8 @@ fn record(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
         Ok(Object::Map(t.map.clone()))
     } else if let Some(t) = downcast::<Class>(&argv[0]) {
         Ok(Object::Map(t.map.clone()))
+    } else if let Some(t) = downcast::<VariantType>(&argv[0]) {
+        Ok(Object::Map(t.map.clone()))
     } else {
         env.type_error1(
             "Type error in record(x): x is not a table.",
@@ -383,17 +393,37 @@ fn record(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     }
 }
 
+fn record_type(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        2 => record_new(env, &argv[0], &argv[1]),
+        n => env.argc_error(n,2,2,"record_type")
+    }
+}
+
+fn check_object_abstract(env: &mut Env, prototype: &Object, map: &Map)
+-> Result<(),Box<Exception>>
+{
+    match unimplemented_abstract(prototype, map) {
+        Some((name,key)) => Err(env.type_error_plain(&format!(
+            "Type error in object({},m): abstract method '{}' is not implemented.",
+            name, key))),
+        None => Ok(())
+    }
+}
+
 fn fobject(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         0 => {
             Ok(Object::Interface(Table::new(Object::Null)))
         },
         1 => {
+            check_object_abstract(env, &argv[0], &Map::new().borrow())?;
             Ok(Object::Interface(Table::new(argv[0].clone())))
         },
         2 => {
             match argv[1] {
                 Object::Map(ref m) => {
+                    check_object_abstract(env, &argv[0], &m.borrow())?;
                     Ok(Object::Interface(Rc::new(Table{
                         prototype: argv[0].clone(),
                         map: m.clone()
@@ -409,7 +439,7 @@ fn fobject(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     }
 }
 
-fn ftype(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+pub fn ftype(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         1 => {}, n => return env.argc_error(n,1,1,"type")
     }
@@ -808,7 +838,7 @@ fn input(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         0 => {
             let s = match crate::system::getline("") {
-                Ok(s)=>s, Err(_) => return env.std_exception("Error in input(): could not obtain input.")
+                Ok(s)=>s, Err(e) => return env.io_error("Error in input(): could not obtain input.", e.raw_os_error(), "")
             };
             Ok(CharString::new_object_str(&s))
         },
@@ -826,7 +856,7 @@ fn input(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
                         h.append(&x.string(env)?);
                     }
                     match crate::system::getline_history(&prompt,&h) {
-                        Ok(s)=>s, Err(_) => return env.std_exception("Error in input(): could not obtain input.")
+                        Ok(s)=>s, Err(e) => return env.io_error("Error in input(): could not obtain input.", e.raw_os_error(), "")
                     }
                 } else {
                     return env.type_error1(
@@ -835,7 +865,7 @@ fn input(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
                 }
             } else {
                 match crate::system::getline(&prompt) {
-                    Ok(s)=>s, Err(_) => return env.std_exception("Error in input(): could not obtain input.")
+                    Ok(s)=>s, Err(e) => return env.io_error("Error in input(): could not obtain input.", e.raw_os_error(), "")
                 }
             };
             Ok(CharString::new_object_str(&s))
@@ -878,14 +908,14 @@ fn read(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
         Object::String(ref id) => {
             let id = id.to_string();
             if !env.rte().read_access(&id) {
-                return env.std_exception(&format!(
+                return env.permission_error(&format!(
                     "Error in read(id): Could not open file id=='{}': permission denied.",id
-                ));
+                ), &id);
             }
 
             match crate::system::read_file(&id) {
                 Ok(s) => Ok(CharString::new_object_str(&s)),
-                Err(e) => env.std_exception(&e)
+                Err((e,errno)) => env.os_error(&e, errno, &id)
             }
         },
         ref x => env.type_error1(
@@ -1144,6 +1174,65 @@ fn oct(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     }
 }
 
+fn help(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"help")
+    }
+    let s = env.help_text(&argv[0])?;
+    println!("{}", s);
+    Ok(Object::Null)
+}
+
+// The documentation of the built-in functions. As for functions
+// documented by a docstring, the first line is the signature.
+const DOCS: &[(&str, &str)] = &[
+    ("print", "print(*a)\nWrites the string representations of the\narguments to stdout, followed by a newline."),
+    ("put", "put(*a)\nLike print, but without the newline."),
+    ("str", "str(x)\nThe string representation of x."),
+    ("int", "int(x)\nConverts a number or a string into an integer."),
+    ("float", "float(x)\nConverts a number or a string into a float."),
+    ("repr", "repr(x)\nThe representation of x as source text, strings\nbeing quoted."),
+    ("hex", "hex(n)\nThe hexadecimal representation of the integer n."),
+    ("bin", "bin(n)\nThe binary representation of the integer n."),
+    ("oct", "oct(n)\nThe octal representation of the integer n."),
+    ("input", "input(prompt=\"\", history=[])\nReads a line from stdin after printing the prompt.\nThe list history provides lines for recall."),
+    ("sgn", "sgn(x)\nThe sign of x: -1, 0 or 1."),
+    ("abs", "abs(x)\nThe absolute value of x."),
+    ("eval", "eval(s, m=gtab())\nCompiles and runs the source text s, with m as\nthe table of global variables."),
+    ("len", "len(a)\nThe number of elements of a string, list or map."),
+    ("load", "load(id)\nLoads the module id and returns it. Modules are\nloaded only once."),
+    ("iter", "iter(a)\nAn iterator over the elements of a."),
+    ("cycle", "cycle(a)\nAn iterator that repeats the elements of a\nendlessly."),
+    ("record", "record(t)\nThe slot map of the table or class t."),
+    ("record_type", "record_type(name, fields)\nA new record type with the given list of field\nnames."),
+    ("object", "object(prototype=null, m={})\nA new table with the given prototype and slots."),
+    ("type", "type(x)\nThe type of x."),
+    ("list", "list(a)\nThe elements of the iterable a as a list."),
+    ("set", "set(a)\nThe elements of the iterable a as a set."),
+    ("copy", "copy(x)\nA shallow copy of a list, map or table."),
+    ("rng", "rng(a=null, seed=null)\nA random number generator drawing from the\nrange or list a, or floats in [0, 1) without a."),
+    ("gtab", "gtab(f=null)\nThe table of global variables of the current\nmodule, or of the module the function f belongs to."),
+    ("const", "const(x)\nFreezes the list, map or table x and returns it."),
+    ("read", "read(id)\nThe content of the file id as a string."),
+    ("zip", "zip(*a)\nAn iterator over tuples of the elements of the\niterables a[0], a[1], ..., stopping at the shortest."),
+    ("pow", "pow(x, n, m)\nModular exponentiation: x^n%m."),
+    ("min", "min(x, y)\nThe smaller one of x and y."),
+    ("max", "max(x, y)\nThe larger one of x and y."),
+    ("ord", "ord(c)\nThe code point of the character c."),
+    ("chr", "chr(n)\nThe character of the code point n."),
+    ("map", "map(a)\nA map from the pairs [key, value] of the iterable a."),
+    ("extend", "extend(t, *m)\nAdds the entries of the maps m to the slots of\nthe table or class t."),
+    ("long", "long(x)\nConverts an integer or a string into a long integer."),
+    ("abort", "abort(text=null)\nRaises an exception telling that the program\nwas aborted."),
+    ("getattr", "getattr(t, key)\nThe slot key of the table t, or null."),
+    ("class", "class(name, parent, m)\nA new class with the methods of the map m.\nUsually written as 'class Name: Parent = {...}'."),
+    ("enum", "enum(name, variants)\nA new enumeration type with the given variants."),
+    ("property", "property(get, set=null)\nA slot that is computed by the function get on\nread access and stored by set on write access."),
+    ("super", "super(C, self)\nThe object self viewed through the parents of the\nclass C, for calling overridden methods."),
+    ("mro", "mro(C)\nThe method resolution order of the class C."),
+    ("help", "help(x)\nPrints the documentation of x.")
+];
+
 pub fn init_rte(rte: &RTE){
     let mut gtab = rte.gtab.borrow_mut();
     gtab.insert_fn_plain("print",print,0,VARIADIC);
@@ -1164,6 +1253,7 @@ pub fn init_rte(rte: &RTE){
     gtab.insert_fn_plain("iter",fiter,1,1);
     gtab.insert_fn_plain("cycle",cycle,1,1);
     gtab.insert_fn_plain("record",record,1,1);
+    gtab.insert_fn_plain("record_type",record_type,2,2);
     gtab.insert_fn_plain("object",fobject,0,2);
     gtab.insert_fn_plain("type",ftype,1,1);
     gtab.insert_fn_plain("list",flist,1,1);
@@ -1185,7 +1275,15 @@ pub fn init_rte(rte: &RTE){
     gtab.insert_fn_plain("abort",abort,0,1);
     gtab.insert_fn_plain("getattr",getattr,2,2);
     gtab.insert_fn_plain("class",class_new,1,1);
+    gtab.insert_fn_plain("enum",enum_new,2,2);
+    gtab.insert_fn_plain("property",property_new,1,2);
+    gtab.insert_fn_plain("super",super_new,2,2);
+    gtab.insert_fn_plain("mro",class_mro,1,1);
+    gtab.insert_fn_plain("help",help,1,1);
+    gtab.set_docs(DOCS);
+    gtab.insert("abstract", Object::Interface(Rc::new(Abstract)));
     gtab.insert("empty", Object::empty());
+    gtab.insert("callcc", Object::Interface(Rc::new(CallCC)));
 
     let type_bool = rte.type_bool.clone();
     gtab.insert("Bool", Object::Interface(type_bool));
@@ -1237,6 +1335,30 @@ pub fn init_rte(rte: &RTE){
     let type_index_error = rte.type_index_error.clone();
     gtab.insert("IndexError", Object::Interface(type_index_error));
 
+    let type_key_error = rte.type_key_error.clone();
+    gtab.insert("KeyError", Object::Interface(type_key_error));
+
+    let type_os_error = Object::Interface(rte.type_os_error.clone());
+    gtab.insert("OSError", type_os_error);
+
+    let type_io_error = rte.type_io_error.clone();
+    gtab.insert("IOError", Object::Interface(type_io_error));
+
+    let type_permission_error = rte.type_permission_error.clone();
+    gtab.insert("PermissionError", Object::Interface(type_permission_error));
+
+    let type_zero_division_error = rte.type_zero_division_error.clone();
+    gtab.insert("ZeroDivisionError", Object::Interface(type_zero_division_error));
+
+    let type_overflow_error = rte.type_overflow_error.clone();
+    gtab.insert("OverflowError", Object::Interface(type_overflow_error));
+
+    let type_import_error = rte.type_import_error.clone();
+    gtab.insert("ImportError", Object::Interface(type_import_error));
+
+    let type_syntax_error = rte.type_syntax_error.clone();
+    gtab.insert("SyntaxError", Object::Interface(type_syntax_error));
+
     let type_type = rte.type_type.clone();
     gtab.insert("Type", Object::Interface(type_type));
 
@@ -13,6 +13,8 @@ pub mod object;
 mod compiler;
 mod vm;
 mod global;
+pub mod testing;
+pub mod docgen;
 
 #[path = "objects/list.rs"]
 mod list;
@@ -95,6 +97,9 @@ mod data;
 #[path = "modules/fs.rs"]
 mod fs;
 
+#[path = "modules/unicode.rs"]
+mod unicode;
+
 #[cfg(feature = "graphics")]
 #[path = "modules/sdl.rs"]
 mod sdl;
@@ -111,6 +116,7 @@ use object::{Object, List, Map, CharString, TypeName, Downcast};
 use vm::{RTE,State,EnvPart,Env};
 pub use vm::{get_env};
 pub use compiler::{Value, CompilerExtra};
+pub use compiler::{outline, formatter, lint};
 use global::init_rte;
 
 pub struct InterpreterLock<'a> {
@@ -138,6 +144,24 @@ impl Interpreter{
     pub fn eval(&self, s: &str) -> Object {
         self.lock().env().eval(s)
     }
+    // Compiles s without running it. A syntax error
+    // is returned in the form shown to the user.
+    pub fn compile(&self, s: &str, id: &str) -> Result<(),String> {
+        let history = &mut system::History::new();
+        match compiler::compile(s, id, false, Value::Optional, history, &self.rte) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(compiler::format_error(&e, s))
+        }
+    }
+    // Loads a module in the binary format of 'moss -c' and runs it.
+    // An error is returned in the form shown to the user.
+    pub fn eval_binary(&self, bv: &[u8], id: &str) -> Result<Object,String> {
+        let module = module::load_from_u8(&self.rte, id, bv)?;
+        self.tie(|env| {
+            vm::eval(env, module, Map::new(), false)
+                .map_err(|e| env.exception_to_string(&e))
+        })
+    }
     pub fn eval_cast<T>(&self, s: &str) -> T
     where T: TypeName+Downcast<Output=T>
     {
@@ -202,8 +226,37 @@ impl Interpreter{
             let mut capabilities = self.rte.capabilities.borrow_mut();
             capabilities.write = true;
             capabilities.command = true;
+            capabilities.env = true;
         }
     }
+
+    // Sandbox configuration. The defaults apply to paths not
+    // covered by a rule. A rule for a directory covers everything
+    // below it, the rule with the longest directory wins.
+    pub fn set_read_access(&self, default: bool) {
+        self.rte.capabilities.borrow_mut().read = default;
+    }
+    pub fn set_write_access(&self, default: bool) {
+        self.rte.capabilities.borrow_mut().write = default;
+    }
+    pub fn allow_read(&self, dir: &str) {
+        self.rte.capabilities.borrow_mut().add_read_rule(dir, true);
+    }
+    pub fn deny_read(&self, dir: &str) {
+        self.rte.capabilities.borrow_mut().add_read_rule(dir, false);
+    }
+    pub fn allow_write(&self, dir: &str) {
+        self.rte.capabilities.borrow_mut().add_write_rule(dir, true);
+    }
+    pub fn deny_write(&self, dir: &str) {
+        self.rte.capabilities.borrow_mut().add_write_rule(dir, false);
+    }
+    pub fn allow_command(&self, value: bool) {
+        self.rte.capabilities.borrow_mut().command = value;
+    }
+    pub fn allow_env(&self, value: bool) {
+        self.rte.capabilities.borrow_mut().env = value;
+    }
 }
 
 pub fn new_list_str(a: &[String]) -> Rc<RefCell<List>> {
@@ -0,0 +1,270 @@
+
+// A small JSON reader and writer, just what JSON-RPC needs.
+
+use std::fmt::Write;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<Json>),
+    Map(Vec<(String, Json)>)
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> &Json {
+        if let Json::Map(a) = self {
+            for (k, v) in a {
+                if k == key {return v;}
+            }
+        }
+        &Json::Null
+    }
+
+    pub fn path(&self, keys: &[&str]) -> &Json {
+        keys.iter().fold(self, |x, key| x.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {Json::String(s) => Some(s), _ => None}
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Json::Number(x) if x >= 0.0 => Some(x as usize),
+            _ => None
+        }
+    }
+
+    pub fn map(a: Vec<(&str, Json)>) -> Json {
+        Json::Map(a.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    pub fn string(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+
+    pub fn number(x: usize) -> Json {
+        Json::Number(x as f64)
+    }
+}
+
+fn write_string(buffer: &mut String, s: &str) {
+    buffer.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(buffer, "\\u{:04x}", c as u32).unwrap();
+            },
+            c => buffer.push(c)
+        }
+    }
+    buffer.push('"');
+}
+
+fn write(buffer: &mut String, x: &Json) {
+    match x {
+        Json::Null => buffer.push_str("null"),
+        Json::Bool(b) => buffer.push_str(if *b {"true"} else {"false"}),
+        Json::Number(x) => {
+            if x.fract() == 0.0 && x.abs() < 1E15 {
+                write!(buffer, "{}", *x as i64).unwrap();
+            } else {
+                write!(buffer, "{}", x).unwrap();
+            }
+        },
+        Json::String(s) => write_string(buffer, s),
+        Json::List(a) => {
+            buffer.push('[');
+            for (i, x) in a.iter().enumerate() {
+                if i != 0 {buffer.push(',');}
+                write(buffer, x);
+            }
+            buffer.push(']');
+        },
+        Json::Map(a) => {
+            buffer.push('{');
+            for (i, (k, v)) in a.iter().enumerate() {
+                if i != 0 {buffer.push(',');}
+                write_string(buffer, k);
+                buffer.push(':');
+                write(buffer, v);
+            }
+            buffer.push('}');
+        }
+    }
+}
+
+pub fn to_string(x: &Json) -> String {
+    let mut buffer = String::new();
+    write(&mut buffer, x);
+    buffer
+}
+
+struct Parser<'a> {
+    a: &'a [char],
+    i: usize
+}
+
+impl<'a> Parser<'a> {
+    fn skip_space(&mut self) {
+        while self.i < self.a.len() && self.a[self.i].is_whitespace() {
+            self.i += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.a.get(self.i).cloned()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.i += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at offset {}", c, self.i))
+        }
+    }
+
+    fn keyword(&mut self, s: &str, value: Json) -> Result<Json, String> {
+        let n = s.chars().count();
+        if self.i + n <= self.a.len()
+            && self.a[self.i..self.i+n].iter().cloned().eq(s.chars())
+        {
+            self.i += n;
+            Ok(value)
+        } else {
+            Err(format!("unexpected character at offset {}", self.i))
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        if self.i + 4 > self.a.len() {
+            return Err(String::from("unterminated escape sequence"));
+        }
+        let s: String = self.a[self.i..self.i+4].iter().collect();
+        self.i += 4;
+        u32::from_str_radix(&s, 16).map_err(|_| String::from("invalid escape sequence"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            let c = match self.a.get(self.i) {
+                Some(c) => *c,
+                None => return Err(String::from("unterminated string"))
+            };
+            self.i += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let c = match self.a.get(self.i) {
+                        Some(c) => *c,
+                        None => return Err(String::from("unterminated string"))
+                    };
+                    self.i += 1;
+                    match c {
+                        'n' => s.push('\n'),
+                        't' => s.push('\t'),
+                        'r' => s.push('\r'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => {
+                            let mut x = self.hex4()?;
+                            if (0xd800..0xdc00).contains(&x)
+                                && self.a.get(self.i) == Some(&'\\')
+                                && self.a.get(self.i+1) == Some(&'u')
+                            {
+                                self.i += 2;
+                                let y = self.hex4()?;
+                                x = 0x10000 + ((x - 0xd800) << 10) + (y.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+                            s.push(std::char::from_u32(x).unwrap_or('\u{fffd}'));
+                        },
+                        c => s.push(c)
+                    }
+                },
+                c => s.push(c)
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.i;
+        while self.i < self.a.len()
+            && matches!(self.a[self.i], '0'..='9' | '-' | '+' | '.' | 'e' | 'E')
+        {
+            self.i += 1;
+        }
+        let s: String = self.a[start..self.i].iter().collect();
+        s.parse::<f64>().map(Json::Number)
+            .map_err(|_| format!("invalid number at offset {}", start))
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('[') => {
+                self.i += 1;
+                let mut a = Vec::new();
+                if self.peek() == Some(']') {
+                    self.i += 1;
+                    return Ok(Json::List(a));
+                }
+                loop {
+                    a.push(self.value()?);
+                    if self.peek() == Some(',') {
+                        self.i += 1;
+                    } else {
+                        self.expect(']')?;
+                        return Ok(Json::List(a));
+                    }
+                }
+            },
+            Some('{') => {
+                self.i += 1;
+                let mut a = Vec::new();
+                if self.peek() == Some('}') {
+                    self.i += 1;
+                    return Ok(Json::Map(a));
+                }
+                loop {
+                    self.skip_space();
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    a.push((key, self.value()?));
+                    if self.peek() == Some(',') {
+                        self.i += 1;
+                    } else {
+                        self.expect('}')?;
+                        return Ok(Json::Map(a));
+                    }
+                }
+            },
+            Some(_) => self.number(),
+            None => Err(String::from("unexpected end of input"))
+        }
+    }
+}
+
+pub fn parse(s: &str) -> Result<Json, String> {
+    let a: Vec<char> = s.chars().collect();
+    let mut p = Parser {a: &a, i: 0};
+    let x = p.value()?;
+    if p.peek().is_some() {
+        return Err(format!("unexpected character at offset {}", p.i));
+    }
+    Ok(x)
+}
@@ -0,0 +1,326 @@
+
+// Language server for Moss sources, speaking the
+// Language Server Protocol over stdin/stdout.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use moss::outline::{self, Kind, Definition};
+
+mod json;
+use json::Json;
+
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Json>> {
+    let mut length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {break;}
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if key.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let mut buffer = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut buffer)?;
+    let s = String::from_utf8_lossy(&buffer);
+    match json::parse(&s) {
+        Ok(x) => Ok(Some(x)),
+        Err(e) => {
+            eprintln!("moss-lsp: invalid message: {}", e);
+            Ok(Some(Json::Null))
+        }
+    }
+}
+
+fn send(output: &mut impl Write, x: &Json) -> io::Result<()> {
+    let s = json::to_string(x);
+    write!(output, "Content-Length: {}\r\n\r\n{}", s.len(), s)?;
+    output.flush()
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|x| x as u8)
+}
+
+fn uri_to_path(uri: &str) -> PathBuf {
+    let s = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut a: Vec<u8> = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] == b'%' && i + 2 < s.len() {
+            if let (Some(h), Some(l)) = (hex_value(s[i+1]), hex_value(s[i+2])) {
+                a.push(16*h + l);
+                i += 3;
+                continue;
+            }
+        }
+        a.push(s[i]);
+        i += 1;
+    }
+    PathBuf::from(String::from_utf8_lossy(&a).into_owned())
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let mut s = String::from("file://");
+    for c in path.to_string_lossy().bytes() {
+        if c.is_ascii_alphanumeric() || b"/-_.~".contains(&c) {
+            s.push(c as char);
+        } else {
+            s.push_str(&format!("%{:02X}", c));
+        }
+    }
+    s
+}
+
+// The lines of a document, to translate positions. The protocol
+// counts lines and columns from zero and by default measures
+// columns in UTF-16 code units, the compiler counts from one
+// and measures in characters.
+struct Text<'a> {
+    lines: Vec<&'a str>,
+    utf16: bool
+}
+
+impl<'a> Text<'a> {
+    fn new(s: &'a str, utf16: bool) -> Self {
+        Text {lines: s.split('\n').collect(), utf16}
+    }
+
+    fn line(&self, line: usize) -> &str {
+        self.lines.get(line.wrapping_sub(1)).copied().unwrap_or("")
+    }
+
+    fn position(&self, line: usize, col: usize) -> Json {
+        let mut character = col.saturating_sub(1);
+        if self.utf16 {
+            let s = self.line(line);
+            character = s.chars().take(character).map(char::len_utf16).sum::<usize>()
+                + character.saturating_sub(s.chars().count());
+        }
+        Json::map(vec![
+            ("line", Json::number(line.saturating_sub(1))),
+            ("character", Json::number(character))
+        ])
+    }
+
+    fn range(&self, line: usize, col: usize, end_line: usize, end_col: usize) -> Json {
+        Json::map(vec![
+            ("start", self.position(line, col)),
+            ("end", self.position(end_line, end_col))
+        ])
+    }
+
+    // The column of a position sent by the client.
+    fn column(&self, line: usize, character: usize) -> usize {
+        if !self.utf16 {return character + 1;}
+        let mut units = 0;
+        let mut col = 1;
+        for c in self.line(line).chars() {
+            units += c.len_utf16();
+            if units > character {break;}
+            col += 1;
+        }
+        col
+    }
+}
+
+fn symbol_kind(kind: Kind) -> usize {
+    match kind {
+        Kind::Module => 2,
+        Kind::Block => 3,
+        Kind::Class => 5,
+        Kind::Method => 6,
+        Kind::Enum => 10,
+        Kind::Function => 12,
+        Kind::Variable => 13,
+        Kind::Record => 23
+    }
+}
+
+fn document_symbol(text: &Text, d: &Definition) -> Json {
+    let name_end = d.col + d.name.chars().count();
+    Json::map(vec![
+        ("name", Json::string(&d.name)),
+        ("detail", Json::string(&d.detail)),
+        ("kind", Json::number(symbol_kind(d.kind))),
+        ("range", text.range(d.line, d.col, d.end_line, d.end_col)),
+        ("selectionRange", text.range(d.line, d.col, d.line, name_end)),
+        ("children", Json::List(d.children.iter().map(|x| document_symbol(text, x)).collect()))
+    ])
+}
+
+struct Server {
+    documents: HashMap<String, String>,
+    shutdown: bool,
+
+    // Whether columns are measured in UTF-16 code units
+    // rather than in characters.
+    utf16: bool
+}
+
+impl Server {
+    fn publish_diagnostics(&self, output: &mut impl Write, uri: &str) -> io::Result<()> {
+        let s = match self.documents.get(uri) {Some(s) => s, None => return Ok(())};
+        let path = uri_to_path(uri);
+        let text = Text::new(s, self.utf16);
+        let diagnostics = outline::check_all(s, &path.to_string_lossy()).iter().map(|d|
+            Json::map(vec![
+                ("range", text.range(d.line, d.col, d.line, d.col + 1)),
+                ("severity", Json::number(1)),
+                ("source", Json::string("moss")),
+                ("message", Json::string(&d.text))
+            ])
+        ).collect();
+        send(output, &Json::map(vec![
+            ("jsonrpc", Json::string("2.0")),
+            ("method", Json::string("textDocument/publishDiagnostics")),
+            ("params", Json::map(vec![
+                ("uri", Json::string(uri)),
+                ("diagnostics", Json::List(diagnostics))
+            ]))
+        ]))
+    }
+
+    fn notification(&mut self, output: &mut impl Write, method: &str, params: &Json)
+    -> io::Result<()>
+    {
+        let uri = params.path(&["textDocument", "uri"]).as_str().unwrap_or("").to_string();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params.path(&["textDocument", "text"]).as_str().unwrap_or("");
+                self.documents.insert(uri.clone(), text.to_string());
+                self.publish_diagnostics(output, &uri)?;
+            },
+            "textDocument/didChange" => {
+                // Only full synchronization is announced, thus the
+                // last change contains the whole text.
+                if let Json::List(a) = params.get("contentChanges") {
+                    if let Some(text) = a.last().and_then(|x| x.get("text").as_str()) {
+                        self.documents.insert(uri.clone(), text.to_string());
+                    }
+                }
+                self.publish_diagnostics(output, &uri)?;
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, (i64, String)> {
+        let uri = params.path(&["textDocument", "uri"]).as_str().unwrap_or("");
+        let path = uri_to_path(uri);
+        let s = self.documents.get(uri).map(|s| s.as_str()).unwrap_or("");
+        let text = Text::new(s, self.utf16);
+        let line = params.path(&["position", "line"]).as_usize().unwrap_or(0) + 1;
+        let col = text.column(line, params.path(&["position", "character"]).as_usize().unwrap_or(0));
+        Ok(match method {
+            "initialize" => {
+                // Characters are counted directly if the client
+                // supports it, otherwise in the mandatory UTF-16.
+                let utf32 = match params.path(&["capabilities", "general", "positionEncodings"]) {
+                    Json::List(a) => a.iter().any(|x| x.as_str() == Some("utf-32")),
+                    _ => false
+                };
+                self.utf16 = !utf32;
+                Json::map(vec![
+                    ("capabilities", Json::map(vec![
+                        ("positionEncoding", Json::string(if utf32 {"utf-32"} else {"utf-16"})),
+                        ("textDocumentSync", Json::number(1)),
+                        ("documentSymbolProvider", Json::Bool(true)),
+                        ("definitionProvider", Json::Bool(true)),
+                        ("hoverProvider", Json::Bool(true))
+                    ])),
+                    ("serverInfo", Json::map(vec![
+                        ("name", Json::string("moss-lsp")),
+                        ("version", Json::string(env!("CARGO_PKG_VERSION")))
+                    ]))
+                ])
+            },
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            },
+            "textDocument/documentSymbol" => {
+                match outline::outline(s, &path.to_string_lossy()) {
+                    Ok(a) => Json::List(a.iter().map(|d| document_symbol(&text, d)).collect()),
+                    Err(_) => Json::Null
+                }
+            },
+            "textDocument/definition" => {
+                match outline::definition(s, &path, line, col) {
+                    Some(loc) => {
+                        let (file, range) = match loc.file {
+                            Some(file) => {
+                                let s = std::fs::read_to_string(&file).unwrap_or_default();
+                                let range = Text::new(&s, self.utf16)
+                                    .range(loc.line, loc.col, loc.line, loc.col);
+                                (file, range)
+                            },
+                            None => (path, text.range(loc.line, loc.col, loc.line, loc.col))
+                        };
+                        Json::map(vec![
+                            ("uri", Json::String(path_to_uri(&file))),
+                            ("range", range)
+                        ])
+                    },
+                    None => Json::Null
+                }
+            },
+            "textDocument/hover" => {
+                match outline::hover(s, &path, line, col) {
+                    Some(s) => Json::map(vec![
+                        ("contents", Json::map(vec![
+                            ("kind", Json::string("markdown")),
+                            ("value", Json::String(format!("```moss\n{}\n```", s)))
+                        ]))
+                    ]),
+                    None => Json::Null
+                }
+            },
+            _ => return Err((-32601, format!("method not found: {}", method)))
+        })
+    }
+}
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut server = Server {documents: HashMap::new(), shutdown: false, utf16: true};
+
+    while let Some(message) = read_message(&mut input)? {
+        let method = message.get("method").as_str().unwrap_or("");
+        let id = message.get("id");
+        if method == "exit" {
+            std::process::exit(if server.shutdown {0} else {1});
+        }
+        if *id == Json::Null {
+            server.notification(&mut output, method, message.get("params"))?;
+            continue;
+        }
+        let response = match server.request(method, message.get("params")) {
+            Ok(result) => vec![("result", result)],
+            Err((code, text)) => vec![("error", Json::map(vec![
+                ("code", Json::Number(code as f64)),
+                ("message", Json::String(text))
+            ]))]
+        };
+        let mut a = vec![("jsonrpc", Json::string("2.0")), ("id", id.clone())];
+        a.extend(response);
+        send(&mut output, &Json::map(a))?;
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
 
 use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
 use moss::object::{Object,Map};
 use moss::CompilerExtra;
 
@@ -21,12 +24,46 @@ Options:
 -e "1+2"    Evaluate some Moss code inline.
 
 -unsafe     Unsafe mode: run the interpreter in privileged mode,
-            which allows write access to files, network access
-            and execution of shell commands. Running an untrusted
-            module in unsafe mode is a security hazard.
+            which allows write access to files, network access,
+            access to environment variables and execution of shell
+            commands. Running an untrusted module in unsafe mode
+            is a security hazard.
+
+-allow-read=DIR, -deny-read=DIR
+-allow-write=DIR, -deny-write=DIR
+            Allow or deny access to the files below a directory.
+            If -allow-read is given, reading is restricted to the
+            allowed directories and the absolute module search
+            paths. The rule with the longest directory wins.
+
+-allow-cmd  Allow execution of shell commands.
+
+-allow-env  Allow access to environment variables.
 
 -c          Compile a module to reduce load time.
             Only needed for very large modules.
+
+-lint file ...
+            Check source files for undefined variables, unused
+            variables, unreachable code and other common mistakes.
+            The exit status is 1 if something was found.
+
+-test [-filter name] [path]
+            Run the test files in a directory, by default 'test',
+            or a single test file. The functions named test_* are
+            called one by one, a file without such functions is a
+            single test. The filter selects the tests by name.
+
+-doc [-md] [-o file] module
+            Generate the documentation of a module from its
+            public names, docstrings and function signatures,
+            as an HTML page or with -md as Markdown.
+
+Tools:
+moss fmt [-check] [file ...]
+            Format source files in place, or stdin to stdout.
+            With -check, nothing is written, but the exit status
+            is 1 if some file is not formatted.
 "#;
 
 fn is_option(s: &str) -> bool {
@@ -38,6 +75,53 @@ struct IFile {
     e: bool
 }
 
+enum Access {
+    AllowRead(String), DenyRead(String),
+    AllowWrite(String), DenyWrite(String),
+    Command, Env
+}
+
+fn parse_access(s: &str) -> Option<Access> {
+    if let Some(dir) = s.strip_prefix("-allow-read=") {
+        Some(Access::AllowRead(dir.to_string()))
+    } else if let Some(dir) = s.strip_prefix("-deny-read=") {
+        Some(Access::DenyRead(dir.to_string()))
+    } else if let Some(dir) = s.strip_prefix("-allow-write=") {
+        Some(Access::AllowWrite(dir.to_string()))
+    } else if let Some(dir) = s.strip_prefix("-deny-write=") {
+        Some(Access::DenyWrite(dir.to_string()))
+    } else if s == "-allow-cmd" {
+        Some(Access::Command)
+    } else if s == "-allow-env" {
+        Some(Access::Env)
+    } else {
+        None
+    }
+}
+
+fn set_access(i: &moss::Interpreter, unsafe_mode: bool, access: &[Access]) {
+    i.set_capabilities(unsafe_mode);
+    if access.iter().any(|x| matches!(x, Access::AllowRead(_))) {
+        i.set_read_access(false);
+        let paths: Vec<String> = i.rte.path.borrow().v.iter().filter_map(|x| {
+            match *x {Object::String(ref s) => Some(s.to_string()), _ => None}
+        }).collect();
+        for path in paths.iter().filter(|s| Path::new(s).is_absolute()) {
+            i.allow_read(path);
+        }
+    }
+    for x in access {
+        match x {
+            Access::AllowRead(dir) => i.allow_read(dir),
+            Access::DenyRead(dir) => i.deny_read(dir),
+            Access::AllowWrite(dir) => i.allow_write(dir),
+            Access::DenyWrite(dir) => i.deny_write(dir),
+            Access::Command => i.allow_command(true),
+            Access::Env => i.allow_env(true)
+        }
+    }
+}
+
 struct Info {
     program: Option<String>,
     ifile: Vec<IFile>,
@@ -46,7 +130,8 @@ struct Info {
     exit: bool,
     compile: bool,
     debug_mode: bool,
-    unsafe_mode: bool
+    unsafe_mode: bool,
+    access: Vec<Access>
 }
 
 impl Info{
@@ -59,7 +144,8 @@ impl Info{
             exit: false,
             debug_mode: true,
             compile: false,
-            unsafe_mode: false
+            unsafe_mode: false,
+            access: Vec::new()
         };
         let mut first = true;
         let mut ifile = false;
@@ -89,6 +175,8 @@ impl Info{
                     info.compile = true;
                 } else if s == "-unsafe" {
                     info.unsafe_mode = true;
+                } else if let Some(access) = parse_access(&s) {
+                    info.access.push(access);
                 } else {
                     info.ifile.push(IFile {s: s[1..].to_string(), e: false});
                 }
@@ -107,13 +195,175 @@ impl Info{
     }
 }
 
+fn format_files(args: &[String]) -> i32 {
+    let check = args.iter().any(|s| s == "-check");
+    let files: Vec<&String> = args.iter().filter(|s| *s != "-check").collect();
+    if files.is_empty() {
+        let mut s = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut s) {
+            eprintln!("Error: {}", e);
+            return 2;
+        }
+        return match moss::formatter::format(&s, "stdin") {
+            Ok(y) => {
+                if check {
+                    if y == s {0} else {1}
+                } else {
+                    print!("{}", y);
+                    0
+                }
+            },
+            Err(e) => {eprintln!("{}", e); 2}
+        };
+    }
+    let mut status = 0;
+    for file in files {
+        let s = match fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Error: could not read '{}': {}", file, e);
+                status = 2;
+                continue;
+            }
+        };
+        match moss::formatter::format(&s, file) {
+            Ok(y) => {
+                if y == s {continue;}
+                if check {
+                    println!("{}", file);
+                    status = status.max(1);
+                } else if let Err(e) = fs::write(file, y) {
+                    eprintln!("Error: could not write '{}': {}", file, e);
+                    status = 2;
+                }
+            },
+            Err(e) => {
+                eprintln!("{}", e);
+                status = 2;
+            }
+        }
+    }
+    status
+}
+
+fn lint_files(files: &[String]) -> i32 {
+    let i = moss::Interpreter::new();
+    let mut known: Vec<String> = i.rte.gtab.borrow().m.keys().filter_map(|key| {
+        match *key {Object::String(ref s) => Some(s.to_string()), _ => None}
+    }).collect();
+    known.sort();
+    let mut status = 0;
+    for file in files {
+        let s = match fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Error: could not read '{}': {}", file, e);
+                status = 2;
+                continue;
+            }
+        };
+        match moss::lint::lint(&s, file, &known) {
+            Ok(a) => {
+                for d in &a {
+                    println!("{}:{}:{}: {}", file, d.line, d.col, d.text);
+                }
+                if !a.is_empty() {status = status.max(1);}
+            },
+            Err(d) => {
+                println!("{}:{}:{}: Syntax error: {}", file, d.line, d.col, d.text);
+                status = 2;
+            }
+        }
+    }
+    status
+}
+
+fn run_tests(args: &[String]) -> i32 {
+    let mut path: Option<&str> = None;
+    let mut filter: Option<&str> = None;
+    let mut unsafe_mode = false;
+    let mut access: Vec<Access> = Vec::new();
+    let mut k = 0;
+    while k < args.len() {
+        if args[k] == "-filter" && k+1 < args.len() {
+            filter = Some(&args[k+1]);
+            k += 1;
+        } else if args[k] == "-unsafe" {
+            unsafe_mode = true;
+        } else if let Some(x) = parse_access(&args[k]) {
+            access.push(x);
+        } else {
+            path = Some(&args[k]);
+        }
+        k += 1;
+    }
+    let path = path.unwrap_or(if Path::new("test").is_dir() {"test"} else {"."});
+    let i = moss::Interpreter::new();
+    i.set_config(CompilerExtra{debug_mode: true});
+    set_access(&i, unsafe_mode, &access);
+    let mut ilock = i.lock();
+    let mut env = ilock.env();
+    moss::testing::run(&mut env, path, filter)
+}
+
+fn generate_doc(args: &[String]) -> i32 {
+    let mut id: Option<&str> = None;
+    let mut output: Option<&str> = None;
+    let mut format = moss::docgen::Format::Html;
+    let mut k = 0;
+    while k < args.len() {
+        if args[k] == "-md" {
+            format = moss::docgen::Format::Markdown;
+        } else if args[k] == "-o" && k+1 < args.len() {
+            output = Some(&args[k+1]);
+            k += 1;
+        } else {
+            id = Some(&args[k]);
+        }
+        k += 1;
+    }
+    let id = match id {
+        Some(id) => id,
+        None => {eprintln!("Usage: moss -doc [-md] [-o file] module"); return 2;}
+    };
+    let i = moss::Interpreter::new();
+    let mut ilock = i.lock();
+    let mut env = ilock.env();
+    let s = match moss::docgen::generate(&mut env, id, format) {
+        Ok(s) => s,
+        Err(e) => {eprintln!("{}", e); return 2;}
+    };
+    match output {
+        Some(path) => if let Err(e) = fs::write(path, s) {
+            eprintln!("Error: could not write '{}': {}", path, e);
+            return 2;
+        },
+        None => print!("{}", s)
+    }
+    0
+}
+
 fn main(){
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 1 && args[1] == "fmt" {
+        std::process::exit(format_files(&args[2..]));
+    }
+    if args.len() > 1 && args[1] == "-lint" {
+        std::process::exit(lint_files(&args[2..]));
+    }
+    if args.len() > 1 && args[1] == "-test" {
+        std::process::exit(run_tests(&args[2..]));
+    }
+    if args.len() > 1 && args[1] == "-doc" {
+        std::process::exit(generate_doc(&args[2..]));
+    }
+
     let mut info = Info::new();
     let i = moss::Interpreter::new();
     i.set_config(CompilerExtra{
         debug_mode: info.debug_mode
     });
-    i.set_capabilities(info.unsafe_mode);
+    set_access(&i, info.unsafe_mode, &info.access);
 
     let gtab = Map::new();
     i.rte.clear_at_exit(gtab.clone());
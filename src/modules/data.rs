@@ -300,6 +300,16 @@ pub fn base16(data: &[u8]) -> Object {
     Object::from(&buffer[..])
 }
 
+const HASH_DOCS: &[(&str, &str)] = &[
+    ("push", "h.push(a)\nFeeds the byte list a into the hash h and returns h."),
+    ("value", "h.value()\nThe hash value of the data pushed into h.")
+];
+
+const DOCS: &[(&str, &str)] = &[
+    ("bytes", "bytes(a)\nA byte list from a list of integers less than 256."),
+    ("hash", "hash()\nA new SHA3-256 hash state.")
+];
+
 pub fn load_data(env: &mut Env) -> Object
 {
     let type_hash = Class::new("Hash", &Object::Null);
@@ -307,6 +317,7 @@ pub fn load_data(env: &mut Env) -> Object
         let mut m = type_hash.map.borrow_mut();
         m.insert_fn_plain("push", crate::data::hash_push, 1, 1);
         m.insert_fn_plain("value", crate::data::hash_value, 0, 0);
+        m.set_docs(HASH_DOCS);
     }
     interface_types_set(env.rte(),interface_index::HASH,type_hash.clone());
 
@@ -315,6 +326,7 @@ pub fn load_data(env: &mut Env) -> Object
         let mut m = data.map.borrow_mut();
         m.insert_fn_plain("bytes", bytes, 1, 1);
         m.insert_fn_plain("hash", data_hash, 1, 1);
+        m.set_docs(DOCS);
 
         let type_bytes = env.rte().interface_types
             .borrow()[interface_index::BYTES].clone();
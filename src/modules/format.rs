@@ -15,16 +15,16 @@ fn get(env: &Env, a: &Object, i: usize) -> FnResult {
         },
         Object::Map(ref m) => {
             let d = &m.borrow_mut().m;
-            if let Ok(index) = i32::try_from(i) {
-                if let Some(value) = d.get(&Object::Int(index)) {
-                    return Ok(value.clone());
-                }
-            } else {
-                return env.index_error(
-                    "Index error in m[key]: key outside of i32 range.");
+            let index = match i32::try_from(i) {
+                Ok(index) => index,
+                Err(_) => return env.index_error(
+                    "Index error in m[key]: key outside of i32 range.")
+            };
+            if let Some(value) = d.get(&Object::Int(index)) {
+                return Ok(value.clone());
             }
-            env.index_error(
-                "Index error in m[key]: key not found.")
+            env.key_error(
+                "Index error in m[key]: key not found.", &Object::Int(index))
         },
         _ => env.type_error("Type error in a[i]: a is not a list.")
     }
@@ -36,9 +36,9 @@ fn get_key(env: &Env, m: &Object, key: &Object) -> FnResult {
             let d = &m.borrow_mut().m;
             match d.get(key) {
                 Some(value) => Ok(value.clone()),
-                None => env.index_error(&format!(
+                None => env.key_error(&format!(
                     "Index error in m[{0}]: {0} not found.", key.to_repr()
-                ))
+                ), key)
             }
         },
         _ => env.type_error("Type error in m[key]: m is not a map.")
@@ -1,21 +1,98 @@
 
-use std::rc::Rc;
-use std::cell::RefCell;
+use std::rc::{Rc,Weak};
+use std::cell::{RefCell,RefMut};
 use std::any::Any;
 use std::fs;
-use std::io::{Read,Write};
+use std::io;
+use std::io::{Read,Write,BufRead,BufReader,Seek,SeekFrom};
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 
 use crate::object::{
-    Object, List, Interface, Exception, FnResult,
+    Object, List, Map, CharString, Interface, Exception, FnResult,
     new_module, downcast, interface_object_get
 };
-use crate::vm::{Env,interface_types_set,interface_index};
+use crate::vm::{Env,Flush,interface_types_set,interface_index};
 use crate::data::{Bytes};
 use crate::class::Class;
+use crate::iterable::new_iterator;
+use crate::long::Long;
+
+const BUFFER_SIZE: usize = 0x10000;
+
+// A file with buffered reading and writing. At any time, at most one
+// of both buffers is nonempty: pending writes are flushed before
+// reading, and read-ahead data is discarded before writing, so that
+// the position of the underlying file stays consistent.
+struct Stream {
+    reader: BufReader<fs::File>,
+    wbuf: Vec<u8>
+}
+
+impl Stream {
+    fn new(file: fs::File) -> Self {
+        Self {reader: BufReader::with_capacity(BUFFER_SIZE,file), wbuf: Vec::new()}
+    }
+
+    fn flush_writes(&mut self) -> io::Result<()> {
+        if !self.wbuf.is_empty() {
+            self.reader.get_mut().write_all(&self.wbuf)?;
+            self.wbuf.clear();
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        if !self.reader.buffer().is_empty() {
+            let pos = self.reader.stream_position()?;
+            self.reader.seek(SeekFrom::Start(pos))?;
+        }
+        self.wbuf.extend_from_slice(data);
+        if self.wbuf.len() >= BUFFER_SIZE {
+            self.flush_writes()?;
+        }
+        Ok(())
+    }
+
+    fn read_to_end(&mut self, buffer: &mut Vec<u8>) -> io::Result<usize> {
+        self.flush_writes()?;
+        self.reader.read_to_end(buffer)
+    }
+
+    fn read_max(&mut self, n: u64, buffer: &mut Vec<u8>) -> io::Result<usize> {
+        self.flush_writes()?;
+        (&mut self.reader).take(n).read_to_end(buffer)
+    }
+
+    fn read_line(&mut self, buffer: &mut Vec<u8>) -> io::Result<usize> {
+        self.flush_writes()?;
+        self.reader.read_until(b'\n',buffer)
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.flush_writes()?;
+        self.reader.seek(pos)
+    }
+
+    fn tell(&mut self) -> io::Result<u64> {
+        self.flush_writes()?;
+        self.reader.stream_position()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_writes()?;
+        self.reader.get_mut().flush()
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        let _ = self.flush_writes();
+    }
+}
 
 struct File {
-    file: RefCell<fs::File>,
+    stream: RefCell<Option<Stream>>,
     id: String
 }
 
@@ -32,125 +109,308 @@ impl Interface for File {
     }
 }
 
+impl Flush for File {
+    fn flush(&self) {
+        if let Some(ref mut stream) = *self.stream.borrow_mut() {
+            let _ = stream.flush();
+        }
+    }
+}
+
+fn stream<'a>(env: &mut Env, pself: &'a Object, id: &str)
+-> Result<(RefMut<'a,Stream>,&'a str),Box<Exception>>
+{
+    let file = match downcast::<File>(pself) {
+        Some(file) => file,
+        None => return Err(env.type_error_plain(&format!(
+            "Type error in f.{}: f is not a file.", id)))
+    };
+    match RefMut::filter_map(file.stream.borrow_mut(), |x| x.as_mut()) {
+        Ok(stream) => Ok((stream,&file.id)),
+        Err(_) => Err(env.value_error_plain(&format!(
+            "Value error in f.{}: the file '{}' is closed.", id, file.id)))
+    }
+}
+
+fn line_object(env: &mut Env, buffer: Vec<u8>, id: &str, path: &str) -> FnResult {
+    match String::from_utf8(buffer) {
+        Ok(s) => Ok(CharString::new_object_str(&s)),
+        Err(_) => env.value_error(&format!(
+            "Value error in f.{}: invalid UTF-8 in file '{}'.", id, path))
+    }
+}
+
+fn path_arg(env: &mut Env, x: &Object, id: &str, name: &str)
+-> Result<String,Box<Exception>>
+{
+    match *x {
+        Object::String(ref s) => Ok(s.to_string()),
+        _ => Err(env.type_error_plain(&format!(
+            "Type error in {}: {} is not a string.", id, name)))
+    }
+}
+
+fn check_read(env: &mut Env, path: &str, id: &str) -> Result<(),Box<Exception>> {
+    if env.rte().read_access(path) {return Ok(());}
+    Err(env.permission_error_plain(&format!(
+        "Error in {}: permission denied.\nNote: path = '{}'.", id, path), path))
+}
+
+fn check_write(env: &mut Env, path: &str, id: &str) -> Result<(),Box<Exception>> {
+    if env.rte().write_access(path) {return Ok(());}
+    Err(env.permission_error_plain(&format!(
+        "Error in {}: permission denied.\nNote: path = '{}'.", id, path), path))
+}
+
+// While path-scoped write rules are active, a file is not written
+// through a symbolic link in place of its last component, as the
+// link might have been redirected after the rules were checked.
+#[cfg(unix)]
+fn no_follow(env: &Env, options: &mut fs::OpenOptions) {
+    use std::os::unix::fs::OpenOptionsExt;
+    if env.rte().write_rules_active() {
+        options.custom_flags(libc::O_NOFOLLOW);
+    }
+}
+
+#[cfg(not(unix))]
+fn no_follow(_env: &Env, _options: &mut fs::OpenOptions) {}
+
+fn io_error(env: &mut Env, e: io::Error, s: &str, path: &str) -> FnResult {
+    env.os_error(&format!("{}\nNote: path = '{}'.", s, path),
+        e.raw_os_error(), path)
+}
+
+fn uint_object(n: u64) -> Object {
+    if n <= i32::MAX as u64 {
+        Object::Int(n as i32)
+    } else {
+        let digits: Vec<char> = n.to_string().chars().collect();
+        Long::object_from_string(&digits).unwrap_or(Object::Float(n as f64))
+    }
+}
+
 fn open(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     let mode = match argv.len() {
-        1 => {'r'},
-        2 => {
-            match argv[1] {
-                Object::String(ref s) => {
-                    if s.data == ['w'] {'w'} else {'r'}
-                },
-                ref x => return env.type_error1(
-                    "Type error in open(path,mode): mode is not a string.","mode",x)
-            }
+        1 => String::from("r"),
+        2 => match argv[1] {
+            Object::String(ref s) => s.to_string(),
+            ref x => return env.type_error1(
+                "Type error in open(path,mode): mode is not a string.","mode",x)
         },
         n => return env.argc_error(n,1,2,"open")
     };
-    let file_id: String = match argv[0] {
-        Object::String(ref s) => s.to_string(),
-        ref x => return env.type_error1(
-            "Type error in open(path): path is not a string.","path",x)
-    };
-    let open_result = if mode=='r' {
-        if !env.rte().read_access(&file_id) {
-            return env.std_exception(&format!(
-                "Error in open(path): permission denied.\n\
-                 Note: path = '{}'", file_id
-            ));
-        }
-        fs::File::open(&file_id)
-    } else {
-        if !env.rte().write_access(&file_id) {
-            return env.std_exception(&format!(
-                "Error in open(path,'w'): permission denied.\n\
-                 Note: path = '{}'.", file_id
-            ));
-        }
-        fs::File::create(&file_id)
+    let file_id = path_arg(env,&argv[0],"open(path)","path")?;
+    let mut options = fs::OpenOptions::new();
+    let (read,write) = match mode.as_str() {
+        "r"  => (true,false),
+        "w"  => {options.create(true).truncate(true); (false,true)},
+        "a"  => {options.create(true).append(true); (false,true)},
+        "r+" => (true,true),
+        "w+" => {options.create(true).truncate(true); (true,true)},
+        "a+" => {options.create(true).append(true); (true,true)},
+        _ => return env.value_error(&format!(
+            "Value error in open(path,mode): unknown mode '{}'.\n\
+             Note: the modes are 'r', 'w', 'a', 'r+', 'w+', 'a+'.", mode))
     };
-    let file = match open_result {
+    if read {check_read(env,&file_id,"open(path)")?;}
+    if write {
+        check_write(env,&file_id,&format!("open(path,'{}')",mode))?;
+        no_follow(env,&mut options);
+    }
+    let file = match options.read(read).write(write).open(&file_id) {
         Ok(file) => file,
-        Err(_) => return env.std_exception(
+        Err(e) => return env.os_error(
             &format!("Error in open(path): could not open file, path = '{}'.",
-            file_id))
+            file_id), e.raw_os_error(), &file_id)
     };
-    let f = File {
-        file: RefCell::new(file),
+    let f = Rc::new(File {
+        stream: RefCell::new(Some(Stream::new(file))),
         id: file_id
-    };
-    Ok(Object::Interface(Rc::new(f)))
+    });
+    let weak: Weak<File> = Rc::downgrade(&f);
+    env.rte().register_buffer(weak);
+    Ok(Object::Interface(f))
 }
 
 fn file_read(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
-    if let Some(file) = downcast::<File>(pself) {
-        match argv.len() {
-            0 => {
-                let mut buffer: Vec<u8> = Vec::new();
-                match file.file.borrow_mut().read_to_end(&mut buffer) {
-                    Ok(_) => {},
-                    Err(_) => return env.std_exception(&format!(
-                        "Error in f.read(): Could not read file '{}'.",
-                        file.id))
-                }
-                Ok(Bytes::object_from_vec(buffer))
-            },
-            1 => {
-                let n = if let Object::Int(x) = argv[0] {
-                    if x < 0 {0} else {x as usize}
-                } else {
-                    return env.type_error("Type error in f.read(n): n is not an integer.");
-                };
-                let mut buffer: Vec<u8> = vec![0;n];
-                match file.file.borrow_mut().read(&mut buffer) {
-                    Ok(count) => {buffer.truncate(count)},
-                    Err(_) => return env.std_exception(&format!(
-                        "Error in f.read(n): Could not read file '{}'.",
-                        file.id))
-                }
-                Ok(Bytes::object_from_vec(buffer))
-            },
-            n => env.argc_error(n,0,1,"read")
-        }
-    } else {
-        env.type_error("Type error in f.read(): f is not a file.")
+    let n = match argv.len() {
+        0 => None,
+        1 => match argv[0] {
+            Object::Int(x) => Some(if x < 0 {0} else {x as u64}),
+            _ => return env.type_error("Type error in f.read(n): n is not an integer.")
+        },
+        n => return env.argc_error(n,0,1,"read")
+    };
+    let (mut stream,id) = stream(env,pself,"read()")?;
+    let mut buffer: Vec<u8> = Vec::new();
+    let result = match n {
+        Some(n) => stream.read_max(n,&mut buffer),
+        None => stream.read_to_end(&mut buffer)
+    };
+    match result {
+        Ok(_) => Ok(Bytes::object_from_vec(buffer)),
+        Err(e) => env.io_error(&format!(
+            "Error in f.read(): Could not read file '{}'.",
+            id), e.raw_os_error(), id)
     }
 }
 
+fn file_readline(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"readline")
+    }
+    let (mut stream,id) = stream(env,pself,"readline()")?;
+    let mut buffer: Vec<u8> = Vec::new();
+    if let Err(e) = stream.read_line(&mut buffer) {
+        return env.io_error(&format!(
+            "Error in f.readline(): Could not read file '{}'.",
+            id), e.raw_os_error(), id);
+    }
+    drop(stream);
+    line_object(env,buffer,"readline()",id)
+}
+
+fn file_lines(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"lines")
+    }
+    stream(env,pself,"lines()")?;
+    let file = pself.clone();
+    let f = Box::new(move |env: &mut Env, _pself: &Object, _argv: &[Object]| -> FnResult {
+        let (mut stream,id) = stream(env,&file,"lines()")?;
+        let mut buffer: Vec<u8> = Vec::new();
+        match stream.read_line(&mut buffer) {
+            Ok(0) => return Ok(Object::empty()),
+            Ok(_) => {},
+            Err(e) => return env.io_error(&format!(
+                "Error in f.lines(): Could not read file '{}'.",
+                id), e.raw_os_error(), id)
+        }
+        drop(stream);
+        if buffer.last() == Some(&b'\n') {
+            buffer.pop();
+            if buffer.last() == Some(&b'\r') {buffer.pop();}
+        }
+        line_object(env,buffer,"lines()",id)
+    });
+    Ok(new_iterator(f))
+}
+
 fn file_write(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         1 => {}, n => return env.argc_error(n,1,1,"write")
     }
-    if let Some(file) = downcast::<File>(pself) {
-        if let Object::String(ref s) = argv[0] {
-            let data = &s.to_string().into_bytes();
-            if let Ok(()) = file.file.borrow_mut().write_all(data) {
-                return Ok(Object::Null);
-            }
-        } else if let Some(a) = downcast::<Bytes>(&argv[0]) {
-            let data = &a.data.borrow();
-            if let Ok(()) = file.file.borrow_mut().write_all(data) {
-                return Ok(Object::Null);
-            }
-        } else {
-            return env.type_error1(
-                "Type error in f.write(data): data must be a string or binary data.",
-                 "data",&argv[0]);
-        }
-        env.std_exception(&format!(
-            "Error in f.write(data): failed to write to file '{}'.",file.id))
+    let (mut stream,id) = stream(env,pself,"write(data)")?;
+    let result = if let Object::String(ref s) = argv[0] {
+        stream.write(s.to_string().as_bytes())
+    } else if let Some(a) = downcast::<Bytes>(&argv[0]) {
+        stream.write(&a.data.borrow())
     } else {
-        env.type_error("Type error in f.write(data): f is not a file.")
+        drop(stream);
+        return env.type_error1(
+            "Type error in f.write(data): data must be a string or binary data.",
+             "data",&argv[0]);
+    };
+    match result {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => env.io_error(&format!(
+            "Error in f.write(data): failed to write to file '{}'.",id),
+            e.raw_os_error(), id)
+    }
+}
+
+fn offset_arg(x: &Object) -> Option<i64> {
+    match *x {
+        Object::Int(x) => Some(i64::from(x)),
+        ref x => downcast::<Long>(x).map(|x| x.as_f64() as i64)
+    }
+}
+
+fn file_seek(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    let whence = match argv.len() {
+        1 => 0,
+        2 => match argv[1] {
+            Object::Int(x) => x,
+            _ => return env.type_error(
+                "Type error in f.seek(offset,whence): whence is not an integer.")
+        },
+        n => return env.argc_error(n,1,2,"seek")
+    };
+    let offset = match offset_arg(&argv[0]) {
+        Some(x) => x,
+        None => return env.type_error(
+            "Type error in f.seek(offset): offset is not an integer.")
+    };
+    let pos = match whence {
+        0 if offset >= 0 => SeekFrom::Start(offset as u64),
+        0 => return env.value_error(
+            "Value error in f.seek(offset): offset is negative."),
+        1 => SeekFrom::Current(offset),
+        2 => SeekFrom::End(offset),
+        _ => return env.value_error(
+            "Value error in f.seek(offset,whence): whence is not 0, 1 or 2.")
+    };
+    let (mut stream,id) = stream(env,pself,"seek(offset)")?;
+    match stream.seek(pos) {
+        Ok(pos) => Ok(uint_object(pos)),
+        Err(e) => env.io_error(&format!(
+            "Error in f.seek(offset): could not seek in file '{}'.",id),
+            e.raw_os_error(), id)
     }
 }
 
+fn file_tell(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"tell")
+    }
+    let (mut stream,id) = stream(env,pself,"tell()")?;
+    match stream.tell() {
+        Ok(pos) => Ok(uint_object(pos)),
+        Err(e) => env.io_error(&format!(
+            "Error in f.tell(): could not determine the position in file '{}'.",id),
+            e.raw_os_error(), id)
+    }
+}
+
+fn file_flush(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"flush")
+    }
+    let (mut stream,id) = stream(env,pself,"flush()")?;
+    match stream.flush() {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => env.io_error(&format!(
+            "Error in f.flush(): failed to write to file '{}'.",id),
+            e.raw_os_error(), id)
+    }
+}
+
+fn file_close(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"close")
+    }
+    let file = match downcast::<File>(pself) {
+        Some(file) => file,
+        None => return env.type_error("Type error in f.close(): f is not a file.")
+    };
+    let stream = file.stream.borrow_mut().take();
+    if let Some(mut stream) = stream {
+        if let Err(e) = stream.flush() {
+            return env.io_error(&format!(
+                "Error in f.close(): failed to write to file '{}'.",file.id),
+                e.raw_os_error(), &file.id);
+        }
+    }
+    Ok(Object::Null)
+}
+
 fn is_file(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         1 => {}, n => return env.argc_error(n,1,1,"is_file")
     }
-    let file_id: String = match argv[0] {
-        Object::String(ref s) => s.to_string(),
-        _ => return env.type_error("Type error in is_file(id): id is not a string.")
-    };
+    let file_id = path_arg(env,&argv[0],"is_file(path)","path")?;
+    check_read(env,&file_id,"is_file(path)")?;
     let path = Path::new(&file_id);
     Ok(Object::Bool(path.is_file()))
 }
@@ -159,10 +419,8 @@ fn is_dir(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         1 => {}, n => return env.argc_error(n,1,1,"is_dir")
     }
-    let file_id: String = match argv[0] {
-        Object::String(ref s) => s.to_string(),
-        _ => return env.type_error("Type error in is_dir(id): id is not a string.")
-    };
+    let file_id = path_arg(env,&argv[0],"is_dir(path)","path")?;
+    check_read(env,&file_id,"is_dir(path)")?;
     let path = Path::new(&file_id);
     Ok(Object::Bool(path.is_dir()))
 }
@@ -170,18 +428,16 @@ fn is_dir(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
 fn read_dir(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     let file_id: String = match argv.len() {
         0 => String::from("."),
-        1 => match argv[0] {
-            Object::String(ref s) => s.to_string(),
-            _ => return env.type_error(
-                "Type error in ls(path): path is not a string.")
-        },
+        1 => path_arg(env,&argv[0],"ls(path)","path")?,
         n => return env.argc_error(n,0,1,"ls")
     };
+    check_read(env,&file_id,"ls(path)")?;
     let path = Path::new(&file_id);
     let it = match path.read_dir() {
         Ok(it) => it,
-        Err(_) => return env.std_exception(&format!(
-            "Error in ls(path): Could not read directory '{}'.",file_id))
+        Err(e) => return env.os_error(&format!(
+            "Error in ls(path): Could not read directory '{}'.",file_id),
+            e.raw_os_error(), &file_id)
     };
     let mut acc: Vec<Object> = Vec::new();
     for x in it {
@@ -198,16 +454,14 @@ fn change_dir(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         1 => {}, n => return env.argc_error(n,1,1,"cd")
     }
-    let dir_id: String = match argv[0] {
-        Object::String(ref s) => s.to_string(),
-        _ => return env.type_error("Type error in cd(path): path is not a string.")
-    };
+    let dir_id = path_arg(env,&argv[0],"cd(path)","path")?;
+    check_read(env,&dir_id,"cd(path)")?;
     let path = Path::new(&dir_id);
-    if std::env::set_current_dir(&path).is_err() {
-        env.std_exception(&format!(
-            "Error: could not change to directory '{}'.",dir_id))
-    } else {
-        Ok(Object::Null)
+    match std::env::set_current_dir(&path) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => env.os_error(&format!(
+            "Error: could not change to directory '{}'.",dir_id),
+            e.raw_os_error(), &dir_id)
     }
 }
 
@@ -217,24 +471,310 @@ fn working_dir(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     }
     let path = match std::env::current_dir() {
         Ok(path) => path,
-        Err(_) => return env.std_exception(
-            "Error in wd(): could not determine the working directory.")
+        Err(e) => return env.os_error(
+            "Error in wd(): could not determine the working directory.",
+            e.raw_os_error(), "")
     };
     if let Some(s) = path.to_str() {
         Ok(Object::from(s))
     } else {
-        env.std_exception(
+        env.value_error(
             "Error in wd(): could not encode the working directory as UTF-8.")
     }
 }
 
+fn file_type(t: fs::FileType) -> &'static str {
+    if t.is_symlink() {"link"}
+    else if t.is_dir() {"dir"}
+    else if t.is_file() {"file"}
+    else {"other"}
+}
+
+#[cfg(unix)]
+fn permissions(m: &fs::Metadata) -> i32 {
+    use std::os::unix::fs::PermissionsExt;
+    (m.permissions().mode() & 0o7777) as i32
+}
+
+#[cfg(not(unix))]
+fn permissions(m: &fs::Metadata) -> i32 {
+    if m.permissions().readonly() {0o444} else {0o666}
+}
+
+fn stat(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    let (file_id,follow) = match argv.len() {
+        1 => (path_arg(env,&argv[0],"stat(path)","path")?, true),
+        2 => match argv[1] {
+            Object::Bool(follow) => (path_arg(env,&argv[0],"stat(path)","path")?, follow),
+            ref x => return env.type_error1(
+                "Type error in stat(path,follow): follow is not a boolean.","follow",x)
+        },
+        n => return env.argc_error(n,1,2,"stat")
+    };
+    check_read(env,&file_id,"stat(path)")?;
+    let result = if follow {fs::metadata(&file_id)} else {fs::symlink_metadata(&file_id)};
+    let m = match result {
+        Ok(m) => m,
+        Err(e) => return io_error(env,e,
+            "Error in stat(path): could not read the file status.",&file_id)
+    };
+    let mtime = match m.modified().map(|t| t.duration_since(UNIX_EPOCH)) {
+        Ok(Ok(d)) => Object::Float(d.as_secs_f64()),
+        Ok(Err(e)) => Object::Float(-e.duration().as_secs_f64()),
+        Err(_) => Object::Null
+    };
+    let map = Map::new();
+    {
+        let mut map = map.borrow_mut();
+        map.insert("size", uint_object(m.len()));
+        map.insert("mtime", mtime);
+        map.insert("mode", Object::Int(permissions(&m)));
+        map.insert("type", Object::from(file_type(m.file_type())));
+    }
+    Ok(Object::Map(map))
+}
+
+fn make_dir(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"mkdir")
+    }
+    let dir_id = path_arg(env,&argv[0],"mkdir(path)","path")?;
+    check_write(env,&dir_id,"mkdir(path)")?;
+    match fs::create_dir(&dir_id) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,
+            "Error in mkdir(path): could not create the directory.",&dir_id)
+    }
+}
+
+fn make_dirs(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"mkdirs")
+    }
+    let dir_id = path_arg(env,&argv[0],"mkdirs(path)","path")?;
+    check_write(env,&dir_id,"mkdirs(path)")?;
+    match fs::create_dir_all(&dir_id) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,
+            "Error in mkdirs(path): could not create the directories.",&dir_id)
+    }
+}
+
+fn remove(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"remove")
+    }
+    let file_id = path_arg(env,&argv[0],"remove(path)","path")?;
+    check_write(env,&file_id,"remove(path)")?;
+    let result = match fs::symlink_metadata(&file_id) {
+        Ok(m) if m.is_dir() => fs::remove_dir(&file_id),
+        _ => fs::remove_file(&file_id)
+    };
+    match result {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,
+            "Error in remove(path): could not remove the node.",&file_id)
+    }
+}
+
+fn remove_tree(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"rmtree")
+    }
+    let dir_id = path_arg(env,&argv[0],"rmtree(path)","path")?;
+    check_write(env,&dir_id,"rmtree(path)")?;
+    match fs::remove_dir_all(&dir_id) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,
+            "Error in rmtree(path): could not remove the directory tree.",&dir_id)
+    }
+}
+
+fn rename(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        2 => {}, n => return env.argc_error(n,2,2,"rename")
+    }
+    let old = path_arg(env,&argv[0],"rename(old,new)","old")?;
+    let new = path_arg(env,&argv[1],"rename(old,new)","new")?;
+    check_write(env,&old,"rename(old,new)")?;
+    check_write(env,&new,"rename(old,new)")?;
+    match fs::rename(&old,&new) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,&format!(
+            "Error in rename(old,new): could not rename to '{}'.",new),&old)
+    }
+}
+
+fn copy(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        2 => {}, n => return env.argc_error(n,2,2,"copy")
+    }
+    let source = path_arg(env,&argv[0],"copy(source,dest)","source")?;
+    let dest = path_arg(env,&argv[1],"copy(source,dest)","dest")?;
+    check_read(env,&source,"copy(source,dest)")?;
+    check_write(env,&dest,"copy(source,dest)")?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    no_follow(env,&mut options);
+    match copy_file(&source,&dest,&options) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,&format!(
+            "Error in copy(source,dest): could not copy to '{}'.",dest),&source)
+    }
+}
+
+fn copy_file(source: &str, dest: &str, options: &fs::OpenOptions) -> io::Result<()> {
+    let mut input = fs::File::open(source)?;
+    let mut output = options.open(dest)?;
+    io::copy(&mut input,&mut output)?;
+    output.set_permissions(input.metadata()?.permissions())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &str, link: &str) -> io::Result<()> {
+    std::os::unix::fs::symlink(target,link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &str, link: &str) -> io::Result<()> {
+    let base = Path::new(link).parent().unwrap_or_else(|| Path::new(""));
+    if base.join(target).is_dir() {
+        std::os::windows::fs::symlink_dir(target,link)
+    } else {
+        std::os::windows::fs::symlink_file(target,link)
+    }
+}
+
+fn symlink(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        2 => {}, n => return env.argc_error(n,2,2,"symlink")
+    }
+    let target = path_arg(env,&argv[0],"symlink(target,link)","target")?;
+    let link = path_arg(env,&argv[1],"symlink(target,link)","link")?;
+    check_write(env,&link,"symlink(target,link)")?;
+    let base = Path::new(&link).parent().unwrap_or_else(|| Path::new(""));
+    check_write(env,&base.join(&target).to_string_lossy(),"symlink(target,link)")?;
+    match create_symlink(&target,&link) {
+        Ok(()) => Ok(Object::Null),
+        Err(e) => io_error(env,e,
+            "Error in symlink(target,link): could not create the link.",&link)
+    }
+}
+
+fn read_link(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"readlink")
+    }
+    let link = path_arg(env,&argv[0],"readlink(path)","path")?;
+    check_read(env,&link,"readlink(path)")?;
+    let target = match fs::read_link(&link) {
+        Ok(target) => target,
+        Err(e) => return io_error(env,e,
+            "Error in readlink(path): could not read the link.",&link)
+    };
+    match target.to_str() {
+        Some(s) => Ok(Object::from(s)),
+        None => env.value_error(
+            "Error in readlink(path): could not encode the target as UTF-8.")
+    }
+}
+
+// Lists the entries of a directory, sorted by name and separated into
+// subdirectories and other nodes. Symbolic links are not followed,
+// a link to a directory counts as a node of the second kind.
+fn walk_entries(dir: &str) -> io::Result<(Vec<String>,Vec<String>)> {
+    let mut dirs: Vec<String> = Vec::new();
+    let mut files: Vec<String> = Vec::new();
+    for x in fs::read_dir(dir)? {
+        let x = x?;
+        let path = match x.path().to_str() {
+            Some(s) => s.to_string(),
+            None => continue
+        };
+        if x.file_type()?.is_dir() {dirs.push(path);} else {files.push(path);}
+    }
+    dirs.sort();
+    files.sort();
+    Ok((dirs,files))
+}
+
+fn walk(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    let root = match argv.len() {
+        0 => String::from("."),
+        1 => path_arg(env,&argv[0],"walk(path)","path")?,
+        n => return env.argc_error(n,0,1,"walk")
+    };
+    check_read(env,&root,"walk(path)")?;
+    if let Err(e) = fs::read_dir(&root) {
+        return io_error(env,e,
+            "Error in walk(path): could not read the directory.",&root);
+    }
+    let mut stack: Vec<String> = vec![root];
+    let f = Box::new(move |env: &mut Env, _pself: &Object, _argv: &[Object]| -> FnResult {
+        while let Some(dir) = stack.pop() {
+            if !env.rte().read_access(&dir) {continue;}
+            let (dirs,files) = match walk_entries(&dir) {
+                Ok(value) => value,
+                Err(_) => continue
+            };
+            stack.extend(dirs.iter().rev().cloned());
+            let dirs: Vec<Object> = dirs.iter().map(|s| Object::from(s.as_str())).collect();
+            let files: Vec<Object> = files.iter().map(|s| Object::from(s.as_str())).collect();
+            return Ok(List::new_object(vec![
+                Object::from(dir.as_str()),
+                List::new_object(dirs),
+                List::new_object(files)
+            ]));
+        }
+        Ok(Object::empty())
+    });
+    Ok(new_iterator(f))
+}
+
+const FILE_DOCS: &[(&str, &str)] = &[
+    ("read", "f.read(n=null)\nReads n bytes, or the rest of the file, as\nbinary data."),
+    ("readline", "f.readline()\nReads a line, including the line break. Returns\nthe empty string at the end of the file."),
+    ("lines", "f.lines()\nA lazy iterator over the remaining lines,\nwithout line breaks."),
+    ("write", "f.write(data)\nWrites a string or binary data to the file."),
+    ("seek", "f.seek(offset, whence=0)\nMoves the position to offset, measured from the\nstart, the current position or the end."),
+    ("tell", "f.tell()\nThe current position."),
+    ("flush", "f.flush()\nWrites the buffered data to the file."),
+    ("close", "f.close()\nFlushes and closes the file.")
+];
+
+const DOCS: &[(&str, &str)] = &[
+    ("open", "open(path, mode=\"r\")\nOpens a binary file and returns a file object.\nThe mode is one of r, w, a, r+, w+, a+."),
+    ("is_file", "is_file(path)\nTrue if path leads to a file."),
+    ("is_dir", "is_dir(path)\nTrue if path leads to a directory."),
+    ("ls", "ls(path=\".\")\nThe list of nodes in the directory path."),
+    ("cd", "cd(path)\nChanges the current working directory."),
+    ("wd", "wd()\nThe current working directory."),
+    ("stat", "stat(path, follow=true)\nA map with size, mtime, mode and type of the\nnode path."),
+    ("mkdir", "mkdir(path)\nCreates a directory."),
+    ("mkdirs", "mkdirs(path)\nCreates a directory and all missing parents."),
+    ("remove", "remove(path)\nRemoves a file, a symbolic link or an empty\ndirectory."),
+    ("rmtree", "rmtree(path)\nRemoves a directory together with its content."),
+    ("rename", "rename(old, new)\nRenames or moves a node."),
+    ("copy", "copy(source, dest)\nCopies a file."),
+    ("symlink", "symlink(target, link)\nCreates a symbolic link at link pointing to target."),
+    ("readlink", "readlink(path)\nThe target of the symbolic link path."),
+    ("walk", "walk(path=\".\")\nAn iterator over [dir, dirs, files] for each\ndirectory of the tree, top-down.")
+];
+
 pub fn load_fs(env: &mut Env) -> Object
 {
     let type_file = Class::new("File", &Object::Null);
     {
         let mut m = type_file.map.borrow_mut();
         m.insert_fn_plain("read", file_read, 0, 1);
+        m.insert_fn_plain("readline", file_readline, 0, 0);
+        m.insert_fn_plain("lines", file_lines, 0, 0);
         m.insert_fn_plain("write", file_write, 1, 1);
+        m.insert_fn_plain("seek", file_seek, 1, 2);
+        m.insert_fn_plain("tell", file_tell, 0, 0);
+        m.insert_fn_plain("flush", file_flush, 0, 0);
+        m.insert_fn_plain("close", file_close, 0, 0);
+        m.set_docs(FILE_DOCS);
     }
     interface_types_set(env.rte(), interface_index::FILE, type_file);
 
@@ -244,9 +784,20 @@ pub fn load_fs(env: &mut Env) -> Object
         m.insert_fn_plain("open",open, 1, 2);
         m.insert_fn_plain("is_file",is_file, 1, 1);
         m.insert_fn_plain("is_dir",is_dir, 1, 1);
-        m.insert_fn_plain("ls", read_dir, 1, 1);
+        m.insert_fn_plain("ls", read_dir, 0, 1);
         m.insert_fn_plain("cd", change_dir, 1, 1);
         m.insert_fn_plain("wd", working_dir, 0, 0);
+        m.insert_fn_plain("stat", stat, 1, 2);
+        m.insert_fn_plain("mkdir", make_dir, 1, 1);
+        m.insert_fn_plain("mkdirs", make_dirs, 1, 1);
+        m.insert_fn_plain("remove", remove, 1, 1);
+        m.insert_fn_plain("rmtree", remove_tree, 1, 1);
+        m.insert_fn_plain("rename", rename, 2, 2);
+        m.insert_fn_plain("copy", copy, 2, 2);
+        m.insert_fn_plain("symlink", symlink, 2, 2);
+        m.insert_fn_plain("readlink", read_link, 1, 1);
+        m.insert_fn_plain("walk", walk, 0, 1);
+        m.set_docs(DOCS);
     }
 
     Object::Interface(Rc::new(fs))
@@ -25,7 +25,7 @@ use std::cell::RefCell;
 
 use crate::object::{
     Object, Interface, Function, FnResult, Map, Table, CharString,
-    new_module, downcast, float
+    Exception, new_module, downcast, float
 };
 use crate::vm::Env;
 use crate::data::Bytes;
@@ -924,35 +924,44 @@ fn canvas_scale(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     }
 }
 
+// An argument that shall be a non-negative integer.
+fn usize_arg(env: &Env, fapp: &str, id: &str, x: &Object)
+-> Result<usize,Box<Exception>>
+{
+    match *x {
+        Object::Int(value) if value >= 0 => Ok(value as usize),
+        _ => Err(env.type_error_plain(&format!(
+            "Type error in {}: {} is not a non-negative integer.", fapp, id)))
+    }
+}
+
+fn bytes_arg<'a>(env: &Env, fapp: &str, id: &str, x: &'a Object)
+-> Result<&'a Bytes,Box<Exception>>
+{
+    match downcast::<Bytes>(x) {
+        Some(value) => Ok(value),
+        None => Err(env.type_error_plain(&format!(
+            "Type error in {}: {} is not of type Bytes.", fapp, id)))
+    }
+}
+
 fn canvas_set_glyph(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
-    let x = match argv[0] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let y = match argv[1] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let data_obj = match downcast::<Bytes>(&argv[2]) {
-        Some(value) => value,
-        None => panic!()
-    };
-    let index = match argv[3] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let w = match argv[4] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let h = match argv[5] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
+    let fapp = "canvas.glyph(x,y,data,index,w,h)";
+    let x = usize_arg(env, fapp, "x", &argv[0])?;
+    let y = usize_arg(env, fapp, "y", &argv[1])?;
+    let data_obj = bytes_arg(env, fapp, "data", &argv[2])?;
+    let index = usize_arg(env, fapp, "index", &argv[3])?;
+    let w = usize_arg(env, fapp, "w", &argv[4])?;
+    let h = usize_arg(env, fapp, "h", &argv[5])?;
     if let Some(canvas) = downcast::<Canvas>(pself) {
         let mut canvas = canvas.canvas.borrow_mut();
         let data = data_obj.data.borrow_mut();
-        let pdata = &data[index*w*h..];
+        let start = index.checked_mul(w*h);
+        let pdata = match start.and_then(|i| data.get(i..i+w*h)) {
+            Some(pdata) => pdata,
+            None => return env.value_error(&format!(
+                "Value error in {}: index is out of range of data.", fapp))
+        };
         canvas.draw_graymap(x,y,w,h,pdata);
         Ok(Object::Null)
     } else {
@@ -961,29 +970,19 @@ fn canvas_set_glyph(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult
 }
 
 fn canvas_set_pixmap(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
-    let x = match argv[0] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let y = match argv[1] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let data_obj = match downcast::<Bytes>(&argv[2]) {
-        Some(value) => value,
-        None => panic!()
-    };
-    let w = match argv[3] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let h = match argv[4] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
+    let fapp = "canvas.pixmap(x,y,data,w,h)";
+    let x = usize_arg(env, fapp, "x", &argv[0])?;
+    let y = usize_arg(env, fapp, "y", &argv[1])?;
+    let data_obj = bytes_arg(env, fapp, "data", &argv[2])?;
+    let w = usize_arg(env, fapp, "w", &argv[3])?;
+    let h = usize_arg(env, fapp, "h", &argv[4])?;
     if let Some(canvas) = downcast::<Canvas>(pself) {
         let mut canvas = canvas.canvas.borrow_mut();
         let data = data_obj.data.borrow_mut();
+        if w.checked_mul(h).and_then(|n| n.checked_mul(3)).map_or(true, |n| n > data.len()) {
+            return env.value_error(&format!(
+                "Value error in {}: data is smaller than w*h pixels.", fapp));
+        }
         canvas.draw_pixmap(x,y,w,h,&data);
         Ok(Object::Null)
     } else {
@@ -1009,11 +1008,16 @@ fn load_img(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
         Object::Int(index) => {
             match index {0 => font::Fmt::PGM, _ => font::Fmt::PPM}
         },
-        _ => panic!()
+        ref x => return env.type_error1(
+            "Type error in load_img_data(data,fmt): fmt is not an integer.", "fmt", x)
     };
     let pdata = data.data.borrow_mut();
 
-    let gdata = font::pnm_as_single_image(&pdata, fmt);
+    let gdata = match font::pnm_as_single_image(&pdata, fmt) {
+        Ok(gdata) => gdata,
+        Err(e) => return env.value_error(&format!(
+            "Value error in load_img_data(data): {}.", e))
+    };
     let map = Map::new();
     {
         let mut m = map.borrow_mut();
@@ -1030,37 +1034,47 @@ fn load_font(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
         None => return env.type_error(
             "Type error in load_font(data): expected data: Data.")
     };
-    let cols = match argv[1] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let rows = match argv[2] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let w = match argv[3] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let h = match argv[4] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let shiftw = match argv[5] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
-    let shifth = match argv[6] {
-        Object::Int(value) => value as usize,
-        _ => panic!()
-    };
+    let fapp = "load_font(data,cols,rows,w,h,shiftw,shifth)";
+    let cols = usize_arg(env, fapp, "cols", &argv[1])?;
+    let rows = usize_arg(env, fapp, "rows", &argv[2])?;
+    let w = usize_arg(env, fapp, "w", &argv[3])?;
+    let h = usize_arg(env, fapp, "h", &argv[4])?;
+    let shiftw = usize_arg(env, fapp, "shiftw", &argv[5])?;
+    let shifth = usize_arg(env, fapp, "shifth", &argv[6])?;
     let pdata = data.data.borrow_mut();
-    let gdata = font::pgm_as_glyph_data(&pdata,
-        cols, rows, w, h, shiftw, shifth
-    );
-    Ok(Bytes::object_from_vec(gdata.data))
+    match font::pgm_as_glyph_data(&pdata, cols, rows, w, h, shiftw, shifth) {
+        Ok(gdata) => Ok(Bytes::object_from_vec(gdata.data)),
+        Err(e) => env.value_error(&format!("Value error in {}: {}.", fapp, e))
+    }
 }
 
+const CANVAS_DOCS: &[(&str, &str)] = &[
+    ("key", "c.key()\nThe next key of the keyboard input queue, or null."),
+    ("scan", "c.scan()\nThe scancode of the next key of the input queue,\nor null."),
+    ("flush", "c.flush()\nFlushes the drawn data of c to the screen."),
+    ("vflush", "c.vflush()\nFlushes the vector graphics buffer."),
+    ("vcflush", "c.vcflush()\nFlushes and clears the vector graphics buffer."),
+    ("point", "c.point(x, y)\nDraws a smooth point at (x, y)."),
+    ("needle", "c.needle(x, y)\nDraws a thin point at (x, y)."),
+    ("circle", "c.circle(x, y, r)\nDraws a circle of radius r at (x, y)."),
+    ("disc", "c.disc(x, y, r)\nDraws a disc of radius r at (x, y)."),
+    ("box", "c.box(x, y, r)\nDraws a box of incircle radius r at (x, y)."),
+    ("rgb", "c.rgb(r, g, b, a=1)\nSets the color by the RGB color model."),
+    ("hsl", "c.hsl(h, s, l, a=1)\nSets the color by the HSL color model."),
+    ("clear", "c.clear(r, g, b)\nClears the canvas by the RGB color."),
+    ("fill", "c.fill(x, y, w, h)\nFills the rectangle of w×h pixels at pixel (x, y)."),
+    ("scale", "c.scale(sx, sy)\nSets the scale of the device independent\ncoordinate system."),
+    ("glyph", "c.glyph(x, y, data, index, w, h)\nDraws the w×h graymap number index of data\nat pixel (x, y)."),
+    ("pixmap", "c.pixmap(x, y, data, w, h)\nDraws the w×h RGB pixmap data at pixel (x, y)."),
+    ("dump", "c.dump()\nThe pixel data of the canvas as binary data.")
+];
+
+const DOCS: &[(&str, &str)] = &[
+    ("sleep", "sleep(x)\nHalts execution for x seconds."),
+    ("load_font", "load_font(data, cols, rows, w, h, shiftw, shifth)\nCuts the glyphs of a font out of the graymap data."),
+    ("load_img_data", "load_img_data(data, fmt)\nA map of width, height and pixel data of the\nPGM (fmt==0) or PPM image data.")
+];
+
 pub fn load_graphics() -> Object
 {
     let type_canvas = Table::new(Object::Null);
@@ -1082,8 +1096,9 @@ pub fn load_graphics() -> Object
         m.insert_fn_plain("fill", canvas_fill, 4, 4);
         m.insert_fn_plain("scale", canvas_scale, 2, 2);
         m.insert_fn_plain("glyph", canvas_set_glyph, 6, 6);
-        m.insert_fn_plain("pixmap", canvas_set_pixmap, 2, 2);
+        m.insert_fn_plain("pixmap", canvas_set_pixmap, 5, 5);
         m.insert_fn_plain("dump", canvas_dump, 0, 0);
+        m.set_docs(CANVAS_DOCS);
     }
 
     let graphics = new_module("graphics");
@@ -1094,6 +1109,7 @@ pub fn load_graphics() -> Object
         m.insert_fn_plain("sleep", graphics_sleep, 1, 1);
         m.insert_fn_plain("load_font", load_font, 7, 7);
         m.insert_fn_plain("load_img_data", load_img, 2, 2);
+        m.set_docs(DOCS);
     }
     
     // Workaround for a bug in SDL on Ubuntu 18.04 on i386.
@@ -11,56 +11,76 @@ pub struct GlyphData {
 }
 
 fn advance_space(p: &[u8], index: &mut usize) {
-    let mut i = *index;
-    loop {
-        let c = p[i];
-        if let b' ' | b'\n' | b'\t' | b'\r' = c {i += 1;}
-        else {break;}
+    while let Some(b' ' | b'\n' | b'\t' | b'\r') = p.get(*index) {
+        *index += 1;
     }
-    *index = i;
 }
 
-fn read_uint(p: &[u8], j: &mut usize) -> u32 {
+fn read_uint(p: &[u8], j: &mut usize) -> Result<u32,String> {
     let mut i = *j;
     let mut value: u32 = 0;
-    loop {
-        let c = p[i];
-        if b'0' <= c && c <= b'9' {
-            value = 10*value + (c as u32) - (b'0' as u32);
-            i += 1;
-        }
-        else {break;}
+    while let Some(&c) = p.get(i) {
+        if !c.is_ascii_digit() {break;}
+        value = value.checked_mul(10)
+            .and_then(|x| x.checked_add((c - b'0') as u32))
+            .ok_or_else(|| String::from("image size too large"))?;
+        i += 1;
+    }
+    if i == *j {
+        return Err(String::from("malformed header"));
     }
     *j = i;
-    value
+    Ok(value)
 }
 
 pub enum Fmt {PGM, PPM}
 
-fn pnm_as_data(p: &[u8], fmt: Fmt) -> ImgData {
-    match fmt {
-        Fmt::PGM => {if &p[0..2] != b"P5" {panic!("Error: not a PGM");}},
-        Fmt::PPM => {if &p[0..2] != b"P6" {panic!("Error: not a PPM");}}
+fn pnm_as_data(p: &[u8], fmt: Fmt) -> Result<ImgData,String> {
+    let (magic, channels) = match fmt {
+        Fmt::PGM => (b"P5", 1),
+        Fmt::PPM => (b"P6", 3)
+    };
+    if p.get(0..2) != Some(&magic[..]) {
+        return Err(match fmt {
+            Fmt::PGM => String::from("not a PGM image"),
+            Fmt::PPM => String::from("not a PPM image")
+        });
     }
     let mut i = 2;
     advance_space(p, &mut i);
-    if p[i] == b'#' {
-        panic!("Error while reading PGM: comments not allowed");
+    if p.get(i) == Some(&b'#') {
+        return Err(String::from("comments are not allowed"));
     }
-    let w = read_uint(p, &mut i);
+    let w = read_uint(p, &mut i)?;
     advance_space(p, &mut i);
-    let h = read_uint(p, &mut i);
+    let h = read_uint(p, &mut i)?;
     advance_space(p, &mut i);
-    let max = read_uint(p, &mut i);
-    ImgData {w, h, max, data: &p[i+1..]}
+    let max = read_uint(p, &mut i)?;
+    let data = p.get(i+1..).unwrap_or(&[]);
+    let size = (w as usize).checked_mul(h as usize)
+        .and_then(|x| x.checked_mul(channels));
+    match size {
+        Some(size) if size <= data.len() => {},
+        _ => return Err(String::from("image data incomplete"))
+    }
+    Ok(ImgData {w, h, max, data})
 }
 
 fn glyph_data(img: ImgData,
     cols: usize, rows: usize, w: usize, h: usize, shiftw: usize, shifth: usize
-) -> Vec<u8> {
+) -> Result<Vec<u8>,String> {
+    let imgw = img.w as usize;
+    let imgh = img.h as usize;
+    let inside = |n: usize, shift: usize, size: usize, bound: usize| {
+        n == 0 || n.checked_sub(1).and_then(|k| k.checked_mul(shift))
+            .and_then(|x| x.checked_add(size))
+            .is_some_and(|x| x <= bound)
+    };
+    if !inside(cols, shiftw, w, imgw) || !inside(rows, shifth, h, imgh) {
+        return Err(String::from("the glyphs exceed the image"));
+    }
     let mut acc: Vec<u8> = Vec::new();
     let data = img.data;
-    let imgw = img.w as usize;
     for row in 0..rows {
         let hstart = row*shifth;
         for col in 0..cols {
@@ -72,20 +92,20 @@ fn glyph_data(img: ImgData,
             }
         }
     }
-    acc
+    Ok(acc)
 }
 
 pub fn pgm_as_glyph_data(pgm: &[u8],
     cols: usize, rows: usize, w: usize, h: usize, shiftw: usize, shifth: usize
-) -> GlyphData {
-    let img = pnm_as_data(pgm, Fmt::PGM);
-    GlyphData {width: img.w, height: img.h,
-        data: glyph_data(img, cols, rows, w, h, shiftw, shifth)}
+) -> Result<GlyphData,String> {
+    let img = pnm_as_data(pgm, Fmt::PGM)?;
+    Ok(GlyphData {width: img.w, height: img.h,
+        data: glyph_data(img, cols, rows, w, h, shiftw, shifth)?})
 }
 
-pub fn pnm_as_single_image(pnm: &[u8], fmt: Fmt) -> GlyphData {
-    let img = pnm_as_data(pnm, fmt);
+pub fn pnm_as_single_image(pnm: &[u8], fmt: Fmt) -> Result<GlyphData,String> {
+    let img = pnm_as_data(pnm, fmt)?;
     let w = img.w;
     let h = img.h;
-    GlyphData{width: w, height: h, data: img.data.to_vec()}
+    Ok(GlyphData{width: w, height: h, data: img.data.to_vec()})
 }
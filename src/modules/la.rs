@@ -155,45 +155,66 @@ impl Interface for Array<C64> {
 fn to_string<T: Number>(a: &Array<T>, _env: &mut Env)
 -> Result<String,Box<Exception>>
 {
+    let data = a.data.borrow();
     if a.n == 1 {
         let mut s = "vector(".to_string();
-        let data = a.data.borrow();
         vector_to_string(a, &mut s, &data);
         s.push(')');
         Ok(s)
-    } else if a.n == 2 {
+    } else {
         let mut s = "matrix(\n".to_string();
-        let data = a.data.borrow();
         matrix_to_string(a, &mut s, &data);
         s.push_str("\n)");
         Ok(s)
+    }
+}
+
+// The positions of the elements in the data, in row major order.
+fn offsets<T>(a: &Array<T>) -> Vec<usize> {
+    let mut acc: Vec<isize> = vec![a.base];
+    for s in a.s.iter() {
+        acc = acc.iter().flat_map(|&k| {
+            (0..s.shape).map(move |i| k + i as isize*s.stride)
+        }).collect();
+    }
+    acc.into_iter().map(|k| k as usize).collect()
+}
+
+fn same_shape<T>(a: &Array<T>, b: &Array<T>) -> bool {
+    a.n == b.n && a.s.iter().zip(b.s.iter()).all(|(x,y)| x.shape == y.shape)
+}
+
+// A new array of the same shape as a.
+fn new_like<T: Number>(a: &Array<T>, v: Vec<T>) -> Rc<Array<T>> {
+    if a.n == 1 {
+        Array::vector(v)
     } else {
-        unimplemented!();
+        Array::matrix(a.s[0].shape, a.s[1].shape, v)
     }
 }
 
 fn index<T>(a: &Array<T>, indices: &[Object], env: &mut Env) -> FnResult
 where T: Number, Object: From<T>
 {
-    if a.n == 1 && indices.len() == 1 {
-        match indices[0] {
-            Object::Int(i) => {
-                let i = i as isize;
-                if i >= 0 && (i as usize) < a.s[0].shape {
-                    let data = a.data.borrow_mut();
-                    let index = (a.base as isize + a.s[0].stride*i) as usize;
-                    Ok(Object::from(data[index]))
-                } else {
-                    env.index_error("Index error in a[i]: out of bounds.")
-                }
-            },
-            ref i => {
-                env.type_error1("Type error in a[i]: i is not an integer.", "i", i)
-            }
+    if indices.len() != a.n {
+        return env.index_error(&format!(
+            "Index error in a[...]: expected {} indices, got {}.",
+            a.n, indices.len()));
+    }
+    let mut k = a.base;
+    for (i,s) in indices.iter().zip(a.s.iter()) {
+        let i = match *i {
+            Object::Int(i) => i as isize,
+            ref i => return env.type_error1(
+                "Type error in a[i]: i is not an integer.", "i", i)
+        };
+        if i < 0 || i as usize >= s.shape {
+            return env.index_error("Index error in a[i]: out of bounds.");
         }
-    } else {
-        panic!()
+        k += s.stride*i;
     }
+    let data = a.data.borrow();
+    Ok(Object::from(data[k as usize]))
 }
 
 fn get<T>(a: &Array<T>, key: &Object, env: &mut Env) -> FnResult
@@ -239,163 +260,127 @@ fn shape<T>(a: &Array<T>) -> FnResult {
 fn array_to_list<T: Number>(a: &Array<T>) -> Object
 where Object: From<T>
 {
+    let data = a.data.borrow();
+    let v: Vec<Object> = offsets(a).into_iter()
+        .map(|k| Object::from(data[k])).collect();
     if a.n == 1 {
-        let data = &*a.data.borrow();
-        let base = a.base;
-        let shape = a.s[0].shape;
-        let stride = a.s[0].stride;
-        let mut acc: Vec<Object> = Vec::with_capacity(shape);
-        for i in 0..shape {
-            let x = data[(base + i as isize*stride) as usize];
-            acc.push(Object::from(x));
-        }
-        List::new_object(acc)
+        List::new_object(v)
     } else {
-        panic!();
+        let (m,n) = (a.s[0].shape, a.s[1].shape);
+        List::new_object((0..m).map(|i| {
+            List::new_object(v[i*n..(i+1)*n].to_vec())
+        }).collect())
     }
 }
 
 fn abs_f64(a: &Array<f64>) -> FnResult {
-    let n = a.s[0].shape;
-    let stride = a.s[0].stride;
-    let base = a.base;
     let data = &a.data.borrow();
     let mut y = 0.0;
-    for i in 0..n {
-        let x = data[(base + i as isize*stride) as usize];
+    for k in offsets(a) {
+        let x = data[k];
         y += x*x;
     }
     Ok(Object::Float(y.sqrt()))
-
 }
 
 fn abs_c64(a: &Array<C64>) -> FnResult {
-    let n = a.s[0].shape;
-    let stride = a.s[0].stride;
-    let base = a.base;
     let data = &a.data.borrow();
     let mut y = 0.0;
-    for i in 0..n {
-        let x = data[(base + i as isize*stride) as usize];
+    for k in offsets(a) {
+        let x = data[k];
         y += x.re*x.re + x.im*x.im;
     }
     Ok(Object::Float(y.sqrt()))
 }
 
-fn add<T: Number>(a: &Array<T>, b: &Object, _env: &mut Env) -> FnResult
+fn add<T: Number>(a: &Array<T>, b: &Object, env: &mut Env) -> FnResult
 where Array<T>: Interface
 {
     if let Some(b) = downcast::<Array<T>>(b) {
         let data = a.data.borrow();
-        map_binary(a, &data, b, &|x,y| x + y)
+        map_binary(env, a, &data, b, "+", &|x,y| x + y)
     } else {
-        panic!()
+        env.type_error("Type error in a+b: b is not an array of the same type.")
     }
 }
 
-fn sub<T: Number>(a: &Array<T>, b: &Object, _env: &mut Env) -> FnResult
+fn sub<T: Number>(a: &Array<T>, b: &Object, env: &mut Env) -> FnResult
 where Array<T>: Interface
 {
     if let Some(b) = downcast::<Array<T>>(b) {
         let data = a.data.borrow();
-        map_binary(a, &data, b, &|x,y| x - y)
+        map_binary(env, a, &data, b, "-", &|x,y| x - y)
     } else {
-        panic!()
+        env.type_error("Type error in a-b: b is not an array of the same type.")
     }
 }
 
 fn mul<T: Number>(a: &Array<T>, b: &Object, env: &mut Env) -> FnResult
 where Array<T>: Interface, Object: From<T>
 {
-    if let Some(b) = downcast::<Array<T>>(b) {
-        let adata = &a.data.borrow();
-        let bdata = &b.data.borrow();
-        if a.n == 2 {
-            if b.n == 2 {
-                let m = a.s[0].shape;
-                let p = a.s[1].shape;
-                let n = b.s[1].shape;
-                if p != b.s[0].shape {
-                    return env.value_error(
-                        "Value error in matrix multiplication A*B:\n  A.shape[1] != B.shape[0].");
-                }
-                Ok(Object::Interface(
-                    mul_matrix_matrix(m,p,n,a,b,adata,bdata)))
-            } else if b.n == 1 {
-                let m = a.s[0].shape;
-                let n = a.s[1].shape;
-                if n != b.s[0].shape {
-                    return env.value_error(
-                        "Value error in A*b: A.shape[1] != b.shape[0].");
-                }
-                Ok(Object::Interface(
-                    mul_matrix_vector(m,n,a,b,adata,bdata)))
-            } else {
-                panic!()
+    let b = match downcast::<Array<T>>(b) {
+        Some(b) => b,
+        None => return env.type_error(
+            "Type error in a*b: b is not an array of the same type.")
+    };
+    let adata = &a.data.borrow();
+    let bdata = &b.data.borrow();
+    match (a.n, b.n) {
+        (2,2) => {
+            let m = a.s[0].shape;
+            let p = a.s[1].shape;
+            let n = b.s[1].shape;
+            if p != b.s[0].shape {
+                return env.value_error(
+                    "Value error in matrix multiplication A*B:\n  A.shape[1] != B.shape[0].");
             }
-        } else if a.n == 1 {
+            Ok(Object::Interface(
+                mul_matrix_matrix(m,p,n,a,b,adata,bdata)))
+        },
+        (2,1) => {
+            let m = a.s[0].shape;
+            let n = a.s[1].shape;
+            if n != b.s[0].shape {
+                return env.value_error(
+                    "Value error in A*b: A.shape[1] != b.shape[0].");
+            }
+            Ok(Object::Interface(
+                mul_matrix_vector(m,n,a,b,adata,bdata)))
+        },
+        (1,1) => {
             let n = a.s[0].shape;
             if n != b.s[0].shape {
                 return env.value_error(
                     "Value error in a*b: a.shape[0] != b.shape[0].");
             }
             Ok(scalar_product(n,a,b,adata,bdata))
-        } else {
-            panic!()
-        }
-    } else {
-        panic!()
+        },
+        _ => env.type_error(
+            "Type error in a*B: a vector cannot be multiplied by a matrix.")
     }
 }
 
-fn map_binary<T: Number>(a: &Array<T>, adata: &[T], b: &Array<T>,
-    f: &dyn Fn(T,T)->T
+fn map_binary<T: Number>(env: &mut Env, a: &Array<T>, adata: &[T], b: &Array<T>,
+    op: &str, f: &dyn Fn(T,T)->T
 ) -> FnResult
 where Array<T>: Interface
 {
-    if a.n == 1 {
-        let astride = a.s[0].stride;
-        let ashape = a.s[0].shape;
-        let abase = a.base;
-        let mut acc: Vec<T> = Vec::with_capacity(ashape);
-
-        if b.n != 1 {
-            panic!()
-        }
-        if ashape != b.s[0].shape {
-            panic!()
-        }
-        let bstride = b.s[0].stride;
-        let bbase = b.base;
-        let bdata = b.data.borrow();
-        for i in 0..ashape {
-            let aindex = (abase+(i as isize)*astride) as usize;
-            let bindex = (bbase+(i as isize)*bstride) as usize;
-            acc.push(f(adata[aindex], bdata[bindex]));
-        }
-        Ok(Object::Interface(Array::vector(acc)))
-    } else {
-        unimplemented!();
-    }
+    if !same_shape(a, b) {
+        return env.value_error(&format!(
+            "Value error in a{}b: a.shape != b.shape.", op));
+    }
+    let bdata = b.data.borrow();
+    let acc: Vec<T> = offsets(a).into_iter().zip(offsets(b))
+        .map(|(i,j)| f(adata[i], bdata[j])).collect();
+    Ok(Object::Interface(new_like(a, acc)))
 }
 
 fn map_unary<T: Number>(a: &Array<T>, data: &[T], f: &dyn Fn(T)->T)
 -> FnResult
 where Array<T>: Interface
 {
-    if a.n == 1 {
-        let stride = a.s[0].stride;
-        let shape = a.s[0].shape;
-        let base = a.base;
-        let mut acc: Vec<T> = Vec::with_capacity(shape);
-        for i in 0..shape {
-            let index = (base + (i as isize)*stride) as usize;
-            acc.push(f(data[index]));
-        }
-        Ok(Object::Interface(Array::vector(acc)))
-    } else {
-        unimplemented!();
-    }
+    let acc: Vec<T> = offsets(a).into_iter().map(|k| f(data[k])).collect();
+    Ok(Object::Interface(new_like(a, acc)))
 }
 
 fn scalar_product<T: Number>(n: usize,
@@ -531,6 +516,11 @@ fn vector_from_list(env: &mut Env, a: &[Object]) -> FnResult {
     Ok(Object::Interface(Array::<f64>::vector(acc)))
 }
 
+fn matrix_row_error(env: &mut Env, x: &Object) -> FnResult {
+    env.type_error1(
+        "Type error in matrix(*a): expected all a[i] of type List.", "a[i]", x)
+}
+
 fn matrix_from_lists_c64(m: usize, n: usize,
     env: &mut Env, argv: &[Object]
 ) -> FnResult
@@ -539,7 +529,7 @@ fn matrix_from_lists_c64(m: usize, n: usize,
     for x in argv {
         let a = match *x {
             Object::List(ref a) => a,
-            _ => panic!()
+            ref x => return matrix_row_error(env, x)
         };
         let data = &a.borrow().v;
         if data.len() != n {
@@ -577,13 +567,13 @@ fn matrix_from_lists(env: &mut Env, argv: &[Object]) -> FnResult {
             }
             n
         },
-        _ => panic!()
+        ref x => return matrix_row_error(env, x)
     };
     let mut acc: Vec<f64> = Vec::with_capacity(m*n);
     for x in argv {
         let a = match *x {
             Object::List(ref a) => a,
-            _ => panic!()
+            ref x => return matrix_row_error(env, x)
         };
         let data = &a.borrow().v;
         if data.len() != n {
@@ -611,6 +601,12 @@ fn matrix(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     matrix_from_lists(env,argv)
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("vector", "vector(*a)\nA coordinate vector of floats."),
+    ("matrix", "matrix(*rows)\nA matrix of floats from the lists of its rows."),
+    ("vec", "vec(*a)\nA shorthand for vector.")
+];
+
 pub fn load_la(env: &mut Env) -> Object
 {
     let type_array = Class::new("Array", &Object::Null);
@@ -626,6 +622,7 @@ pub fn load_la(env: &mut Env) -> Object
         m.insert_fn_plain("vector", vector, 0, VARIADIC);
         m.insert_fn_plain("matrix", matrix, 1, VARIADIC);
         m.insert_fn_plain("vec", vector, 0, VARIADIC);
+        m.set_docs(DOCS);
     }
 
     Object::Interface(Rc::new(la))
@@ -1338,6 +1338,22 @@ fn la_copy(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     })
 }
 
+const ARRAY_DOCS: &[(&str, &str)] = &[
+    ("map", "a.map(f)\nA new array of the values f(x) for x in a."),
+    ("list", "a.list()\nThe array converted into nested lists.")
+];
+
+const DOCS: &[(&str, &str)] = &[
+    ("vector", "vector(*a)\nA coordinate vector."),
+    ("matrix", "matrix(*rows)\nA matrix from the lists of its rows, or from\nits column vectors."),
+    ("array", "array(n, data)\nA coordinate tensor of order n from nested lists."),
+    ("vec", "vec(*a)\nA shorthand for vector."),
+    ("scalar", "scalar(n, x, zero)\nThe scalar n×n matrix with x on the diagonal."),
+    ("unit", "unit(n, k)\nThe k-th unit vector of dimension n."),
+    ("diag", "diag(*a)\nThe diagonal matrix with the diagonal a."),
+    ("id", "id(a)\nA copy of the array a, other objects as they are.")
+];
+
 pub fn load_math_la(env: &mut Env) -> Object
 {
     let type_array = Class::new("Array",&Object::Null);
@@ -1345,6 +1361,7 @@ pub fn load_math_la(env: &mut Env) -> Object
         let mut m = type_array.map.borrow_mut();
         m.insert_fn_plain("map", map, 1, 1);
         m.insert_fn_plain("list", array_list, 1, 1);
+        m.set_docs(ARRAY_DOCS);
     }
     interface_types_set(env.rte(), interface_index::POLY_ARRAY,
         type_array.clone());
@@ -1360,6 +1377,7 @@ pub fn load_math_la(env: &mut Env) -> Object
         m.insert_fn_plain("unit", unit, 2, 2);
         m.insert_fn_plain("diag", diag, 0, VARIADIC);
         m.insert_fn_plain("id", la_copy, 1, 1);
+        m.set_docs(DOCS);
         m.insert("Array", Object::Interface(type_array));
     }
 
@@ -794,6 +794,36 @@ fn catanh(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
 }
 
 
+const MATH_DOCS: &[(&str, &str)] = &[
+    ("floor", "floor(x)\nThe largest integer not greater than x, as a float."),
+    ("ceil", "ceil(x)\nThe smallest integer not less than x, as a float."),
+    ("trunc", "trunc(x)\nx rounded towards zero, as a float."),
+    ("sqrt", "sqrt(x)\nThe square root of x."),
+    ("exp", "exp(x)\nThe exponential function."),
+    ("ln", "ln(x)\nThe natural logarithm."),
+    ("lg", "lg(x)\nThe logarithm to base 10."),
+    ("sin", "sin(x)\nThe sine of x, in radians."),
+    ("cos", "cos(x)\nThe cosine of x, in radians."),
+    ("tan", "tan(x)\nThe tangent of x, in radians."),
+    ("sinh", "sinh(x)\nThe hyperbolic sine."),
+    ("cosh", "cosh(x)\nThe hyperbolic cosine."),
+    ("tanh", "tanh(x)\nThe hyperbolic tangent."),
+    ("asin", "asin(x)\nThe inverse sine."),
+    ("acos", "acos(x)\nThe inverse cosine."),
+    ("atan", "atan(x)\nThe inverse tangent."),
+    ("asinh", "asinh(x)\nThe inverse hyperbolic sine."),
+    ("acosh", "acosh(x)\nThe inverse hyperbolic cosine."),
+    ("atanh", "atanh(x)\nThe inverse hyperbolic tangent."),
+    ("gamma", "gamma(x)\nThe gamma function."),
+    ("lgamma", "lgamma(x)\nThe logarithm of the absolute value of gamma(x)."),
+    ("sgngamma", "sgngamma(x)\nThe sign of gamma(x)."),
+    ("hypot", "hypot(x, y)\nThe length sqrt(x^2+y^2) of the vector [x, y]."),
+    ("atan2", "atan2(y, x)\nThe angle of the vector [x, y], in (-pi, pi]."),
+    ("erf", "erf(x)\nThe error function."),
+    ("isnan", "isnan(x)\nTrue if x is not a number."),
+    ("isinf", "isinf(x)\nTrue if x is infinite.")
+];
+
 pub fn load_math() -> Object {
     let math = new_module("math");
     {
@@ -834,10 +864,33 @@ pub fn load_math() -> Object {
         m.insert_fn_plain("erf", math_erf, 1, 1);
         m.insert_fn_plain("isnan", isnan, 1, 1);
         m.insert_fn_plain("isinf", isinf, 1, 1);
+        m.set_docs(MATH_DOCS);
     }
     Object::Interface(Rc::new(math))
 }
 
+const CMATH_DOCS: &[(&str, &str)] = &[
+    ("exp", "exp(x)\nThe complex exponential function."),
+    ("sin", "sin(x)\nThe complex sine."),
+    ("cos", "cos(x)\nThe complex cosine."),
+    ("tan", "tan(x)\nThe complex tangent."),
+    ("sinh", "sinh(x)\nThe complex hyperbolic sine."),
+    ("cosh", "cosh(x)\nThe complex hyperbolic cosine."),
+    ("tanh", "tanh(x)\nThe complex hyperbolic tangent."),
+    ("asin", "asin(x)\nThe complex inverse sine."),
+    ("acos", "acos(x)\nThe complex inverse cosine."),
+    ("atan", "atan(x)\nThe complex inverse tangent."),
+    ("asinh", "asinh(x)\nThe complex inverse hyperbolic sine."),
+    ("acosh", "acosh(x)\nThe complex inverse hyperbolic cosine."),
+    ("atanh", "atanh(x)\nThe complex inverse hyperbolic tangent."),
+    ("re", "re(z)\nThe real part of z."),
+    ("im", "im(z)\nThe imaginary part of z."),
+    ("conj", "conj(z)\nThe complex conjugate of z."),
+    ("ln", "ln(x)\nThe principal value of the complex logarithm."),
+    ("sqrt", "sqrt(x)\nThe principal value of the complex square root."),
+    ("arg", "arg(z)\nThe argument of z, in (-pi, pi].")
+];
+
 pub fn load_cmath() -> Object {
     let cmath = new_module("cmath");
     {
@@ -863,6 +916,7 @@ pub fn load_cmath() -> Object {
         m.insert_fn_plain("ln", cln, 1, 1);
         m.insert_fn_plain("sqrt", csqrt, 1, 1);
         m.insert_fn_plain("arg", arg, 1, 1);
+        m.set_docs(CMATH_DOCS);
     }
     Object::Interface(Rc::new(cmath))
 }
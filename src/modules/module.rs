@@ -9,6 +9,12 @@ use crate::object::{Object, FnResult, Map};
 use crate::vm::{Module, RTE, Env, eval};
 use crate::compiler::{compile, Value};
 
+// The version of the binary format written by 'moss -c', stored in
+// the first header word. It has to be increased whenever the bytecode
+// changes, since the bytecode itself is not verified on loading.
+// Files of version 0 predate the version field.
+const BINARY_VERSION: u32 = 1;
+
 fn push_u32(bv: &mut Vec<u8>, x: u32) {
     bv.push(x as u8);
     bv.push((x>>8) as u8);
@@ -37,7 +43,7 @@ pub fn push_object(bv: &mut Vec<u8>, x: &Object) {
 
 fn serialize(v: &[u32], data: &[Object]) -> Vec<u8> {
     let mut bv: Vec<u8> = Vec::with_capacity(v.len());
-    push_u32(&mut bv, 0); // indicates a binary file
+    push_u32(&mut bv, BINARY_VERSION);
     push_u32(&mut bv, 0xcafe); // pointer to data
     for x in v {
         push_u32(&mut bv, *x);
@@ -53,40 +59,47 @@ fn serialize(v: &[u32], data: &[Object]) -> Vec<u8> {
     bv
 }
 
-fn load_u32(bv: &[u8]) -> u32 {
-       bv[0] as u32
-    | (bv[1] as u32)<<8
-    | (bv[2] as u32)<<16
-    | (bv[3] as u32)<<24
+// Reads a little endian u32 at index, None if the data ends before.
+fn load_u32(bv: &[u8], index: usize) -> Option<u32> {
+    let a = bv.get(index..index.checked_add(4)?)?;
+    Some(u32::from_le_bytes([a[0], a[1], a[2], a[3]]))
 }
 
-fn load_object(bv: &[u8], index: usize) -> (usize, Object) {
+fn load_object(bv: &[u8], index: usize) -> Option<(usize, Object)> {
     let i = index + 4;
-    let size = load_u32(&bv[index..i]) as usize;
-    let a: Vec<u8> = Vec::from(&bv[i..i+size]);
-    let s = match String::from_utf8(a) {
-        Ok(value) => value,
-        _ => panic!("Could not load binary module: invalid Unicode.")
-    };
-    (i + size, Object::from(&s[..]))
+    let size = load_u32(bv, index)? as usize;
+    let a = bv.get(i..i.checked_add(size)?)?;
+    let s = std::str::from_utf8(a).ok()?;
+    Some((i + size, Object::from(s)))
 }
 
-fn load_from_u8(rte: &Rc<RTE>, id: &str, bv: &[u8])
--> Result<Rc<Module>,()>
+// Loads a module from the binary format written by 'moss -c'.
+// Malformed data is rejected, but the bytecode itself is not
+// verified, thus it must stem from a trusted source.
+pub fn load_from_u8(rte: &Rc<RTE>, id: &str, bv: &[u8])
+-> Result<Rc<Module>,String>
 {
-    let data_index = load_u32(&bv[4..8]) as usize;
+    let invalid = || format!("Could not load module '{}': invalid binary data.", id);
+    let version = load_u32(bv, 0).ok_or_else(invalid)?;
+    if version != BINARY_VERSION {
+        return Err(format!(
+            "Could not load module '{}': binary format version {} is not supported,\n\
+             the module has to be recompiled by 'moss -c'.", id, version));
+    }
+    let data_index = load_u32(bv, 4).ok_or_else(invalid)? as usize;
+    if data_index < 8 || data_index > bv.len() {return Err(invalid());}
     let program_size = (data_index-8)/4;
     let mut v: Vec<u32> = Vec::with_capacity(program_size);
     let mut i = 8;
     while i<data_index {
-        v.push(load_u32(&bv[i..i+4]));
+        v.push(load_u32(bv, i).ok_or_else(invalid)?);
         i += 4;
     }
-    let data_count = load_u32(&bv[data_index..data_index+4]) as usize;
-    let mut data: Vec<Object> = Vec::with_capacity(data_count);
+    let data_count = load_u32(bv, data_index).ok_or_else(invalid)? as usize;
+    let mut data: Vec<Object> = Vec::with_capacity(data_count.min(bv.len()));
     i = data_index + 4;
     for _ in 0..data_count {
-        let (index,x) = load_object(bv,i);
+        let (index,x) = load_object(bv,i).ok_or_else(invalid)?;
         data.push(x);
         i = index;
     }
@@ -115,20 +128,16 @@ pub fn compile_file(rte: &Rc<RTE>, id: &str) {
     let history = &mut History::new();
     match compile(&s, id, false, Value::Optional, history, rte) {
         Ok(module) => save_module(&module),
-        Err(e) => println!("{}", crate::compiler::format_error(&e))
+        Err(e) => println!("{}", crate::compiler::format_error(&e, &s))
     };
 }
 
 fn load_module(rte: &Rc<RTE>, f: &mut File, id: &str)
--> Result<Rc<Module>,()>
+-> Result<Rc<Module>,String>
 {
     let mut bv: Vec<u8> = Vec::new();
-    match f.read_to_end(&mut bv) {
-        Ok(_) => {},
-        Err(_) => {
-            println!("Could open but not read file '{}'.", id);
-            return Err(());
-        }
+    if f.read_to_end(&mut bv).is_err() {
+        return Err(format!("Could open but not read file '{}'.", id));
     }
     load_from_u8(rte, id, &bv)
 }
@@ -138,8 +147,7 @@ pub fn eval_module(env: &mut Env, gtab: Rc<RefCell<Map>>, f: &mut File, id: &str
 {
     let m = match load_module(env.rte(),f,id) {
         Ok(m) => m,
-        Err(()) => return env.std_exception(&format!(
-            "Could not load module '{}'.", id))
+        Err(e) => return env.import_error(&e, id)
     };
     eval(env, m, gtab, false)
 }
@@ -1,17 +1,29 @@
 
+// Regular expressions. A pattern is parsed into a syntax tree of
+// RegexSymbol, that is compiled into a program for a Pike VM. The VM
+// simulates the NFA with all threads in lockstep, so a match takes
+// time linear in the length of the string, independent of the
+// structure of the pattern.
+
 use std::rc::Rc;
 use std::any::Any;
 
 use crate::object::{
     Object, List, CharString, Function, FnResult, Interface,
-    Exception, new_module, downcast, ptr_eq_plain
+    Exception, Map, new_module, downcast, ptr_eq_plain,
+    interface_object_get
 };
 use crate::vm::{RTE,Env,interface_index,interface_types_set};
+use crate::iterable::new_iterator;
 use crate::class;
 
+// Bounds on {m,n} and on the size of the compiled program.
+const MAX_REPEAT: u32 = 1000;
+const MAX_PROGRAM_SIZE: usize = 100000;
+
 #[derive(Debug,Clone,Copy)]
 enum Class{
-    Alpha, Digit, HexDigit, Lower, Upper,
+    Alpha, Digit, HexDigit, Lower, Upper, Graph,
     Whitespace, UnicodeAlpha, UnicodeLower, UnicodeUpper
 }
 
@@ -21,19 +33,36 @@ struct CharClass {
     neg: bool
 }
 
+#[derive(Debug,Clone,Copy)]
+enum SetItem {
+    Char(char), Range(char,char), Class(CharClass)
+}
+
 #[derive(Debug)]
-struct Range {
-    a: char, b: char
+struct CharSet {
+    items: Box<[SetItem]>,
+    neg: bool
+}
+
+#[derive(Debug,Clone,Copy)]
+enum Assertion {
+    Begin, End, LineBegin, LineEnd, WordBoundary, NotWordBoundary
+}
+
+#[derive(Debug)]
+struct Repeat {
+    symbol: RegexSymbol,
+    min: u32,
+    max: Option<u32>,
+    greedy: bool
 }
 
 #[derive(Debug)]
 enum RegexSymbol {
-    Dot, Char(char), Class(CharClass),
-    Range(Box<Range>), Complement(Box<RegexSymbol>),
-    Regex(Box<[RegexSymbol]>), Group(Box<RegexSymbol>),
-    Qm(Box<RegexSymbol>),
-    Plus(Box<RegexSymbol>),
-    Star(Box<RegexSymbol>),
+    Empty, Dot, Char(char), Class(CharClass), Set(Rc<CharSet>),
+    Assert(Assertion),
+    Regex(Box<[RegexSymbol]>), Group(usize,Box<RegexSymbol>),
+    Repeat(Box<Repeat>),
     Or(Box<[RegexSymbol]>)
 }
 
@@ -47,7 +76,9 @@ struct Token {
 
 struct TokenIterator {
     v: Vec<Token>,
-    index: usize
+    index: usize,
+    groups: usize,
+    names: Vec<(String,usize)>
 }
 
 impl TokenIterator {
@@ -73,21 +104,6 @@ impl TokenIterator {
     }
 }
 
-struct CharIterator<'a> {
-    v: &'a [char],
-    index: usize
-}
-
-impl<'a> CharIterator<'a> {
-    fn get(&self) -> Option<char> {
-        if self.index < self.v.len() {
-            Some(self.v[self.index])
-        } else {
-            None
-        }
-    }
-}
-
 fn scan(s: &[char]) -> Vec<Token> {
     let mut i: usize = 0;
     let mut line: usize = 0;
@@ -107,7 +123,7 @@ fn scan(s: &[char]) -> Vec<Token> {
     acc
 }
 
-fn syntax_error(text: &str) -> Result<Option<RegexSymbol>,String> {
+fn syntax_error<T>(text: &str) -> Result<T,String> {
     Err(format!("Syntax error in regex: {}", text))
 }
 
@@ -115,7 +131,7 @@ fn new_char_class(neg: bool, value: Class) -> RegexSymbol {
     RegexSymbol::Class(CharClass {neg, value})
 }
 
-fn escape_seq(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
+fn escape_seq(i: &mut TokenIterator) -> Result<RegexSymbol,String> {
     let neg = if i.next_is(0,'~') {
         i.index += 1; true
     } else {
@@ -127,12 +143,30 @@ fn escape_seq(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
             't' => RegexSymbol::Char('\t'),
             'n' => RegexSymbol::Char('\n'),
             'r' => RegexSymbol::Char('\r'),
-            'L' => RegexSymbol::Char('{'),
+            'L' => {
+                if i.next_is(1,'B') {
+                    i.index += 1;
+                    RegexSymbol::Assert(Assertion::LineBegin)
+                } else if i.next_is(1,'E') {
+                    i.index += 1;
+                    RegexSymbol::Assert(Assertion::LineEnd)
+                } else {
+                    RegexSymbol::Char('{')
+                }
+            },
             'R' => RegexSymbol::Char('}'),
+            'B' => RegexSymbol::Assert(Assertion::Begin),
+            'E' => RegexSymbol::Assert(Assertion::End),
+            'b' => RegexSymbol::Assert(if neg {
+                Assertion::NotWordBoundary
+            } else {
+                Assertion::WordBoundary
+            }),
             'd' => new_char_class(neg, Class::Digit),
             'x' => new_char_class(neg, Class::HexDigit),
             'l' => new_char_class(neg, Class::Lower),
             'a' => new_char_class(neg, Class::Alpha),
+            'g' => new_char_class(neg, Class::Graph),
             '_' => new_char_class(neg, Class::Whitespace),
             'u' => {
                 if i.next_is(1,'a') {
@@ -154,7 +188,7 @@ fn escape_seq(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
         if let Some(c) = i.get() {
             if c == '}' {
                 i.index += 1;
-                return Ok(Some(x));
+                return Ok(x);
             }
         }
         syntax_error("expected '}'.")
@@ -163,120 +197,191 @@ fn escape_seq(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
     }
 }
 
-fn char_class(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
+fn char_class(i: &mut TokenIterator) -> Result<RegexSymbol,String> {
     let neg = if i.next_is(0,'~') {
         i.index += 1; true
     } else {
         false
     };
-    let mut acc: Vec<RegexSymbol> = Vec::new();
+    let mut acc: Vec<SetItem> = Vec::new();
     loop {
         if i.exists_at(0) {
             let c = i.at(0);
             if c == '{' {
                 i.index += 1;
                 acc.push(match escape_seq(i)? {
-                    Some(x) => x,
-                    None => unreachable!()
+                    RegexSymbol::Char(c) => SetItem::Char(c),
+                    RegexSymbol::Class(class) => SetItem::Class(class),
+                    _ => return syntax_error(
+                        "assertion inside of a character class.")
                 });
             } else if i.next_is(1,'-') && i.exists_at(2) && !i.next_is(2,']') {
                 let a = i.at(0);
                 let b = i.at(2);
-                acc.push(RegexSymbol::Range(Box::new(Range {a,b})));
+                acc.push(SetItem::Range(a,b));
                 i.index += 3;
             } else {
                 if c == ']' {i.index += 1; break;}
-                acc.push(RegexSymbol::Char(c));
+                acc.push(SetItem::Char(c));
                 i.index += 1;
             }
         } else {
             return syntax_error("expected ']', but reached end of regex.");
         }
     }
-    let y = RegexSymbol::Or(acc.into_boxed_slice());
-    Ok(Some(if neg {
-        RegexSymbol::Complement(Box::new(y))
-    } else {y}))
+    Ok(RegexSymbol::Set(Rc::new(CharSet {
+        items: acc.into_boxed_slice(), neg
+    })))
 }
 
-fn atom(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
-    if let Some(c) = i.get() {
-        if c == '.' {
-            i.index += 1;
-            Ok(Some(RegexSymbol::Dot))
-        } else if c == '(' {
-            i.index += 1;
-            let c = i.get().unwrap_or('_');
-            let r = if c == '*' {
+fn group_name(i: &mut TokenIterator) -> Result<String,String> {
+    let mut name = String::new();
+    loop {
+        match i.get() {
+            Some('>') => {i.index += 1; break;},
+            Some(c) if c.is_alphanumeric() || c == '_' => {
+                name.push(c);
                 i.index += 1;
-                RegexSymbol::Group(Box::new(regex(i)?))
-            } else {
-                regex(i)?
-            };
-            if let Some(c) = i.get() {
-                if c == ')' {
-                    i.index += 1;
-                    Ok(Some(r))
-                } else {
-                    syntax_error("expected ')'.")
-                }
-            } else {
-                syntax_error("unexpected end of regex.")
-            }
-        } else if c == '{' {
-            i.index += 1;
-            escape_seq(i)
-        } else if c == '[' {
-            i.index += 1;
-            char_class(i)
-        } else {
-            i.index += 1;
-            Ok(Some(RegexSymbol::Char(c)))
+            },
+            _ => return syntax_error("expected '>' after group name.")
         }
+    }
+    if name.is_empty() {
+        return syntax_error("empty group name.");
+    }
+    if i.names.iter().any(|(x,_)| *x == name) {
+        return syntax_error(&format!("group name '{}' is not unique.", name));
+    }
+    Ok(name)
+}
+
+fn group(i: &mut TokenIterator) -> Result<RegexSymbol,String> {
+    let r = if i.next_is(0,'*') {
+        i.index += 1;
+        i.groups += 1;
+        let index = i.groups;
+        RegexSymbol::Group(index, Box::new(regex(i)?))
+    } else if i.next_is(0,'?') && i.next_is(1,'<') {
+        i.index += 2;
+        let name = group_name(i)?;
+        i.groups += 1;
+        let index = i.groups;
+        i.names.push((name,index));
+        RegexSymbol::Group(index, Box::new(regex(i)?))
+    } else if i.next_is(0,'?') && i.next_is(1,':') {
+        i.index += 2;
+        regex(i)?
     } else {
-        Ok(None)
+        regex(i)?
+    };
+    match i.get() {
+        Some(')') => {
+            i.index += 1;
+            Ok(r)
+        },
+        Some(_) => syntax_error("expected ')'."),
+        None => syntax_error("unexpected end of regex.")
     }
 }
 
-fn postfix_operation(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
-    if let Some(x) = atom(i)? {
-        if let Some(c) = i.get() {
-            if c == '?' {
-                i.index += 1;
-                Ok(Some(RegexSymbol::Qm(Box::new(x))))
-            } else if c == '*' {
-                i.index += 1;
-                Ok(Some(RegexSymbol::Star(Box::new(x))))
-            } else if c == '+' {
-                i.index += 1;
-                Ok(Some(RegexSymbol::Plus(Box::new(x))))
-            } else {
-                Ok(Some(x))
-            }
-        } else {
-            Ok(Some(x))
-        }
+fn atom(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
+    if let Some(c) = i.get() {
+        i.index += 1;
+        Ok(Some(match c {
+            '.' => RegexSymbol::Dot,
+            '^' => RegexSymbol::Assert(Assertion::Begin),
+            '$' => RegexSymbol::Assert(Assertion::End),
+            '(' => group(i)?,
+            '{' => escape_seq(i)?,
+            '[' => char_class(i)?,
+            c => RegexSymbol::Char(c)
+        }))
     } else {
         Ok(None)
     }
 }
 
+fn number(i: &mut TokenIterator) -> Result<u32,String> {
+    let mut n: u32 = 0;
+    let mut empty = true;
+    while let Some(c) = i.get() {
+        let Some(d) = c.to_digit(10) else {break};
+        n = n.saturating_mul(10).saturating_add(d);
+        empty = false;
+        i.index += 1;
+    }
+    if empty {
+        return syntax_error("expected a number in {m,n}.");
+    }
+    if n > MAX_REPEAT {
+        return syntax_error(&format!(
+            "repetition count exceeds {}.", MAX_REPEAT));
+    }
+    Ok(n)
+}
+
+// Parses m}, m,} or m,n} of a bounded repetition p{m,n}.
+fn repeat_bounds(i: &mut TokenIterator) -> Result<(u32,Option<u32>),String> {
+    let min = number(i)?;
+    let max = if i.next_is(0,',') {
+        i.index += 1;
+        if i.next_is(0,'}') {None} else {Some(number(i)?)}
+    } else {
+        Some(min)
+    };
+    if !i.next_is(0,'}') {
+        return syntax_error("expected '}' in {m,n}.");
+    }
+    i.index += 1;
+    if let Some(max) = max {
+        if max < min {
+            return syntax_error("m > n in {m,n}.");
+        }
+    }
+    Ok((min,max))
+}
+
+fn postfix_operation(i: &mut TokenIterator) -> Result<Option<RegexSymbol>,String> {
+    let x = match atom(i)? {
+        Some(x) => x,
+        None => return Ok(None)
+    };
+    let (min,max) = match i.get() {
+        Some('?') => {i.index += 1; (0,Some(1))},
+        Some('*') => {i.index += 1; (0,None)},
+        Some('+') => {i.index += 1; (1,None)},
+        Some('{') if i.exists_at(1) && i.at(1).is_ascii_digit() => {
+            i.index += 1;
+            repeat_bounds(i)?
+        },
+        _ => return Ok(Some(x))
+    };
+    let greedy = if i.next_is(0,'?') {
+        i.index += 1; false
+    } else {
+        true
+    };
+    Ok(Some(RegexSymbol::Repeat(Box::new(Repeat {
+        symbol: x, min, max, greedy
+    }))))
+}
+
 fn regex_chain(i: &mut TokenIterator) -> Result<RegexSymbol,String> {
     let mut acc: Vec<RegexSymbol> = Vec::new();
     loop {
+        if let Some(c) = i.get() {
+            if c == ')' || c == '|' {break;}
+        }
         if let Some(x) = postfix_operation(i)? {
             acc.push(x);
         } else {
             break;
         }
-        if let Some(c) = i.get() {
-            if c == ')' || c == '|' {break;}
-        }
     }
-    Ok(if acc.len() == 1 {
-        match acc.pop() {Some(x) => x, None => unreachable!()}
-    } else {
-        RegexSymbol::Regex(acc.into_boxed_slice())
+    Ok(match acc.len() {
+        0 => RegexSymbol::Empty,
+        1 => match acc.pop() {Some(x) => x, None => unreachable!()},
+        _ => RegexSymbol::Regex(acc.into_boxed_slice())
     })
 }
 
@@ -302,209 +407,486 @@ fn regex(i: &mut TokenIterator) -> Result<RegexSymbol,String> {
     or_operation(i)
 }
 
-fn compile(s: &[char]) -> Result<RegexSymbol,String> {
-    let v = scan(s);
-    let mut i = TokenIterator {v, index: 0};
-    regex(&mut i)
+enum Inst {
+    Char(char), Any, Class(CharClass), Set(Rc<CharSet>),
+    Assert(Assertion), Split(usize,usize), Jmp(usize),
+    Save(usize), Match
 }
 
-fn symbol_match(regex: &RegexSymbol, i: &mut CharIterator,
-    groups: &mut Option<Vec<Object>>
-) -> bool {
-    match *regex {
-        RegexSymbol::Char(c) => {
-            if let Some(x) = i.get() {
-                i.index += 1;
-                x == c
-            } else {
-                false
-            }
-        },
-        RegexSymbol::Dot => {
-            if i.get().is_some() {i.index += 1; true} else {false}
-        },
+struct Program {
+    code: Vec<Inst>,
+    groups: usize,
+    names: Rc<[(String,usize)]>
+}
+
+fn too_large<T>() -> Result<T,String> {
+    syntax_error("the regex is too large.")
+}
+
+fn emit(code: &mut Vec<Inst>, x: &RegexSymbol) -> Result<(),String> {
+    if code.len() > MAX_PROGRAM_SIZE {return too_large();}
+    match *x {
+        RegexSymbol::Empty => {},
+        RegexSymbol::Dot => code.push(Inst::Any),
+        RegexSymbol::Char(c) => code.push(Inst::Char(c)),
+        RegexSymbol::Class(class) => code.push(Inst::Class(class)),
+        RegexSymbol::Set(ref set) => code.push(Inst::Set(set.clone())),
+        RegexSymbol::Assert(a) => code.push(Inst::Assert(a)),
         RegexSymbol::Regex(ref a) => {
-            for r in a.iter() {
-                if !symbol_match(r,i,groups) {return false;}
-            }
-            true
-        },
-        RegexSymbol::Group(ref r) => {
-            let j = i.index;
-            if symbol_match(r,i,groups) {
-                if let Some(ref mut groups) = *groups {
-                    let x = CharString::new_object(i.v[j..i.index].to_vec());
-                    groups.push(x);
-                }
-                true
-            } else {
-                false
-            }
+            for x in a.iter() {emit(code,x)?;}
         },
-        RegexSymbol::Qm(ref r) => {
-            let index = i.index;
-            if !symbol_match(r,i,groups) {
-                i.index = index;
-            }
-            true
-        },
-        RegexSymbol::Star(ref r) => {
-            loop {
-                let index = i.index;
-                if !symbol_match(r,i,groups) {
-                    i.index = index;
-                    break;
-                }
-            }
-            true
+        RegexSymbol::Group(index, ref x) => {
+            code.push(Inst::Save(2*index));
+            emit(code,x)?;
+            code.push(Inst::Save(2*index+1));
         },
-        RegexSymbol::Plus(ref r) => {
-            if !symbol_match(r,i,groups) {return false;}
-            loop {
-                let index = i.index;
-                if !symbol_match(r,i,groups) {
-                    i.index = index;
-                    break;
+        RegexSymbol::Or(ref a) => {
+            let mut jumps: Vec<usize> = Vec::new();
+            for (k,x) in a.iter().enumerate() {
+                if k+1 < a.len() {
+                    let split = code.len();
+                    code.push(Inst::Split(split+1,0));
+                    emit(code,x)?;
+                    jumps.push(code.len());
+                    code.push(Inst::Jmp(0));
+                    let next = code.len();
+                    code[split] = Inst::Split(split+1,next);
+                } else {
+                    emit(code,x)?;
                 }
             }
-            true
+            let end = code.len();
+            for j in jumps {code[j] = Inst::Jmp(end);}
         },
-        RegexSymbol::Or(ref a) => {
-            let index = i.index;
-            for r in a.iter() {
-                if symbol_match(r,i,groups) {return true;}
-                i.index = index;
-            }
-            false
+        RegexSymbol::Repeat(ref r) => emit_repeat(code,r)?
+    }
+    Ok(())
+}
+
+fn split(greedy: bool, body: usize, out: usize) -> Inst {
+    if greedy {Inst::Split(body,out)} else {Inst::Split(out,body)}
+}
+
+fn emit_repeat(code: &mut Vec<Inst>, r: &Repeat) -> Result<(),String> {
+    for _ in 0..r.min {
+        emit(code,&r.symbol)?;
+        if code.len() > MAX_PROGRAM_SIZE {return too_large();}
+    }
+    match r.max {
+        None => {
+            let start = code.len();
+            code.push(Inst::Split(0,0));
+            emit(code,&r.symbol)?;
+            code.push(Inst::Jmp(start));
+            code[start] = split(r.greedy,start+1,code.len());
         },
-        RegexSymbol::Class(char_class) => {
-            if let Some(x) = i.get() {
-                i.index += 1;
-                let y = match char_class.value {
-                    Class::Alpha => x.is_ascii_alphabetic(),
-                    Class::Digit => x.is_digit(10),
-                    Class::HexDigit => x.is_digit(16),
-                    Class::Lower => x.is_ascii_lowercase(),
-                    Class::Upper => x.is_ascii_uppercase(),
-                    Class::Whitespace => x.is_whitespace(),
-                    Class::UnicodeAlpha => x.is_alphabetic(),
-                    Class::UnicodeLower => x.is_lowercase(),
-                    Class::UnicodeUpper => x.is_uppercase()
-                };
-                if char_class.neg {!y} else {y}
-            } else {
-                false
+        Some(max) => {
+            let mut splits: Vec<usize> = Vec::new();
+            for _ in r.min..max {
+                splits.push(code.len());
+                code.push(Inst::Split(0,0));
+                emit(code,&r.symbol)?;
+                if code.len() > MAX_PROGRAM_SIZE {return too_large();}
             }
-        },
-        RegexSymbol::Range(ref range) => {
-            if let Some(x) = i.get() {
-                i.index += 1;
-                range.a <= x && x <= range.b
-            } else {
-                false
+            let end = code.len();
+            for s in splits {code[s] = split(r.greedy,s+1,end);}
+        }
+    }
+    Ok(())
+}
+
+fn compile(s: &[char]) -> Result<Program,String> {
+    let v = scan(s);
+    let mut i = TokenIterator {v, index: 0, groups: 0, names: Vec::new()};
+    let x = regex(&mut i)?;
+    if i.get().is_some() {
+        return syntax_error("unexpected ')'.");
+    }
+    let mut code: Vec<Inst> = vec![Inst::Save(0)];
+    emit(&mut code,&x)?;
+    code.push(Inst::Save(1));
+    code.push(Inst::Match);
+    if code.len() > MAX_PROGRAM_SIZE {return too_large();}
+    Ok(Program {code, groups: i.groups+1, names: Rc::from(i.names)})
+}
+
+fn class_match(class: CharClass, x: char) -> bool {
+    let y = match class.value {
+        Class::Alpha => x.is_ascii_alphabetic(),
+        Class::Digit => x.is_ascii_digit(),
+        Class::HexDigit => x.is_ascii_hexdigit(),
+        Class::Lower => x.is_ascii_lowercase(),
+        Class::Upper => x.is_ascii_uppercase(),
+        Class::Graph => x.is_ascii_graphic(),
+        Class::Whitespace => x.is_whitespace(),
+        Class::UnicodeAlpha => x.is_alphabetic(),
+        Class::UnicodeLower => x.is_lowercase(),
+        Class::UnicodeUpper => x.is_uppercase()
+    };
+    if class.neg {!y} else {y}
+}
+
+fn set_match(set: &CharSet, x: char) -> bool {
+    let y = set.items.iter().any(|item| match *item {
+        SetItem::Char(c) => c == x,
+        SetItem::Range(a,b) => a <= x && x <= b,
+        SetItem::Class(class) => class_match(class,x)
+    });
+    if set.neg {!y} else {y}
+}
+
+fn is_word(c: Option<&char>) -> bool {
+    c.is_some_and(|&c| c.is_alphanumeric() || c == '_')
+}
+
+fn assertion_holds(a: Assertion, s: &[char], pos: usize) -> bool {
+    let prev = if pos > 0 {s.get(pos-1)} else {None};
+    let next = s.get(pos);
+    match a {
+        Assertion::Begin => pos == 0,
+        Assertion::End => pos == s.len(),
+        Assertion::LineBegin => matches!(prev, None | Some(&'\n')),
+        Assertion::LineEnd => matches!(next, None | Some(&'\n')),
+        Assertion::WordBoundary => is_word(prev) != is_word(next),
+        Assertion::NotWordBoundary => is_word(prev) == is_word(next)
+    }
+}
+
+const NONE: usize = usize::MAX;
+
+struct Threads {
+    list: Vec<(usize,Vec<usize>)>,
+    mark: Vec<usize>
+}
+
+// Follows the empty transitions from pc and adds the resulting
+// threads to the list, in the order of their priority.
+fn add_thread(prog: &Program, t: &mut Threads, gen: usize,
+    pc: usize, slots: Vec<usize>, s: &[char], pos: usize
+) {
+    let mut stack: Vec<(usize,Vec<usize>)> = vec![(pc,slots)];
+    while let Some((pc,mut slots)) = stack.pop() {
+        if t.mark[pc] == gen {continue;}
+        t.mark[pc] = gen;
+        match prog.code[pc] {
+            Inst::Jmp(x) => stack.push((x,slots)),
+            Inst::Split(x,y) => {
+                stack.push((y,slots.clone()));
+                stack.push((x,slots));
+            },
+            Inst::Save(k) => {
+                slots[k] = pos;
+                stack.push((pc+1,slots));
+            },
+            Inst::Assert(a) => {
+                if assertion_holds(a,s,pos) {
+                    stack.push((pc+1,slots));
+                }
+            },
+            _ => t.list.push((pc,slots))
+        }
+    }
+}
+
+// Runs the program on s from position start. Returns the capture
+// slots of the leftmost-first match. If anchored, the match has to
+// begin at start, if full, it has to end at the end of s.
+fn exec(prog: &Program, s: &[char], start: usize, anchored: bool, full: bool)
+-> Option<Vec<usize>>
+{
+    let n = prog.code.len();
+    let mut clist = Threads {list: Vec::new(), mark: vec![NONE;n]};
+    let mut nlist = Threads {list: Vec::new(), mark: vec![NONE;n]};
+    let mut matched: Option<Vec<usize>> = None;
+    let mut pos = start;
+    loop {
+        if matched.is_none() && (!anchored || pos == start) {
+            add_thread(prog,&mut clist,pos,0,vec![NONE;2*prog.groups],s,pos);
+        }
+        if clist.list.is_empty() && (anchored || matched.is_some()) {break;}
+        let c = s.get(pos).copied();
+        for (pc,slots) in clist.list.drain(..) {
+            let step = match prog.code[pc] {
+                Inst::Match => {
+                    if full && pos != s.len() {continue;}
+                    matched = Some(slots);
+                    break;
+                },
+                Inst::Char(x) => c == Some(x),
+                Inst::Any => c.is_some(),
+                Inst::Class(class) => c.is_some_and(|c| class_match(class,c)),
+                Inst::Set(ref set) => c.is_some_and(|c| set_match(set,c)),
+                _ => false
+            };
+            if step {
+                add_thread(prog,&mut nlist,pos+1,pc+1,slots,s,pos+1);
             }
+        }
+        if pos >= s.len() {break;}
+        std::mem::swap(&mut clist,&mut nlist);
+        pos += 1;
+    }
+    matched
+}
+
+fn slice_object(s: &[char], slots: &[usize], k: usize) -> Object {
+    let (a,b) = (slots[2*k], slots[2*k+1]);
+    if a == NONE || b == NONE {
+        Object::Null
+    } else {
+        CharString::new_object(s[a..b].to_vec())
+    }
+}
+
+// Searches the next of the non-overlapping matches, beginning at
+// position i. An empty match is followed by a search from the next
+// position. Each call is a new search that may scan up to the end of
+// the string, thus iteration is quadratic in the worst case.
+fn next_match(prog: &Program, s: &[char], i: &mut usize) -> Option<Vec<usize>> {
+    if *i > s.len() {return None;}
+    match exec(prog,s,*i,false,false) {
+        Some(slots) => {
+            let (a,b) = (slots[0],slots[1]);
+            *i = if b == a {b+1} else {b};
+            Some(slots)
         },
-        RegexSymbol::Complement(ref compl) => {
-            if i.get().is_some() {
-                let index = i.index + 1;
-                if symbol_match(compl,i,groups) {
-                    i.index = index;
-                    false
+        None => {
+            *i = s.len()+1;
+            None
+        }
+    }
+}
+
+struct Match {
+    s: Rc<CharString>,
+    slots: Vec<usize>,
+    names: Rc<[(String,usize)]>
+}
+
+impl Match {
+    fn new_object(prog: &Program, s: &Rc<CharString>, slots: Vec<usize>) -> Object {
+        Object::Interface(Rc::new(Match {
+            s: s.clone(), slots, names: prog.names.clone()
+        }))
+    }
+    fn group_index(&self, key: &Object) -> Option<usize> {
+        match *key {
+            Object::Int(k) => {
+                if k >= 0 && (k as usize) < self.slots.len()/2 {
+                    Some(k as usize)
                 } else {
-                    i.index = index;
-                    true
+                    None
                 }
-            } else {
-                false
-            }
+            },
+            Object::String(ref name) => {
+                let name = name.to_string();
+                self.names.iter().find(|(x,_)| *x == name).map(|&(_,k)| k)
+            },
+            _ => None
         }
     }
+    fn span(&self, k: usize) -> Option<(usize,usize)> {
+        let (a,b) = (self.slots[2*k], self.slots[2*k+1]);
+        if a == NONE || b == NONE {None} else {Some((a,b))}
+    }
+    fn group(&self, k: usize) -> Object {
+        slice_object(&self.s.data,&self.slots,k)
+    }
 }
 
-fn re_match(regex: &RegexSymbol, s: &[char]) -> bool {
-    let mut i = CharIterator{index: 0, v: s};
-    let y = symbol_match(regex, &mut i, &mut None);
-    y && i.index == i.v.len()
+impl Interface for Match {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Match".to_string()
+    }
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        let (a,b) = (self.slots[0], self.slots[1]);
+        Ok(format!("match object {}..{}: \"{}\"", a, b,
+            self.s.data[a..b].iter().collect::<String>()))
+    }
+    fn get_type(&self, env: &mut Env) -> FnResult {
+        Ok(Object::Interface(env.rte().interface_types
+            .borrow()[interface_index::MATCH].clone()))
+    }
+    fn is_instance_of(&self, type_obj: &Object, rte: &RTE) -> bool {
+        if let Object::Interface(p) = type_obj {
+            ptr_eq_plain(p,&rte.interface_types.borrow()[interface_index::MATCH])
+        } else {false}
+    }
+    fn get(self: Rc<Self>, key: &Object, env: &mut Env) -> FnResult {
+        interface_object_get("Match",key,env,interface_index::MATCH)
+    }
+    fn index(self: Rc<Self>, indices: &[Object], env: &mut Env) -> FnResult {
+        match indices.len() {
+            1 => {}, n => return env.argc_error(n,1,1,"index operation")
+        }
+        match self.group_index(&indices[0]) {
+            Some(k) => Ok(self.group(k)),
+            None => env.index_error(&format!(
+                "Index error in m[k]: there is no group {}.", indices[0]))
+        }
+    }
 }
 
-fn re_groups(regex: &RegexSymbol, s: &[char]) -> Object {
-    let mut groups: Option<Vec<Object>> = Some(Vec::new());
-    let mut i = CharIterator{index: 0, v: s};
-    let y = symbol_match(regex, &mut i, &mut groups);
-    if y && i.index == i.v.len() {
-        List::new_object(groups.unwrap())
-    } else {
-        Object::Null
+fn match_group<'a>(env: &mut Env, pself: &'a Object, argv: &[Object], id: &str)
+-> Result<(&'a Match,usize),FnResult>
+{
+    let m = match downcast::<Match>(pself) {
+        Some(m) => m,
+        None => return Err(env.type_error1(&format!(
+            "Type error in m.{}(): m is not a match object.", id), "m", pself))
+    };
+    let k = match argv.len() {
+        0 => 0,
+        1 => match m.group_index(&argv[0]) {
+            Some(k) => k,
+            None => return Err(env.index_error(&format!(
+                "Index error in m.{}(k): there is no group {}.", id, argv[0])))
+        },
+        n => return Err(env.argc_error(n,0,1,id))
+    };
+    Ok((m,k))
+}
+
+fn match_start(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    let (m,k) = match match_group(env,pself,argv,"start") {Ok(x) => x, Err(e) => return e};
+    Ok(match m.span(k) {Some((a,_)) => Object::Int(a as i32), None => Object::Null})
+}
+
+fn match_stop(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    let (m,k) = match match_group(env,pself,argv,"stop") {Ok(x) => x, Err(e) => return e};
+    Ok(match m.span(k) {Some((_,b)) => Object::Int(b as i32), None => Object::Null})
+}
+
+fn match_span(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    let (m,k) = match match_group(env,pself,argv,"span") {Ok(x) => x, Err(e) => return e};
+    Ok(match m.span(k) {
+        Some((a,b)) => List::new_object(vec![
+            Object::Int(a as i32), Object::Int(b as i32)]),
+        None => Object::Null
+    })
+}
+
+fn match_group_value(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    let (m,k) = match match_group(env,pself,argv,"group") {Ok(x) => x, Err(e) => return e};
+    Ok(m.group(k))
+}
+
+fn match_groups(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"groups")
     }
+    let (m,_) = match match_group(env,pself,argv,"groups") {Ok(x) => x, Err(e) => return e};
+    let v: Vec<Object> = (1..m.slots.len()/2).map(|k| m.group(k)).collect();
+    Ok(List::new_object(v))
 }
 
+fn match_named(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        0 => {}, n => return env.argc_error(n,0,0,"named")
+    }
+    let (m,_) = match match_group(env,pself,argv,"named") {Ok(x) => x, Err(e) => return e};
+    let map = Map::new();
+    {
+        let mut map = map.borrow_mut();
+        for (name,k) in m.names.iter() {
+            map.insert(name,m.group(*k));
+        }
+    }
+    Ok(Object::Map(map))
+}
 
-fn re_list(regex: &RegexSymbol, s: &[char]) -> Object {
-    let n = s.len();
-    let mut i = 0;
-    let mut j = CharIterator {index: 0, v: s};
+fn re_match(regex: &Program, s: &[char]) -> bool {
+    exec(regex,s,0,true,true).is_some()
+}
+
+fn re_groups(regex: &Program, s: &[char]) -> Object {
+    match exec(regex,s,0,true,true) {
+        Some(slots) => {
+            let v: Vec<Object> = (1..regex.groups)
+                .map(|k| slice_object(s,&slots,k)).collect();
+            List::new_object(v)
+        },
+        None => Object::Null
+    }
+}
+
+fn re_named(regex: &Program, s: &[char]) -> Object {
+    match exec(regex,s,0,true,true) {
+        Some(slots) => {
+            let m = Map::new();
+            {
+                let mut m = m.borrow_mut();
+                for (name,k) in regex.names.iter() {
+                    m.insert(name,slice_object(s,&slots,*k));
+                }
+            }
+            Object::Map(m)
+        },
+        None => Object::Null
+    }
+}
+
+fn re_search(regex: &Program, s: &Rc<CharString>, start: usize) -> Object {
+    if start > s.data.len() {return Object::Null;}
+    match exec(regex,&s.data,start,false,false) {
+        Some(slots) => Match::new_object(regex,s,slots),
+        None => Object::Null
+    }
+}
+
+fn re_list(regex: &Program, s: &[char]) -> Object {
     let mut acc: Vec<Object> = Vec::new();
-    while i < n {
-        j.index = i;
-        if symbol_match(regex, &mut j, &mut None) {
-            let x = CharString::new_object(j.v[i..j.index].to_vec());
-            acc.push(x);
-            i = j.index;
-        } else {
-            i += 1;
-        }
+    let mut i = 0;
+    while let Some(slots) = next_match(regex,s,&mut i) {
+        acc.push(slice_object(s,&slots,0));
     }
     List::new_object(acc)
 }
 
-fn re_split(regex: &RegexSymbol, s: &[char]) -> Object {
-    let n = s.len();
-    let mut i = 0;
-    let mut j = CharIterator {index: 0, v: s};
+fn re_split(regex: &Program, s: &[char]) -> Object {
     let mut acc: Vec<Object> = Vec::new();
     let mut start = 0;
-    while i < n {
-        j.index = i;
-        if symbol_match(regex, &mut j, &mut None) {
-            let x = CharString::new_object(j.v[start..i].to_vec());
-            acc.push(x);
-            i = j.index;
-            start = j.index;
-        } else {
-            i += 1;
-        }
+    let mut i = 0;
+    while let Some(slots) = next_match(regex,s,&mut i) {
+        let (a,b) = (slots[0],slots[1]);
+        if b == 0 || a == s.len() {continue;}
+        acc.push(CharString::new_object(s[start..a].to_vec()));
+        start = b;
     }
-    let x = CharString::new_object(j.v[start..].to_vec());
-    acc.push(x);
+    acc.push(CharString::new_object(s[start..].to_vec()));
     List::new_object(acc)
 }
 
-fn re_replace(regex: &RegexSymbol, s: &[char],
-    env: &mut Env, f: &Object
+// Calls f with the matched substring, or with the match object
+// if with_match is set.
+fn re_replace(regex: &Program, s: &Rc<CharString>,
+    env: &mut Env, f: &Object, with_match: bool
 ) -> FnResult
 {
-    let n = s.len();
+    let data = &s.data;
+    let mut acc: Vec<char> = Vec::with_capacity(data.len());
+    let mut start = 0;
     let mut i = 0;
-    let mut j = CharIterator {index: 0, v: s};
-    let mut acc: Vec<char> = Vec::with_capacity(n);
-    while i<n {
-        j.index = i;
-        if symbol_match(regex, &mut j, &mut None) {
-            let x = CharString::new_object(j.v[i..j.index].to_vec());
-            let y = env.call(f, &Object::Null, &[x])?;
-            match y {
-                Object::String(sy) => {
-                    acc.extend_from_slice(&sy.data);
-                },
-                _ => return env.type_error(
-                    "Type error in r.replace(s,f): f(x) is not a string.")
-            }
-            i = j.index;
+    while let Some(slots) = next_match(regex,data,&mut i) {
+        let (a,b) = (slots[0],slots[1]);
+        acc.extend_from_slice(&data[start..a]);
+        let x = if with_match {
+            Match::new_object(regex,s,slots)
         } else {
-            acc.push(j.v[i]);
-            i += 1;
+            CharString::new_object(data[a..b].to_vec())
+        };
+        match env.call(f, &Object::Null, &[x])? {
+            Object::String(y) => {
+                acc.extend_from_slice(&y.data);
+            },
+            y => return env.type_error1(&format!(
+                "Type error in r.{}(s,f): f(x) is not a string.", method(with_match)),
+                "f(x)", &y)
         }
+        start = b;
     }
+    acc.extend_from_slice(&data[start..]);
     Ok(CharString::new_object(acc))
 }
 
@@ -572,25 +954,108 @@ fn regex_groups(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     }
 }
 
-fn regex_replace(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+fn regex_named(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
-        2 => {}, n => return env.argc_error(n,2,2,"replace")
+        1 => {}, n => return env.argc_error(n,1,1,"named")
     }
     match argv[0] {
         Object::String(ref s) => {
             if let Some(r) = downcast::<Regex>(pself) {
-                re_replace(&r.regex, &s.data, env, &argv[1])
+                Ok(re_named(&r.regex, &s.data))
             } else {
-                env.type_error1("Type error in r.replace(s,f): r is not a regex.", "r", pself)
+                env.type_error1("Type error in r.named(s): r is not a regex.", "r", pself)
             }
         },
-        ref s => env.type_error1("Type error in r.replace(s,f): s is not a string.", "s", s)
+        ref s => env.type_error1("Type error in r.named(s): s is not a string.", "s", s)
     }
 }
 
+fn regex_search(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    let start = match argv.len() {
+        1 => 0,
+        2 => match argv[1] {
+            Object::Int(i) => if i<0 {0} else {i as usize},
+            ref i => return env.type_error1(
+                "Type error in r.search(s,i): i is not an integer.", "i", i)
+        },
+        n => return env.argc_error(n,1,2,"search")
+    };
+    match argv[0] {
+        Object::String(ref s) => {
+            if let Some(r) = downcast::<Regex>(pself) {
+                Ok(re_search(&r.regex, s, start))
+            } else {
+                env.type_error1("Type error in r.search(s): r is not a regex.", "r", pself)
+            }
+        },
+        ref s => env.type_error1("Type error in r.search(s): s is not a string.", "s", s)
+    }
+}
+
+fn regex_finditer(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"finditer")
+    }
+    let s = match argv[0] {
+        Object::String(ref s) => s.clone(),
+        ref s => return env.type_error1(
+            "Type error in r.finditer(s): s is not a string.", "s", s)
+    };
+    if downcast::<Regex>(pself).is_none() {
+        return env.type_error1(
+            "Type error in r.finditer(s): r is not a regex.", "r", pself);
+    }
+    let r = pself.clone();
+    let mut i = 0;
+    let f = Box::new(move |_env: &mut Env, _pself: &Object, _argv: &[Object]| -> FnResult {
+        let regex = match downcast::<Regex>(&r) {
+            Some(r) => &r.regex,
+            None => unreachable!()
+        };
+        Ok(match next_match(regex,&s.data,&mut i) {
+            Some(slots) => Match::new_object(regex,&s,slots),
+            None => Object::empty()
+        })
+    });
+    Ok(new_iterator(f))
+}
+
+fn method(with_match: bool) -> &'static str {
+    if with_match {"replace_match"} else {"replace"}
+}
+
+fn replace(env: &mut Env, pself: &Object, argv: &[Object], with_match: bool)
+-> FnResult
+{
+    let id = method(with_match);
+    match argv.len() {
+        2 => {}, n => return env.argc_error(n,2,2,id)
+    }
+    match argv[0] {
+        Object::String(ref s) => {
+            if let Some(r) = downcast::<Regex>(pself) {
+                re_replace(&r.regex, s, env, &argv[1], with_match)
+            } else {
+                env.type_error1(&format!(
+                    "Type error in r.{}(s,f): r is not a regex.", id), "r", pself)
+            }
+        },
+        ref s => env.type_error1(&format!(
+            "Type error in r.{}(s,f): s is not a string.", id), "s", s)
+    }
+}
+
+fn regex_replace(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    replace(env, pself, argv, false)
+}
+
+fn regex_replace_match(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+    replace(env, pself, argv, true)
+}
+
 struct Regex {
     index: usize,
-    regex: RegexSymbol
+    regex: Program
 }
 
 impl Interface for Regex {
@@ -629,7 +1094,7 @@ fn regex_compile(index: usize) -> Object {
             Object::String(ref s) => {
                 let r = match compile(&s.data) {
                     Ok(r) => r,
-                    Err(e) => return env.std_exception(&e)
+                    Err(e) => return env.value_error(&e)
                 };
                 Ok(Object::Interface(Rc::new(Regex {
                     index, regex: r
@@ -641,6 +1106,31 @@ fn regex_compile(index: usize) -> Object {
     Function::mutable(f,1,1)
 }
 
+const REGEX_DOCS: &[(&str, &str)] = &[
+    ("match", "r.match(s)\nTrue if r matches the whole string s."),
+    ("list", "r.list(s)\nThe list of all non-overlapping matches in s."),
+    ("split", "r.split(s)\nSplits s at the matches of r."),
+    ("groups", "r.groups(s)\nThe list of groups if r matches s, else null."),
+    ("named", "r.named(s)\nThe map of named groups if r matches s, else null."),
+    ("replace", "r.replace(s, f)\nReplaces each match t by f(t), or by f if f\nis a string."),
+    ("replace_match", "r.replace_match(s, f)\nReplaces each match by f(m), m being the\nmatch object."),
+    ("search", "r.search(s, i=0)\nThe leftmost match in s from index i on, as a\nmatch object, or null."),
+    ("finditer", "r.finditer(s)\nA lazy iterator over the match objects of all\nnon-overlapping matches.")
+];
+
+const MATCH_DOCS: &[(&str, &str)] = &[
+    ("start", "m.start(k=0)\nThe index where the group k begins."),
+    ("stop", "m.stop(k=0)\nThe index after the end of the group k."),
+    ("span", "m.span(k=0)\nThe list [m.start(k), m.stop(k)]."),
+    ("group", "m.group(k=0)\nThe substring matched by the group k."),
+    ("groups", "m.groups()\nThe list of groups, without group 0."),
+    ("named", "m.named()\nThe map of named groups.")
+];
+
+const DOCS: &[(&str, &str)] = &[
+    ("re", "re(pattern)\nCompiles the regular expression pattern into a\nregex object.")
+];
+
 pub fn load_regex(env: &mut Env) -> Object {
     let type_regex = class::Class::new("Regex", &Object::Null);
     {
@@ -649,15 +1139,35 @@ pub fn load_regex(env: &mut Env) -> Object {
         m.insert_fn_plain("list", regex_list, 1, 1);
         m.insert_fn_plain("split", regex_split, 1, 1);
         m.insert_fn_plain("groups", regex_groups, 1, 1);
+        m.insert_fn_plain("named", regex_named, 1, 1);
         m.insert_fn_plain("replace", regex_replace, 2, 2);
+        m.insert_fn_plain("replace_match", regex_replace_match, 2, 2);
+        m.insert_fn_plain("search", regex_search, 1, 2);
+        m.insert_fn_plain("finditer", regex_finditer, 1, 1);
+        m.set_docs(REGEX_DOCS);
     }
     interface_types_set(env.rte(), interface_index::REGEX, type_regex.clone());
 
+    let type_match = class::Class::new("Match", &Object::Null);
+    {
+        let mut m = type_match.map.borrow_mut();
+        m.insert_fn_plain("start", match_start, 0, 1);
+        m.insert_fn_plain("stop", match_stop, 0, 1);
+        m.insert_fn_plain("span", match_span, 0, 1);
+        m.insert_fn_plain("group", match_group_value, 0, 1);
+        m.insert_fn_plain("groups", match_groups, 0, 0);
+        m.insert_fn_plain("named", match_named, 0, 0);
+        m.set_docs(MATCH_DOCS);
+    }
+    interface_types_set(env.rte(), interface_index::MATCH, type_match.clone());
+
     let regex = new_module("regex");
     {
         let mut m = regex.map.borrow_mut();
         m.insert("re",regex_compile(interface_index::REGEX));
+        m.set_docs(DOCS);
         m.insert("Regex",Object::Interface(type_regex));
+        m.insert("Match",Object::Interface(type_match));
     }
     Object::Interface(Rc::new(regex))
 }
@@ -616,6 +616,17 @@ fn sf_Beta(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     Ok(Object::Float(beta(x,y)))
 }
 
+const EI_DOCS: &[(&str, &str)] = &[
+    ("K", "K(m)\nThe complete elliptic integral of the first kind,\nm=k^2."),
+    ("E", "E(m), E(phi, m)\nThe complete or incomplete elliptic integral of\nthe second kind, m=k^2."),
+    ("F", "F(phi, m)\nThe incomplete elliptic integral of the first kind,\nm=k^2."),
+    ("Pi", "Pi(phi, n, m)\nThe incomplete elliptic integral of the third kind,\nm=k^2."),
+    ("RF", "RF(x, y, z)\nThe Carlson symmetric form RF."),
+    ("RC", "RC(x, y)\nThe Carlson symmetric form RC(x, y) = RF(x, y, y)."),
+    ("RJ", "RJ(x, y, z, p)\nThe Carlson symmetric form RJ."),
+    ("RD", "RD(x, y, z)\nThe Carlson symmetric form RD(x, y, z) = RJ(x, y, z, z).")
+];
+
 pub fn load_sf_ei() -> Object {
     let ei = new_module("ei");
     {
@@ -628,10 +639,23 @@ pub fn load_sf_ei() -> Object {
         m.insert_fn_plain("RC", sf_RC, 2,2);
         m.insert_fn_plain("RJ", sf_RJ, 4,4);
         m.insert_fn_plain("RD", sf_RD, 3,3);
+        m.set_docs(EI_DOCS);
     }
     Object::Interface(Rc::new(ei))
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("PP", "PP(n, m, x)\nThe associated Legendre function P_n,m(x)."),
+    ("PH", "PH(n, x)\nThe Hermite polynomial H_n(x)."),
+    ("PT", "PT(n, x)\nThe Chebyshev polynomial of the first kind T_n(x)."),
+    ("PU", "PU(n, x)\nThe Chebyshev polynomial of the second kind U_n(x)."),
+    ("gamma", "gamma(s, x)\nThe lower incomplete gamma function."),
+    ("Gamma", "Gamma(s, x)\nThe upper incomplete gamma function."),
+    ("zeta", "zeta(x)\nThe Riemann zeta function."),
+    ("B", "B(n)\nThe Bernoulli number B_n, with B(1) = +1/2."),
+    ("Beta", "Beta(x, y)\nThe beta function.")
+];
+
 pub fn load_sf() -> Object {
     let sf = new_module("sf");
     {
@@ -645,6 +669,7 @@ pub fn load_sf() -> Object {
         m.insert_fn_plain("zeta", sf_zeta, 1, 1);
         m.insert_fn_plain("B", sf_B, 1, 1);
         m.insert_fn_plain("Beta", sf_Beta, 2, 2);
+        m.set_docs(DOCS);
     }
     Object::Interface(Rc::new(sf))
 }
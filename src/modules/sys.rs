@@ -7,14 +7,21 @@ use crate::object::{
     Object, FnResult, Interface, Exception,
     VARIADIC, new_module, downcast
 };
-use crate::vm::{RTE, Env};
+use crate::vm::{RTE, Env, Exit};
 use crate::class::{Class, Table};
 
+fn exit_process(env: &mut Env, status: i32) -> FnResult {
+    let rte = env.rte();
+    if rte.trap_exit.get() {
+        return Err(Exception::raise(rte, Object::Interface(Rc::new(Exit {status}))));
+    }
+    rte.flush_buffers();
+    process::exit(status);
+}
+
 fn exit(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
-        0 => {
-            process::exit(0);
-        },
+        0 => exit_process(env, 0),
         1 => {
             let x = match argv[0] {
                 Object::Int(x)=>x,
@@ -23,7 +30,7 @@ fn exit(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
                     "n", x
                 )
             };
-            process::exit(x);
+            exit_process(env, x)
         },
         n => {
             env.argc_error(n,0,1,"exit")
@@ -140,22 +147,55 @@ fn cmd(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
             }
             buffer
         },
-        _ => panic!()
+        ref x => return env.type_error1(
+            "Type error in cmd(command,argv): argv is not a list.","argv",x)
     };
     if env.rte().capabilities.borrow().command {
         match process::Command::new(&cmd_name).args(&a[..]).status() {
             Ok(status) => {
                 Ok(if status.success() {Object::Null} else {Object::Int(1)})
             },
-            Err(_) => env.std_exception(&format!(
+            Err(e) => env.os_error(&format!(
                 "Error in cmd(command,argv): failed to execute command=='{}'.",
-                cmd_name))
+                cmd_name), e.raw_os_error(), &cmd_name)
         }
     } else {
-        env.std_exception("Error in cmd(command,argv): permission denied.")
+        env.permission_error("Error in cmd(command,argv): permission denied.", &cmd_name)
     }
 }
 
+fn getenv(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"getenv")
+    }
+    let key = match argv[0] {
+        Object::String(ref s) => s.to_string(),
+        ref x => return env.type_error1(
+            "Type error in getenv(key): key is not a string.","key",x)
+    };
+    if !env.rte().capabilities.borrow().env {
+        return env.permission_error(
+            "Error in getenv(key): permission denied.", "");
+    }
+    match std::env::var(&key) {
+        Ok(value) => Ok(Object::from(value.as_str())),
+        Err(_) => Ok(Object::Null)
+    }
+}
+
+const DOCS: &[(&str, &str)] = &[
+    ("exit", "exit(n=0)\nExits the program with the return value n."),
+    ("call", "call(n, f, *argv)\nCalls f(*argv) with a new call stack of size n,\nfor unlimited recursion depth."),
+    ("eput", "eput(*a)\nLike put, but writes to stderr."),
+    ("eprint", "eprint(*a)\nLike print, but writes to stderr."),
+    ("istable", "istable(x)\nTrue if x is a table object."),
+    ("isclass", "isclass(x)\nTrue if x is a class object."),
+    ("id", "id(x)\nThe memory address of a pointer object, or null."),
+    ("main", "main()\nTrue if the current module is executed as the\nmain program."),
+    ("cmd", "cmd(command, argv)\nRuns the command with the argument list argv.\nReturns null on success, otherwise an integer."),
+    ("getenv", "getenv(key)\nThe value of the environment variable key, or null.")
+];
+
 pub fn load_sys(rte: &Rc<RTE>) -> Object {
     let sys = new_module("sys");
     {
@@ -173,6 +213,8 @@ pub fn load_sys(rte: &Rc<RTE>) -> Object {
         m.insert_fn_plain("id", id, 1, 1);
         m.insert_fn_plain("main", ismain, 0, 0);
         m.insert_fn_plain("cmd", cmd, 2, 2);
+        m.insert_fn_plain("getenv", getenv, 1, 1);
+        m.set_docs(DOCS);
     }
     Object::Interface(Rc::new(sys))
 }
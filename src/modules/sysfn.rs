@@ -12,8 +12,12 @@ fn info(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
             n => n-1
         },
         1 => match argv[0] {
-            Object::Int(value) => value as usize,
-            _ => panic!()
+            Object::Int(value) if value >= 0 &&
+                (value as usize) < frame_stack_len(env) => value as usize,
+            Object::Int(_) => return env.index_error(
+                "Index error in info(i): i is out of bounds."),
+            ref x => return env.type_error1(
+                "Type error in info(i): i is not an integer.", "i", x)
         },
         n => return env.argc_error(n,0,1,"info")
     };
@@ -47,8 +51,10 @@ fn sysfn(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
         Object::Int(value) => value as usize,
         _ => return Ok(Object::Null)
     };
-    let (f,min,max) = FN_TABLE[index];
-    Ok(Function::plain(f,min,max))
+    match FN_TABLE.get(index) {
+        Some(&(f,min,max)) => Ok(Function::plain(f,min,max)),
+        None => Ok(Object::Null)
+    }
 }
 
 pub fn load_sysfn(_rte: &Rc<RTE>) -> Object {
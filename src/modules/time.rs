@@ -42,12 +42,18 @@ fn time_sleep(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     Ok(Object::Null)
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("sleep", "sleep(x)\nHalts execution for x seconds."),
+    ("clock", "clock()\nStarts a stop watch, a function that returns\nthe elapsed time in seconds.")
+];
+
 pub fn load_time() -> Object {
     let time = new_module("time");
     {
         let mut m = time.map.borrow_mut();
         m.insert_fn_plain("sleep", time_sleep, 1, 1);
         m.insert_fn_plain("clock", time_clock, 0, 0);
+        m.set_docs(DOCS);
     }
     Object::Interface(Rc::new(time))
 }
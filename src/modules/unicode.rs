@@ -0,0 +1,363 @@
+
+// Unicode normalization, case folding, grapheme clusters and
+// display width. The data tables are generated from the UCD by
+// misc/unicode-tables.py.
+
+use std::rc::Rc;
+use std::cmp::Ordering;
+
+use crate::object::{
+    Object, FnResult, CharString, List, new_module
+};
+use crate::vm::Env;
+
+#[path = "unicode/tables.rs"]
+mod tables;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone,Copy,PartialEq,Eq)]
+pub enum GraphemeBreak {
+    Other, CR, LF, Control, Extend, ZWJ, RegionalIndicator,
+    Prepend, SpacingMark, L, V, T, LV, LVT
+}
+
+const S_BASE: u32 = 0xac00;
+const L_BASE: u32 = 0x1100;
+const V_BASE: u32 = 0x1161;
+const T_BASE: u32 = 0x11a7;
+const L_COUNT: u32 = 19;
+const V_COUNT: u32 = 21;
+const T_COUNT: u32 = 28;
+const N_COUNT: u32 = V_COUNT*T_COUNT;
+const S_COUNT: u32 = L_COUNT*N_COUNT;
+
+fn lookup_range<T: Copy>(table: &[(char,char,T)], c: char) -> Option<T> {
+    table.binary_search_by(|&(a,b,_)| {
+        if b<c {Ordering::Less} else if a>c {Ordering::Greater}
+        else {Ordering::Equal}
+    }).ok().map(|i| table[i].2)
+}
+
+fn lookup_map(table: &'static [(char,&'static [char])], c: char)
+-> Option<&'static [char]>
+{
+    table.binary_search_by(|&(x,_)| x.cmp(&c)).ok().map(|i| table[i].1)
+}
+
+fn combining_class(c: char) -> u8 {
+    if c < '\u{300}' {return 0;}
+    lookup_range(tables::COMBINING_CLASS,c).unwrap_or(0)
+}
+
+fn hangul_syllable(c: char) -> Option<u32> {
+    let s = (c as u32).wrapping_sub(S_BASE);
+    if s < S_COUNT {Some(s)} else {None}
+}
+
+fn decompose(s: &[char], compat: bool) -> Vec<char> {
+    let mut acc: Vec<char> = Vec::with_capacity(s.len());
+    for &c in s {
+        if let Some(s) = hangul_syllable(c) {
+            let l = L_BASE + s/N_COUNT;
+            let v = V_BASE + (s%N_COUNT)/T_COUNT;
+            let t = T_BASE + s%T_COUNT;
+            acc.extend(char::from_u32(l));
+            acc.extend(char::from_u32(v));
+            if t != T_BASE {acc.extend(char::from_u32(t));}
+            continue;
+        }
+        let d = if compat {
+            lookup_map(tables::COMPATIBILITY,c)
+                .or_else(|| lookup_map(tables::CANONICAL,c))
+        } else {
+            lookup_map(tables::CANONICAL,c)
+        };
+        match d {
+            Some(d) => acc.extend_from_slice(d),
+            None => acc.push(c)
+        }
+    }
+    // Canonical ordering: stable sort of each run of non-starters.
+    let mut i = 0;
+    while i < acc.len() {
+        if combining_class(acc[i]) == 0 {i += 1; continue;}
+        let j = i;
+        while i < acc.len() && combining_class(acc[i]) != 0 {i += 1;}
+        acc[j..i].sort_by_key(|&c| combining_class(c));
+    }
+    acc
+}
+
+fn compose_pair(a: char, b: char) -> Option<char> {
+    let (a32, b32) = (a as u32, b as u32);
+    if (L_BASE..L_BASE+L_COUNT).contains(&a32)
+        && (V_BASE..V_BASE+V_COUNT).contains(&b32)
+    {
+        let l = a32-L_BASE;
+        let v = b32-V_BASE;
+        return char::from_u32(S_BASE + l*N_COUNT + v*T_COUNT);
+    }
+    if let Some(s) = hangul_syllable(a) {
+        if s%T_COUNT == 0 && (T_BASE+1..T_BASE+T_COUNT).contains(&b32) {
+            return char::from_u32(a32 + b32-T_BASE);
+        }
+    }
+    let table = tables::COMPOSITION;
+    table.binary_search_by(|&(x,y,_)| (x,y).cmp(&(a,b)))
+        .ok().map(|i| table[i].2)
+}
+
+fn compose(v: Vec<char>) -> Vec<char> {
+    let mut acc: Vec<char> = Vec::with_capacity(v.len());
+    let mut starter: Option<usize> = None;
+    let mut last_class: u8 = 0;
+    for c in v {
+        let class = combining_class(c);
+        if let Some(i) = starter {
+            let blocked = acc.len() > i+1
+                && (last_class == 0 || last_class >= class);
+            if !blocked {
+                if let Some(x) = compose_pair(acc[i],c) {
+                    acc[i] = x;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(acc.len());
+        }
+        last_class = class;
+        acc.push(c);
+    }
+    acc
+}
+
+fn case_fold(s: &[char]) -> Vec<char> {
+    let mut acc: Vec<char> = Vec::with_capacity(s.len());
+    for &c in s {
+        match lookup_map(tables::CASE_FOLDING,c) {
+            Some(x) => acc.extend_from_slice(x),
+            None => acc.push(c)
+        }
+    }
+    acc
+}
+
+fn grapheme_break(c: char) -> GraphemeBreak {
+    if let Some(s) = hangul_syllable(c) {
+        return if s%T_COUNT == 0 {GraphemeBreak::LV} else {GraphemeBreak::LVT};
+    }
+    lookup_range(tables::GRAPHEME_BREAK,c).unwrap_or(GraphemeBreak::Other)
+}
+
+fn extended_pictographic(c: char) -> bool {
+    tables::EXTENDED_PICTOGRAPHIC.binary_search_by(|&(a,b)| {
+        if b<c {Ordering::Less} else if a>c {Ordering::Greater}
+        else {Ordering::Equal}
+    }).is_ok()
+}
+
+// Extended grapheme cluster boundaries, UAX #29.
+fn graphemes(s: &[char]) -> Vec<&[char]> {
+    use GraphemeBreak::*;
+    let mut acc: Vec<&[char]> = Vec::new();
+    if s.is_empty() {return acc;}
+    let mut start = 0;
+    let mut p = grapheme_break(s[0]);
+    let mut pict = extended_pictographic(s[0]);
+    let mut pict_zwj = false;
+    let mut ri_odd = p == RegionalIndicator;
+    for i in 1..s.len() {
+        let c = s[i];
+        let q = grapheme_break(c);
+        let join = match (p,q) {
+            (CR,LF) => true,
+            (Control | CR | LF, _) | (_, Control | CR | LF) => false,
+            (L, L | V | LV | LVT) => true,
+            (LV | V, V | T) => true,
+            (LVT | T, T) => true,
+            (_, Extend | ZWJ | SpacingMark) => true,
+            (Prepend, _) => true,
+            (ZWJ, _) => pict_zwj && extended_pictographic(c),
+            (RegionalIndicator, RegionalIndicator) => ri_odd,
+            _ => false
+        };
+        if !join {
+            acc.push(&s[start..i]);
+            start = i;
+        }
+        pict_zwj = pict && q == ZWJ;
+        if extended_pictographic(c) {
+            pict = true;
+        } else if q != Extend {
+            pict = false;
+        }
+        ri_odd = q == RegionalIndicator && !(p == RegionalIndicator && ri_odd);
+        p = q;
+    }
+    acc.push(&s[start..]);
+    acc
+}
+
+fn char_width(c: char) -> usize {
+    if c < '\u{7f}' {
+        return if c < ' ' {0} else {1};
+    }
+    match lookup_range(tables::WIDTH,c) {
+        Some(w) => w as usize,
+        None => 1
+    }
+}
+
+// The width of a grapheme cluster is that of its widest character,
+// except for emoji presentation sequences and flags, which are wide.
+fn cluster_width(g: &[char]) -> usize {
+    if g.len() > 1 && (g.contains(&'\u{fe0f}')
+        || grapheme_break(g[0]) == GraphemeBreak::RegionalIndicator)
+    {
+        return 2;
+    }
+    g.iter().map(|&c| char_width(c)).max().unwrap_or(0)
+}
+
+fn width(s: &[char]) -> usize {
+    graphemes(s).into_iter().map(cluster_width).sum()
+}
+
+fn string_arg<'a>(env: &mut Env, argv: &'a [Object], id: &str)
+-> Result<&'a [char],FnResult>
+{
+    match argv.first() {
+        Some(Object::String(s)) => Ok(&s.data),
+        Some(s) => Err(env.type_error1(&format!(
+            "Type error in {}(s): s is not a string.", id), "s", s)),
+        None => Err(env.argc_error(0,1,1,id))
+    }
+}
+
+fn normalize(env: &mut Env, argv: &[Object], id: &str,
+    compat: bool, composed: bool
+) -> FnResult {
+    if argv.len() != 1 {
+        return env.argc_error(argv.len(),1,1,id);
+    }
+    let s = match string_arg(env,argv,id) {Ok(s) => s, Err(e) => return e};
+    let v = decompose(s,compat);
+    let v = if composed {compose(v)} else {v};
+    Ok(CharString::new_object(v))
+}
+
+fn unicode_nfc(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    normalize(env,argv,"nfc",false,true)
+}
+
+fn unicode_nfd(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    normalize(env,argv,"nfd",false,false)
+}
+
+fn unicode_nfkc(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    normalize(env,argv,"nfkc",true,true)
+}
+
+fn unicode_nfkd(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    normalize(env,argv,"nfkd",true,false)
+}
+
+fn unicode_casefold(env: &mut Env, _pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"casefold")
+    }
+    let s = match string_arg(env,argv,"casefold") {Ok(s) => s, Err(e) => return e};
+    Ok(CharString::new_object(case_fold(s)))
+}
+
+fn unicode_graphemes(env: &mut Env, _pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"graphemes")
+    }
+    let s = match string_arg(env,argv,"graphemes") {Ok(s) => s, Err(e) => return e};
+    let v: Vec<Object> = graphemes(s).into_iter()
+        .map(|g| CharString::new_object(Vec::from(g))).collect();
+    Ok(List::new_object(v))
+}
+
+fn unicode_width(env: &mut Env, _pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"width")
+    }
+    let s = match string_arg(env,argv,"width") {Ok(s) => s, Err(e) => return e};
+    Ok(Object::Int(width(s) as i32))
+}
+
+fn justify(env: &mut Env, argv: &[Object], id: &str, left: bool)
+-> FnResult
+{
+    let c = match argv.len() {
+        2 => ' ',
+        3 => match argv[2] {
+            Object::String(ref c) if c.data.len()==1 => c.data[0],
+            Object::String(_) => return env.value_error(&format!(
+                "Value error in {}(s,n,c): size(c)!=1.", id)),
+            ref c => return env.type_error1(&format!(
+                "Type error in {}(s,n,c): c is not a string.", id), "c", c)
+        },
+        n => return env.argc_error(n,2,3,id)
+    };
+    let s = match string_arg(env,argv,id) {Ok(s) => s, Err(e) => return e};
+    let n = match argv[1] {
+        Object::Int(n) => if n<0 {0} else {n as usize},
+        ref n => return env.type_error1(&format!(
+            "Type error in {}(s,n): n is not an integer.", id), "n", n)
+    };
+    let fill = n.saturating_sub(width(s));
+    let mut acc: Vec<char> = Vec::with_capacity(s.len()+fill);
+    if left {acc.extend_from_slice(s);}
+    acc.resize(acc.len()+fill, c);
+    if !left {acc.extend_from_slice(s);}
+    Ok(CharString::new_object(acc))
+}
+
+fn unicode_ljust(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    justify(env,argv,"ljust",true)
+}
+
+fn unicode_rjust(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    justify(env,argv,"rjust",false)
+}
+
+const DOCS: &[(&str, &str)] = &[
+    ("nfc", "nfc(s)\nThe normalization form NFC of s."),
+    ("nfd", "nfd(s)\nThe normalization form NFD of s."),
+    ("nfkc", "nfkc(s)\nThe normalization form NFKC of s."),
+    ("nfkd", "nfkd(s)\nThe normalization form NFKD of s."),
+    ("casefold", "casefold(s)\nFull case folding, for case-insensitive comparison."),
+    ("graphemes", "graphemes(s)\nThe list of the extended grapheme clusters of s."),
+    ("width", "width(s)\nThe display width of s in a terminal."),
+    ("ljust", "ljust(s, n, c=\" \")\nLike s.ljust(n, c), but pads to the display width n."),
+    ("rjust", "rjust(s, n, c=\" \")\nLike s.rjust(n, c), but pads to the display width n.")
+];
+
+pub fn load_unicode() -> Object {
+    let unicode = new_module("unicode");
+    {
+        let mut m = unicode.map.borrow_mut();
+        m.insert_fn_plain("nfc", unicode_nfc, 1, 1);
+        m.insert_fn_plain("nfd", unicode_nfd, 1, 1);
+        m.insert_fn_plain("nfkc", unicode_nfkc, 1, 1);
+        m.insert_fn_plain("nfkd", unicode_nfkd, 1, 1);
+        m.insert_fn_plain("casefold", unicode_casefold, 1, 1);
+        m.insert_fn_plain("graphemes", unicode_graphemes, 1, 1);
+        m.insert_fn_plain("width", unicode_width, 1, 1);
+        m.insert_fn_plain("ljust", unicode_ljust, 2, 3);
+        m.insert_fn_plain("rjust", unicode_rjust, 2, 3);
+        m.set_docs(DOCS);
+        m.insert("version", CharString::new_object_str(tables::VERSION));
+    }
+    Object::Interface(Rc::new(unicode))
+}
@@ -168,23 +168,68 @@ impl Map {
             f: EnumFunction::Plain(fp),
             argc: if argc_min==argc_max {argc_min} else {VARIADIC},
             argc_min, argc_max,
-            id: key.clone()
+            id: key.clone(),
+            doc: Object::Null
         }));
         
         self.m.insert(key,f);
     }
+
+    // Attaches documentation to functions that were just inserted.
+    // A key without such a function is a mistake in the table, thus
+    // it is reported at once instead of being ignored. In debug builds
+    // it is also checked that every native function got a doc.
+    pub fn set_docs(&mut self, docs: &[(&str, &str)]) {
+        for (key, doc) in docs {
+            match self.m.get_mut(&Object::from(*key)) {
+                Some(Object::Function(f)) => match Rc::get_mut(f) {
+                    Some(f) => {f.doc = Object::from(*doc);},
+                    None => panic!("set_docs: function '{}' is shared", key)
+                },
+                _ => panic!("set_docs: no function '{}'", key)
+            }
+        }
+        if cfg!(debug_assertions) {
+            for (key, value) in &self.m {
+                if let Object::Function(f) = value {
+                    if let (EnumFunction::Plain(_), Object::Null) = (&f.f, &f.doc) {
+                        panic!("set_docs: function '{}' has no doc", key);
+                    }
+                }
+            }
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Spot {
     pub line: usize,
     pub col: usize,
     pub module: String
 }
 
+// A frame of a traceback: the function and the position in it
+// that was executed. Builtin functions have no position, thus
+// line and col are zero and the module is empty.
+#[derive(Clone)]
+pub struct TraceFrame {
+    pub function: String,
+    pub module: String,
+    pub line: usize,
+    pub col: usize
+}
+
+#[derive(Clone)]
 pub struct Exception {
     pub value: Object,
-    pub traceback: Option<List>,
-    pub spot: Option<Spot>
+
+    // The frames, beginning with the innermost one.
+    pub traceback: Option<Vec<TraceFrame>>,
+    pub spot: Option<Spot>,
+
+    // The exception whose catch block was running
+    // when this exception was raised.
+    pub context: Option<Rc<Exception>>
 }
 
 impl Exception {
@@ -193,21 +238,21 @@ impl Exception {
         t.map.borrow_mut().insert("text", CharString::new_object_str(s));
         Box::new(Exception{
             value: Object::Interface(Rc::new(t)),
-            traceback: Some(List::new()), spot: None
+            traceback: Some(Vec::new()), spot: None, context: None
         })
     }
 
     pub fn raise(rte: &RTE, x: Object) -> Box<Exception> {
         let traceback = if let Some(t) = downcast::<Table>(&x) {
             if t.is_instance_of(&rte.exception_obj,rte) {
-                Some(List::new())
+                Some(Vec::new())
             } else {
                 None
             }
         } else {
             None
         };
-        Box::new(Exception {value: x, spot: None, traceback})
+        Box::new(Exception {value: x, spot: None, traceback, context: None})
     }
 
     pub fn set_spot(&mut self, line: usize, col: usize, module: &str) {
@@ -216,18 +261,28 @@ impl Exception {
 
     pub fn push_clm(&mut self, line: usize, col: usize, module: &str, fid: &str) {
         if let Some(ref mut a) = self.traceback {
-            let s = CharString::new_object_str(&format!(
-                "{}, {}:{}:{}", fid, module, line, col));
-            a.v.push(s);
+            a.push(TraceFrame {
+                function: fid.to_string(), module: module.to_string(), line, col
+            });
         }
     }
 
     pub fn traceback_push(&mut self, fid: &str) {
-        if let Some(ref mut a) = self.traceback {
-            let s = CharString::new_object_str(fid);
-            a.v.push(s);
+        self.push_clm(0, 0, "", fid);
+    }
+
+    // The frames from the outermost call to the
+    // innermost one, where the exception was raised.
+    pub fn frames(&self) -> Vec<TraceFrame> {
+        match self.traceback {
+            Some(ref a) => a.iter().rev().cloned().collect(),
+            None => Vec::new()
         }
     }
+
+    pub fn context(&self) -> Option<&Exception> {
+        self.context.as_deref()
+    }
 }
 
 impl fmt::Debug for Exception {
@@ -272,7 +327,11 @@ pub struct Function {
     pub argc: u32,
     pub argc_min: u32,
     pub argc_max: u32,
-    pub id: Object
+    pub id: Object,
+
+    // The documentation: the signature in the first line,
+    // followed by the docstring. Null if undocumented.
+    pub doc: Object
 }
 
 pub const VARIADIC: u32 = 0xffffffff;
@@ -285,7 +344,7 @@ impl Function {
             f: EnumFunction::Plain(fp),
             argc: if argc_min == argc_max {argc_min} else {VARIADIC},
             argc_min, argc_max,
-            id: Object::Null
+            id: Object::Null, doc: Object::Null
         }))
     }
 
@@ -295,7 +354,7 @@ impl Function {
         Object::Function(Rc::new(Function {
             f: EnumFunction::Std(f),
             argc: if argc_min == argc_max {argc_min} else {VARIADIC},
-            argc_min, argc_max, id
+            argc_min, argc_max, id, doc: Object::Null
         }))
     }
 
@@ -305,7 +364,7 @@ impl Function {
         Object::Function(Rc::new(Function {
             f: EnumFunction::Mut(RefCell::new(fp)),
             argc: if argc_min == argc_max {argc_min} else {VARIADIC},
-            argc_min, argc_max, id: Object::Null
+            argc_min, argc_max, id: Object::Null, doc: Object::Null
         }))
     }
 }
@@ -349,10 +408,10 @@ pub trait Interface {
         Ok(Object::unimplemented())
     }
     fn idiv(self: Rc<Self>, _b: &Object, env: &mut Env) -> FnResult {
-        env.std_exception("Error: a//b is not implemented for objects of this type.")
+        env.type_error("Error: a//b is not implemented for objects of this type.")
     }
     fn ridiv(self: Rc<Self>, _a: &Object, env: &mut Env) -> FnResult {
-        env.std_exception("Error: a//b is not implemented for objects of this type.")
+        env.type_error("Error: a//b is not implemented for objects of this type.")
     }
     fn imod(self: Rc<Self>, _b: &Object, _env: &mut Env) -> FnResult {
         Ok(Object::unimplemented())
@@ -361,10 +420,10 @@ pub trait Interface {
         Ok(Object::unimplemented())
     }
     fn pow(self: Rc<Self>, _b: &Object, env: &mut Env) -> FnResult {
-        env.std_exception("Error: a^b is not implemented for objects of this type.")
+        env.type_error("Error: a^b is not implemented for objects of this type.")
     }
     fn rpow(self: Rc<Self>, _b: &Object, env: &mut Env) -> FnResult {
-        env.std_exception("Error: a^b is not implemented for objects of this type.")
+        env.type_error("Error: a^b is not implemented for objects of this type.")
     }
 
     fn eq_plain(&self, _b: &Object) -> bool {
@@ -402,24 +461,24 @@ pub trait Interface {
     }
 
     fn abs(self: Rc<Self>, env: &mut Env) -> FnResult {
-        env.std_exception("Error: abs(x) is not implemented for objects of this type.")
+        env.type_error("Error: abs(x) is not implemented for objects of this type.")
     }
     fn sgn(self: Rc<Self>, env: &mut Env) -> FnResult {
-        env.std_exception("Error: sgn(x) is not implemented for objects of this type.")
+        env.type_error("Error: sgn(x) is not implemented for objects of this type.")
     }
     fn get(self: Rc<Self>, key: &Object, env: &mut Env) -> FnResult {
-        env.std_exception(&format!(
+        env.type_error(&format!(
             "Type error in t.{}: getter is not implemented for objects of this type.", key))
     }
     fn set(self: Rc<Self>, env: &mut Env, key: Object, _value: Object) -> FnResult {
-        env.std_exception(&format!(
+        env.type_error(&format!(
             "Type error in t.{} = value: setter is not implemented for objects of this type.", key))
     }
     fn index(self: Rc<Self>, _indices: &[Object], env: &mut Env) -> FnResult {
-        env.std_exception("Type error in a[i]: indexing is not implemented for objects of this type.")
+        env.type_error("Type error in a[i]: indexing is not implemented for objects of this type.")
     }
     fn set_index(&self, _indices: &[Object], _value: &Object, env: &mut Env) -> FnResult {
-        env.std_exception("Type error in a[i]=value: indexing is not implemented for objects of this type.")
+        env.type_error("Type error in a[i]=value: indexing is not implemented for objects of this type.")
     }
     fn type_name(&self, _env: &mut Env) -> String {
         "Interface object".to_string()
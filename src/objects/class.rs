@@ -3,12 +3,14 @@ use std::any::Any;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::mem::replace;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 use crate::object::{
     Object, List, Map, Interface, downcast, ptr_eq_plain,
     FnResult, Exception
 };
-use crate::vm::{Env, RTE, secondary_env, object_to_string};
+use crate::vm::{Env, RTE, secondary_env, object_to_string, op_eq};
 
 type PGet = Box<dyn Fn(&mut Env, Rc<Table>, &Object) -> FnResult>;
 type PSet = Box<dyn Fn(&mut Env, Rc<Table>, Object, Object) -> FnResult>;
@@ -43,7 +45,10 @@ pub struct Class {
     pub to_string: PToString,
     pub name: String,
     pub map: Rc<RefCell<Map>>,
-    pub parent: Object
+    pub parent: Object,
+    pub mro: Vec<Object>,
+    pub abstract_methods: Vec<Object>,
+    pub doc: Object
 }
 
 impl Class {
@@ -55,7 +60,10 @@ impl Class {
             to_string: Box::new(standard_to_string),
             name: String::from(name),
             map: Map::new(),
-            parent: parent.clone()
+            parent: parent.clone(),
+            mro: linearize(parent).unwrap_or_default(),
+            abstract_methods: Vec::new(),
+            doc: Object::Null
         })
     }
     pub fn slot(&self, key: &Object) -> Option<Object> {
@@ -102,69 +110,127 @@ impl Interface for Class {
     }
 }
 
-fn get_from_ancestor(mut p: &Object, key: &Object) -> Option<Object> {
-    loop {
-        if let Some(class) = downcast::<Class>(p) {
-            if let Some(y) = class.map.borrow().m.get(key) {
+fn same(x: &Object, y: &Object) -> bool {
+    match (x, y) {
+        (Object::Interface(x), Object::Interface(y)) => ptr_eq_plain(x, y),
+        _ => false
+    }
+}
+
+// The slot table of a class or of a prototype table.
+fn own_map(x: &Object) -> Option<Rc<RefCell<Map>>> {
+    match downcast::<Class>(x) {
+        Some(class) => Some(class.map.clone()),
+        None => downcast::<Table>(x).map(|t| t.map.clone())
+    }
+}
+
+fn mro_get(mro: &[Object], key: &Object) -> Option<Object> {
+    for x in mro {
+        if let Some(map) = own_map(x) {
+            if let Some(y) = map.borrow().m.get(key) {
                 return Some(y.clone());
-            } else {
-                p = &class.parent;
-                if let Object::Null = p {return None;}
             }
-        } else if let Object::List(a) = p {
-            for x in &a.borrow().v {
-                if let Some(value) = get_from_ancestor(x,key) {
-                    return Some(value);
-                }
-            }
-            return None;
-        } else if let Some(t) = downcast::<Table>(p) {
-            if let Some(y) = t.map.borrow().m.get(key) {
-                return Some(y.clone());
-            } else {
-                p = &t.prototype;
-                if let Object::Null = p {return None;}
+        }
+    }
+    None
+}
+
+// The method resolution order of x, starting with x itself.
+fn linearization(x: &Object) -> Vec<Object> {
+    let mut v = vec![x.clone()];
+    if let Some(class) = downcast::<Class>(x) {
+        v.extend(class.mro.iter().cloned());
+    } else if let Some(t) = downcast::<Table>(x) {
+        v.extend(linearize(&t.prototype).unwrap_or_default());
+    }
+    v
+}
+
+// C3 linearization of the parents of a class. The parent may be
+// null, a single class or a list of classes. Returns None if the
+// bases do not admit a consistent order.
+fn linearize(parent: &Object) -> Option<Vec<Object>> {
+    let bases: Vec<Object> = match parent {
+        Object::Null => return Some(Vec::new()),
+        Object::List(a) => a.borrow().v.clone(),
+        x => vec![x.clone()]
+    };
+    let mut seqs: Vec<Vec<Object>> = bases.iter().map(linearization).collect();
+    seqs.push(bases);
+    let mut mro = Vec::new();
+    loop {
+        seqs.retain(|s| !s.is_empty());
+        if seqs.is_empty() {return Some(mro);}
+        let head = seqs.iter().map(|s| &s[0]).find(|&h| {
+            !seqs.iter().any(|s| s[1..].iter().any(|x| same(x, h)))
+        })?.clone();
+        for s in &mut seqs {
+            if same(&s[0], &head) {s.remove(0);}
+        }
+        mro.push(head);
+    }
+}
+
+fn get_from_ancestor(p: &Object, key: &Object) -> Option<Object> {
+    if let Some(class) = downcast::<Class>(p) {
+        match class.slot(key) {
+            Some(y) => Some(y),
+            None => mro_get(&class.mro, key)
+        }
+    } else if let Object::List(a) = p {
+        for x in &a.borrow().v {
+            if let Some(value) = get_from_ancestor(x,key) {
+                return Some(value);
             }
-        } else {
-            return None;
         }
+        None
+    } else if let Some(t) = downcast::<Table>(p) {
+        if let Some(y) = t.map.borrow().m.get(key) {
+            return Some(y.clone());
+        }
+        get_from_ancestor(&t.prototype, key)
+    } else {
+        None
+    }
+}
+
+// The slot tables searched for a property of x, in lookup order.
+pub fn slot_maps(x: &Object) -> Vec<Rc<RefCell<Map>>> {
+    let mro = match downcast::<Table>(x) {
+        Some(t) => {
+            let mut v = vec![t.map.clone()];
+            v.extend(linearization(&t.prototype).iter().filter_map(own_map));
+            return v;
+        },
+        None => linearization(x)
+    };
+    mro.iter().filter_map(own_map).collect()
+}
+
+pub fn class_mro(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    if downcast::<Class>(&argv[0]).is_none() {
+        return env.type_error1(
+            "Type error in mro(C): C is not a class.", "C", &argv[0]);
     }
+    Ok(List::new_object(linearization(&argv[0])))
 }
 
 fn standard_getter(env: &mut Env, t: Rc<Table>, key: &Object)
 -> FnResult
 {
     if let Some(y) = t.map.borrow().m.get(key) {
-        Ok(y.clone())
-    } else {
-        let mut p = &t.prototype;
-        loop {
-            if let Some(class) = downcast::<Class>(p) {
-                if let Some(y) = class.map.borrow().m.get(key) {
-                    return Ok(y.clone());
-                } else {
-                    p = &class.parent;
-                    if let Object::Null = p {break;}
-                }
-            } else if let Object::List(a) = p {
-                for x in &a.borrow().v {
-                    if let Some(value) = get_from_ancestor(x,key) {
-                        return Ok(value);
-                    }
-                }
-                break;
-            } else if let Some(t) = downcast::<Table>(p) {
-                if let Some(y) = t.map.borrow().m.get(key) {
-                    return Ok(y.clone());
-                } else {
-                    p = &t.prototype;
-                    if let Object::Null = p {break;}
-                }
+        return Ok(y.clone());
+    }
+    match get_from_ancestor(&t.prototype, key) {
+        Some(y) => {
+            if let Some(p) = downcast::<Property>(&y) {
+                env.call(&p.get, &Object::Interface(t), &[])
             } else {
-                break;
+                Ok(y)
             }
-        }
-        env.index_error(&format!("Index error: slot '{}' not found.", key))
+        },
+        None => env.index_error(&format!("Index error: slot '{}' not found.", key))
     }
 }
 
@@ -174,9 +240,20 @@ fn custom_getter(f: Object) -> PGet {
     })
 }
 
-fn standard_setter(_env: &mut Env, t: Rc<Table>, key: Object, value: Object)
+fn standard_setter(env: &mut Env, t: Rc<Table>, key: Object, value: Object)
 -> FnResult
 {
+    if !t.map.borrow().m.contains_key(&key) {
+        if let Some(y) = get_from_ancestor(&t.prototype, &key) {
+            if let Some(p) = downcast::<Property>(&y) {
+                return match p.set {
+                    Object::Null => env.type_error(&format!(
+                        "Type error in t.{} = value: property is read-only.", key)),
+                    ref f => env.call(f, &Object::Interface(t), &[value])
+                };
+            }
+        }
+    }
     t.map.borrow_mut().m.insert(key, value);
     Ok(Object::Null)
 }
@@ -223,7 +300,7 @@ fn custom_to_string(f: Object) -> PToString {
 
 pub fn class_new(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
-        3 => {}, n => return env.argc_error(n,3,3,"class")
+        3 | 4 => {}, n => return env.argc_error(n,3,4,"class")
     }
     let mut drop = None;
     let name: String = argv[0].to_string();
@@ -250,15 +327,439 @@ pub fn class_new(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
                 Some(f) => custom_to_string(f.clone()),
                 None => Box::new(standard_to_string)
             };
+            let mro = match linearize(&parent) {
+                Some(mro) => mro,
+                None => return env.type_error(&format!(
+                    "Type error in class {}: inconsistent order of parents.", name))
+            };
+            let mut abstract_methods = Vec::new();
+            collect_abstract(&map.borrow(), &mut abstract_methods);
+            for x in &mro {
+                if let Some(map) = own_map(x) {
+                    collect_abstract(&map.borrow(), &mut abstract_methods);
+                }
+            }
+            abstract_methods.retain(|key| {
+                let value = match m.get(key) {
+                    Some(value) => Some(value.clone()),
+                    None => mro_get(&mro, key)
+                };
+                matches!(value, Some(ref x) if downcast::<Abstract>(x).is_some())
+            });
+            let doc = argv.get(3).cloned().unwrap_or(Object::Null);
             Ok(Object::Interface(Rc::new(Class {
                 drop, pget, pset, name, to_string,
-                map: map.clone(), parent
+                map: map.clone(), parent, mro, abstract_methods, doc
             })))
         },
-        _ => panic!()
+        ref x => env.type_error1(&format!(
+            "Type error in class {}: expected a map of slots.", name),
+            "slots", x)
     }
 }
 
+// Gathers the keys of the methods marked abstract in a slot table.
+fn collect_abstract(map: &Map, acc: &mut Vec<Object>) {
+    for (key, value) in &map.m {
+        if downcast::<Abstract>(value).is_some() && !acc.contains(key) {
+            acc.push(key.clone());
+        }
+    }
+}
+
+// Called on table construction. A class having abstract methods
+// can only be instantiated if the object itself provides them.
+// The name of the class and the first of its abstract methods
+// that is implemented neither by the new instance nor by a class.
+pub fn unimplemented_abstract(prototype: &Object, map: &Map)
+-> Option<(String,Object)>
+{
+    let class = downcast::<Class>(prototype)?;
+    for key in &class.abstract_methods {
+        if map.m.contains_key(key) {continue;}
+        if let Some(ref x) = get_from_ancestor(prototype, key) {
+            if downcast::<Abstract>(x).is_none() {continue;}
+        }
+        return Some((class.name.to_string(), key.clone()));
+    }
+    None
+}
+
+pub fn check_abstract(env: &mut Env, prototype: &Object, map: &Map)
+-> Result<(),Box<Exception>>
+{
+    match unimplemented_abstract(prototype, map) {
+        Some((name,key)) => Err(env.type_error_plain(&format!(
+            "Type error in table {}{{...}}: abstract method '{}' is not implemented.",
+            name, key))),
+        None => Ok(())
+    }
+}
+
+// Marks a method as abstract, see check_abstract.
+pub struct Abstract;
+
+impl Interface for Abstract {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Abstract".to_string()
+    }
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok("abstract".to_string())
+    }
+    fn call(&self, env: &mut Env, _pself: &Object, _argv: &[Object]) -> FnResult {
+        env.type_error("Error: call of an abstract method.")
+    }
+}
+
+// A computed attribute, stored in a class. Reading the attribute
+// calls get, writing calls set, both with the object as self.
+pub struct Property {
+    pub get: Object,
+    pub set: Object
+}
+
+impl Interface for Property {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Property".to_string()
+    }
+    fn get(self: Rc<Self>, key: &Object, env: &mut Env) -> FnResult {
+        if let Object::String(ref s) = *key {
+            match &*s.to_string() {
+                "get" => return Ok(self.get.clone()),
+                "set" => return Ok(self.set.clone()),
+                _ => {}
+            }
+        }
+        env.index_error(&format!("Index error in property.{0}: '{0}' not found.", key))
+    }
+}
+
+pub fn property_new(_env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    let set = if argv.len() == 2 {argv[1].clone()} else {Object::Null};
+    Ok(Object::Interface(Rc::new(Property {get: argv[0].clone(), set})))
+}
+
+// A method together with the object it shall be called on.
+struct BoundMethod {
+    f: Object,
+    obj: Object
+}
+
+impl Interface for BoundMethod {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Function".to_string()
+    }
+    fn call(&self, env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+        env.call(&self.f, &self.obj, argv)
+    }
+}
+
+// The view of obj as seen from the parents of class.
+struct Super {
+    class: Object,
+    obj: Object
+}
+
+impl Interface for Super {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Super".to_string()
+    }
+    fn get(self: Rc<Self>, key: &Object, env: &mut Env) -> FnResult {
+        let class = match downcast::<Class>(&self.class) {
+            Some(class) => class, None => unreachable!()
+        };
+        // Continue the lookup behind the class in the order of the
+        // class of the object, so that mixins can cooperate.
+        let prototype = match downcast::<Table>(&self.obj) {
+            Some(t) => t.prototype.clone(),
+            None => Object::Null
+        };
+        let mro = linearization(&prototype);
+        let value = match mro.iter().position(|x| same(x, &self.class)) {
+            Some(i) => mro_get(&mro[i+1..], key),
+            None => get_from_ancestor(&class.parent, key)
+        };
+        match value {
+            Some(x) => {
+                if let Some(p) = downcast::<Property>(&x) {
+                    env.call(&p.get, &self.obj, &[])
+                } else if let Object::Function(_) = x {
+                    Ok(Object::Interface(Rc::new(BoundMethod {f: x, obj: self.obj.clone()})))
+                } else {
+                    Ok(x)
+                }
+            },
+            None => env.index_error(&format!(
+                "Index error in super({}, self).{}: not found in parents.",
+                class.name, key))
+        }
+    }
+}
+
+pub fn super_new(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        2 => {}, n => return env.argc_error(n,2,2,"super")
+    }
+    if downcast::<Class>(&argv[0]).is_none() {
+        return env.type_error1(
+            "Type error in super(C,self): C is not a class.", "C", &argv[0]);
+    }
+    Ok(Object::Interface(Rc::new(Super {
+        class: argv[0].clone(), obj: argv[1].clone()
+    })))
+}
+
+// The constructor of a variant of an enum. Its parent
+// is the enum type, a class that contains all variants.
+// A record type is a variant without parent.
+pub struct VariantType {
+    pub name: String,
+    pub fields: Vec<Object>,
+    pub parent: Object,
+    pub map: Rc<RefCell<Map>>
+}
+
+impl Interface for VariantType {
+    fn as_any(&self) -> &dyn Any {self}
+    fn get_type(&self, env: &mut Env) -> FnResult {
+        Ok(Object::Interface(env.rte().type_type.clone()))
+    }
+    fn is_instance_of(&self, type_obj: &Object, rte: &RTE) -> bool {
+        if let Object::Interface(t) = type_obj {
+            return ptr_eq_plain(t, &rte.type_type);
+        }
+        false
+    }
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Variant".to_string()
+    }
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok(self.name.clone())
+    }
+    fn get(self: Rc<Self>, key: &Object, _env: &mut Env) -> FnResult {
+        Ok(if let Some(value) = self.map.borrow().m.get(key) {
+            value.clone()
+        } else {
+            Object::Null
+        })
+    }
+    fn set(self: Rc<Self>, _env: &mut Env, key: Object, value: Object) -> FnResult {
+        self.map.borrow_mut().m.insert(key, value);
+        Ok(Object::Null)
+    }
+    fn call(&self, env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+        let n = self.fields.len();
+        if argv.len() != n {
+            return env.argc_error(argv.len(), n as u32, n as u32, &self.name);
+        }
+        Ok(Object::Interface(Rc::new(Variant {
+            constructor: pself.clone(),
+            v: argv.to_vec()
+        })))
+    }
+}
+
+pub struct Variant {
+    pub constructor: Object,
+    pub v: Vec<Object>
+}
+
+impl Variant {
+    fn variant_type(&self) -> &VariantType {
+        match downcast::<VariantType>(&self.constructor) {
+            Some(t) => t,
+            None => unreachable!()
+        }
+    }
+    fn same_variant(&self, b: &Variant) -> bool {
+        match (&self.constructor, &b.constructor) {
+            (Object::Interface(x), Object::Interface(y)) => ptr_eq_plain(x,y),
+            _ => false
+        }
+    }
+}
+
+impl Interface for Variant {
+    fn as_any(&self) -> &dyn Any {self}
+    fn get_type(&self, _env: &mut Env) -> FnResult {
+        Ok(match self.variant_type().parent {
+            Object::Null => self.constructor.clone(),
+            ref parent => parent.clone()
+        })
+    }
+    fn is_instance_of(&self, type_obj: &Object, _rte: &RTE) -> bool {
+        if let Object::Interface(t) = type_obj {
+            if let Object::Interface(c) = &self.constructor {
+                if ptr_eq_plain(t,c) {return true;}
+            }
+            if let Object::Interface(p) = &self.variant_type().parent {
+                return ptr_eq_plain(t,p);
+            }
+        }
+        false
+    }
+    fn type_name(&self, _env: &mut Env) -> String {
+        let t = self.variant_type();
+        match downcast::<Class>(&t.parent) {
+            Some(class) => class.name.clone(),
+            None => t.name.clone()
+        }
+    }
+    fn to_string(self: Rc<Self>, env: &mut Env) -> Result<String,Box<Exception>> {
+        let mut acc = self.variant_type().name.clone();
+        if !self.v.is_empty() {
+            acc.push('(');
+            for (i,x) in self.v.iter().enumerate() {
+                if i != 0 {acc.push_str(", ");}
+                acc.push_str(&x.repr(env)?);
+            }
+            acc.push(')');
+        }
+        Ok(acc)
+    }
+    fn get(self: Rc<Self>, key: &Object, env: &mut Env) -> FnResult {
+        let t = self.variant_type();
+        if let Some(i) = t.fields.iter().position(|x| x == key) {
+            return Ok(self.v[i].clone());
+        }
+        if let Some(y) = t.map.borrow().m.get(key) {
+            return Ok(y.clone());
+        }
+        if let Some(class) = downcast::<Class>(&t.parent) {
+            if let Some(y) = class.slot(key) {
+                return Ok(y);
+            }
+        }
+        env.index_error(&format!("Index error: slot '{}' not found.", key))
+    }
+    fn index(self: Rc<Self>, indices: &[Object], env: &mut Env) -> FnResult {
+        match indices.len() {
+            1 => {}, n => return env.argc_error(n,1,1,"variant indexing")
+        }
+        let i = match indices[0] {
+            Object::Int(i) => if i >= 0 {i as usize} else {
+                let n = self.v.len() as i32;
+                if i + n < 0 {
+                    return env.index_error(
+                        "Index error in x[i]: i is out of lower bound.");
+                }
+                (i + n) as usize
+            },
+            Object::String(_) => {
+                let t = self.variant_type();
+                match t.fields.iter().position(|x| *x == indices[0]) {
+                    Some(i) => i,
+                    None => return env.index_error(&format!(
+                        "Index error in x[key]: key '{}' not found.", indices[0]))
+                }
+            },
+            ref i => return env.type_error1(
+                "Type error in x[i]: i is not an integer.",
+                "i", i)
+        };
+        if i < self.v.len() {
+            Ok(self.v[i].clone())
+        } else {
+            env.index_error("Index error in x[i]: i is out of upper bound.")
+        }
+    }
+    fn set(self: Rc<Self>, env: &mut Env, key: Object, _value: Object) -> FnResult {
+        env.type_error(&format!(
+            "Type error in x.{} = value: x is immutable.", key))
+    }
+    fn eq_plain(&self, b: &Object) -> bool {
+        if let Some(b) = downcast::<Variant>(b) {
+            self.same_variant(b) && self.v == b.v
+        } else {
+            false
+        }
+    }
+    fn eq(self: Rc<Self>, b: &Object, env: &mut Env) -> FnResult {
+        if let Some(b) = downcast::<Variant>(b) {
+            if !self.same_variant(b) {
+                return Ok(Object::Bool(false));
+            }
+            for i in 0..self.v.len() {
+                let y = op_eq(env,&self.v[i],&b.v[i])?;
+                if let Object::Bool(y) = y {
+                    if !y {return Ok(Object::Bool(false));}
+                } else {
+                    return env.type_error(
+                        "Type error in x==y: x[i]==y[i] is not a boolean.");
+                }
+            }
+            Ok(Object::Bool(true))
+        } else {
+            Ok(Object::Bool(false))
+        }
+    }
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        if let Object::Interface(ref c) = self.constructor {
+            (&**c as *const _ as *const u8 as usize).hash(&mut hasher);
+        }
+        self.v.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+// enum(name, [[variant, field1, field2, ...], ...])
+pub fn enum_new(env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+    match argv.len() {
+        2 => {}, n => return env.argc_error(n,2,2,"enum")
+    }
+    let name = argv[0].to_string();
+    let class = Class::new(&name, &Object::Null);
+    let parent = Object::Interface(class.clone());
+    let variants = match argv[1] {
+        Object::List(ref a) => a.borrow().v.clone(),
+        ref x => return env.type_error1(
+            "Type error in enum(name,variants): variants is not a list.",
+            "variants", x)
+    };
+    for x in &variants {
+        let v = match *x {
+            Object::List(ref a) if !a.borrow().v.is_empty() => a.borrow().v.clone(),
+            ref x => return env.type_error1(
+                "Type error in enum(name,variants): expected a list [name, fields...].",
+                "x", x)
+        };
+        let constructor = Object::Interface(Rc::new(VariantType {
+            name: v[0].to_string(),
+            fields: v[1..].to_vec(),
+            parent: parent.clone(),
+            map: Map::new()
+        }));
+        // A variant without fields is a constant.
+        let value = if v.len() == 1 {
+            Object::Interface(Rc::new(Variant {constructor, v: Vec::new()}))
+        } else {
+            constructor
+        };
+        class.map.borrow_mut().m.insert(v[0].clone(), value);
+    }
+    Ok(parent)
+}
+
+// record_type(name, [field1, field2, ...])
+pub fn record_new(env: &mut Env, name: &Object, fields: &Object) -> FnResult {
+    let fields = match *fields {
+        Object::List(ref a) => a.borrow().v.clone(),
+        ref x => return env.type_error1(
+            "Type error in record_type(name,fields): fields is not a list.",
+            "fields", x)
+    };
+    Ok(Object::Interface(Rc::new(VariantType {
+        name: name.to_string(),
+        fields, parent: Object::Null,
+        map: Map::new()
+    })))
+}
+
 pub struct Table {
     pub prototype: Object,
     pub map: Rc<RefCell<Map>>
@@ -300,7 +801,7 @@ impl Interface for Table {
                 if let Object::Interface(p) = pobj {
                     if ptr_eq_plain(p,t) {return true;}
                     if let Some(pclass) = p.as_any().downcast_ref::<Class>() {
-                        pobj = &pclass.parent;
+                        return pclass.mro.iter().any(|x| same(x, type_obj));
                     } else if let Some(pt) = p.as_any().downcast_ref::<Table>() {
                         pobj = &pt.prototype;
                     } else {
@@ -477,7 +978,7 @@ impl Interface for Table {
         if let Some(f) = self.slot(&env.rte().key_abs) {
             env.call(&f, &Object::Interface(self), &[])
         } else {
-            env.std_exception(
+            env.type_error(
                 "Error: abs(x) is not implemented for objects of this type.")
         }
     }
@@ -548,17 +1049,7 @@ fn class_get(c: &Class, key: &Object) -> Option<Object> {
     if let Some(value) = c.map.borrow().m.get(key) {
         Some(value.clone())
     } else {
-        object_get(&c.parent, key)
-    }
-}
-
-pub fn object_get(x: &Object, key: &Object) -> Option<Object> {
-    if let Some(pt) = downcast::<Table>(x) {
-        table_get(pt,key)
-    } else if let Object::List(ref a) = *x {
-        list_get(a,key)
-    } else {
-        None
+        mro_get(&c.mro, key)
     }
 }
 
@@ -41,7 +41,7 @@ fn argc(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
                 a: min, b: max, step: Object::Null
             })))
         } else if f.argc > i32::MAX as u32 {
-            env.value_error("Value error f.argc(): the count is too large to be represented as i32.")
+            env.overflow_error("Value error f.argc(): the count is too large to be represented as i32.")
         } else {
             Ok(Object::Int(f.argc as i32))
         }
@@ -67,8 +67,14 @@ pub fn iterate(env: &mut Env, f: &Object, n: &Object) -> FnResult {
     }
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("orbit", "f.orbit(x)\nAn iterator over x, f(x), f(f(x)), ..."),
+    ("argc", "f.argc()\nThe argument count of f, a range if f is variadic.")
+];
+
 pub fn init(t: &Class){
     let mut m = t.map.borrow_mut();
     m.insert_fn_plain("orbit", orbit, 1, 1);
     m.insert_fn_plain("argc", argc, 0, 0);
+    m.set_docs(DOCS);
 }
@@ -17,7 +17,7 @@ pub fn new_iterator(f: MutableFn) -> Object {
     Object::Function(Rc::new(Function {
         f: EnumFunction::Mut(RefCell::new(f)),
         argc: 0, argc_min: 0, argc_max: 0,
-        id: Object::Null
+        id: Object::Null, doc: Object::Null
     }))
 }
 
@@ -357,7 +357,7 @@ fn count_all(env: &mut Env, a: &Object) -> FnResult {
     }
 }
 
-fn count(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
+pub fn count(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     match argv.len() {
         0 => return count_all(env,pself),
         1 => {},
@@ -928,6 +928,28 @@ fn iterable_max(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     Ok(maximum)
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("list", "a.list(n=null)\nThe elements of a as a list, at most n of them."),
+    ("each", "a.each(f)\nCalls f(x) for every element x of a."),
+    ("any", "a.any(p)\nTrue if p(x) is true for at least one element x."),
+    ("all", "a.all(p)\nTrue if p(x) is true for all elements x."),
+    ("count", "a.count(p=null)\nThe number of elements x for which p(x) is true,\nor of all elements."),
+    ("reduce", "a.reduce(e=null, f)\nReduces a from left to right by the binary\nfunction f, starting with e if given."),
+    ("sum", "a.sum(f=null)\nThe sum of f(x), or of x, for x in a."),
+    ("prod", "a.prod(f=null)\nThe product of f(x), or of x, for x in a."),
+    ("sort", "a.sort(p=null, cmp=null)\nSorts a by the projection p, or by the comparison\ncmp. A list is sorted in place."),
+    ("map", "a.map(f)\nAn iterator over f(x) for x in a."),
+    ("filter", "a.filter(p)\nAn iterator over the elements x of a for which\np(x) is true."),
+    ("chunks", "a.chunks(n)\nAn iterator over lists of n consecutive elements."),
+    ("skip", "a.skip(n)\nLeaves out the next n elements of a."),
+    ("until", "a.until(p)\nAn iterator that takes elements from a as long\nas p(x) is false."),
+    ("enum", "a.enum(start=0)\nAn iterator over the pairs [i, x] of indices and\nelements."),
+    ("take", "a.take(n)\nAn iterator over at most n elements of a."),
+    ("join", "a.join(sep=\"\", left=\"\", right=\"\")\nJoins the string representations of the elements,\nseparated by sep and surrounded by left and right."),
+    ("min", "a.min(p=null)\nThe minimum of a, with respect to the projection p."),
+    ("max", "a.max(p=null)\nThe maximum of a, with respect to the projection p.")
+];
+
 pub fn init(t: &Class){
     let mut m = t.map.borrow_mut();
     m.insert_fn_plain("list", to_list, 0, 1);
@@ -949,5 +971,6 @@ pub fn init(t: &Class){
     m.insert_fn_plain("join", iterable_join, 0, 1);
     m.insert_fn_plain("min", iterable_min, 0, 1);
     m.insert_fn_plain("max", iterable_max, 0, 1);
+    m.set_docs(DOCS);
 }
 
@@ -24,7 +24,7 @@ fn push(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
                     }
                     Ok(Object::Null)
                 },
-                Err(_) => {env.std_exception(
+                Err(_) => {env.value_error(
                     "Memory error in a.push(x): internal buffer of a was aliased.\n\
                      Try to replace a by copy(a) at some place."
                 )}
@@ -105,7 +105,7 @@ fn pop(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
                         }
                     }
                 },
-                Err(_) => env.std_exception(
+                Err(_) => env.value_error(
                     "Memory error in a.pop(): internal buffer of a is aliased.\n\
                      Try to replace a by copy(a) at some place.")
             }
@@ -142,7 +142,7 @@ fn insert(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult{
                     Ok(Object::Null)
                 },
                 Err(_) => {
-                    env.std_exception("Memory error in a.insert(i,x): internal buffer of a is aliased.")
+                    env.value_error("Memory error in a.insert(i,x): internal buffer of a is aliased.")
                 }
             }
         },
@@ -387,7 +387,7 @@ fn clear(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult{
                     }
                     Ok(Object::Null)
                 },
-                Err(_) => env.std_exception(
+                Err(_) => env.value_error(
                     "Memory error in a.clear(x): internal buffer of a was aliased.")
             }
         },
@@ -474,7 +474,7 @@ fn list_rot(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult{
                     rotate(&mut a.v,n);
                     Ok(pself.clone())
                 },
-                Err(_) => {env.std_exception(
+                Err(_) => {env.value_error(
                     "Memory error in a.rot(n): internal buffer of a was aliased."
                 )}
             }
@@ -484,6 +484,22 @@ fn list_rot(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult{
     }
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("push", "a.push(*x)\nAppends the arguments to a."),
+    ("plus", "a.plus(*x)\nLike a.push(*x), but returns a."),
+    ("append", "a.append(*b)\nAppends all elements of the lists b to a."),
+    ("pop", "a.pop(i=null)\nRemoves the last element, or the element at\nindex i, and returns it."),
+    ("insert", "a.insert(i, x)\nInserts x at index i, shifting the elements\nfrom index i onwards to the right."),
+    ("size", "a.size()\nThe number of elements of a."),
+    ("map", "a.map(f)\nA new list of the values f(x) for x in a."),
+    ("filter", "a.filter(p)\nA new list of the elements x of a for which\np(x) is true."),
+    ("chain", "a.chain()\nA new list with the elements of a, elements\nthat are lists or ranges being unpacked."),
+    ("rev", "a.rev()\nReverses a in place and returns it."),
+    ("swap", "a.swap(i, j)\nSwaps the elements at index i and j."),
+    ("clear", "a.clear(n=0)\nRemoves all elements but the first n."),
+    ("rot", "a.rot(n)\nRotates a by n positions, to the right side\nif n>0, to the left side if n<0.")
+];
+
 pub fn init(t: &Class){
     let mut m = t.map.borrow_mut();
     m.insert_fn_plain("push", push, 0, VARIADIC);
@@ -499,5 +515,6 @@ pub fn init(t: &Class){
     m.insert_fn_plain("swap", list_swap, 2, 2);
     m.insert_fn_plain("clear", clear, 0, 1);
     m.insert_fn_plain("rot", list_rot, 1, 1);
+    m.set_docs(DOCS);
     m.insert("shuffle", Function::mutable(new_shuffle(), 0, 0));
 }
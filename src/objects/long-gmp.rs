@@ -507,14 +507,14 @@ impl Interface for Long {
     fn idiv(self: Rc<Self>, b: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(b) = *b {
             if b==0 {
-                return env.value_error("Value error in a//b: b==0.");
+                return env.zero_division_error("Value error in a//b: b==0.");
             }
             let mut y = Mpz::new();
             y.fdiv_int(&self.value,b.into());
             Ok(Object::Interface(Rc::new(Long {value: y})))
         } else if let Some(b) = downcast::<Long>(b) {
             if b.value.cmp_int(0)==0 {
-                return env.value_error("Value error in a//b: b==0.");
+                return env.zero_division_error("Value error in a//b: b==0.");
             }
             let mut y = Mpz::new();
             y.fdiv(&self.value,&b.value);
@@ -527,7 +527,7 @@ impl Interface for Long {
     fn ridiv(self: Rc<Self>, a: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(a) = *a {
             if self.value.cmp_int(0)==0 {
-                return env.value_error("Value error in a//b: b==0.");
+                return env.zero_division_error("Value error in a//b: b==0.");
             }
             let a = Mpz::from_int(a.into());
             let mut y = Mpz::new();
@@ -540,10 +540,16 @@ impl Interface for Long {
 
     fn imod(self: Rc<Self>, b: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(b) = *b {
+            if b==0 {
+                return env.zero_division_error("Value error in a%b: b==0.");
+            }
             let mut y = Mpz::new();
             y.fdiv_int_rem(&self.value,b.into());
             Ok(Object::Interface(Rc::new(Long {value: y})))
         } else if let Some(b) = downcast::<Long>(b) {
+            if b.value.cmp_int(0)==0 {
+                return env.zero_division_error("Value error in a%b: b==0.");
+            }
             let mut y = Mpz::new();
             y.fdiv_rem(&self.value,&b.value);
             Ok(Object::Interface(Rc::new(Long{value: y})))
@@ -554,6 +560,9 @@ impl Interface for Long {
 
     fn rimod(self: Rc<Self>, a: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(a) = *a {
+            if self.value.cmp_int(0)==0 {
+                return env.zero_division_error("Value error in a%b: b==0.");
+            }
             let a = Mpz::from_int(a.into());
             let mut y = Mpz::new();
             y.fdiv_rem(&a,&self.value);
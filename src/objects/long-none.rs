@@ -14,14 +14,14 @@ impl Long {
 }
 
 pub fn pow_mod(env: &mut Env, _a: &Object, _n: &Object, _m: &Object) -> FnResult {
-    env.std_exception("Error: pow(x,n,m) is unimplemented.")
+    env.type_error("Error: pow(x,n,m) is unimplemented.")
 }
 
 #[inline(never)]
 fn overflow_exception(env: &EnvPart, op: &str, x: i32, y: i32)
 -> OperatorResult
 {
-    Err(env.std_exception_plain(&format!(
+    Err(env.overflow_error_plain(&format!(
         "Integer overflow in x{}y. Note: x={}, y={}.",op,x,y
     )))
 }
@@ -212,14 +212,14 @@ impl Interface for Long {
     fn idiv(self: Rc<Self>, b: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(b) = *b {
             if b==0 {
-                return env.value_error("Value error in a//b: b==0.");
+                return env.zero_division_error("Value error in a//b: b==0.");
             }
             // Todo: ensure floor division
             let value = self.value.clone()/b;
             return Ok(Object::Interface(Rc::new(Long {value})));
         } else if let Some(b) = downcast::<Long>(b) {
             if b.value==Zero::zero() {
-                return env.value_error("Value error in a//b: b==0.");
+                return env.zero_division_error("Value error in a//b: b==0.");
             }
             // Todo: ensure floor division
             let value = self.value.clone()/b.value.clone();
@@ -232,7 +232,7 @@ impl Interface for Long {
     fn ridiv(self: Rc<Self>, a: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(a) = *a {
             if self.value==Zero::zero() {
-                return env.value_error("Value error in a//b: b==0.");
+                return env.zero_division_error("Value error in a//b: b==0.");
             }
             // Todo: ensure floor division
             let value = a/self.value.clone();
@@ -244,10 +244,16 @@ impl Interface for Long {
 
     fn imod(self: Rc<Self>, b: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(b) = *b {
+            if b==0 {
+                return env.zero_division_error("Value error in a%b: b==0.");
+            }
             // Todo: ensure floor division
             let value = self.value.clone()%b;
             return Ok(Object::Interface(Rc::new(Long{value})));
         } else if let Some(b) = downcast::<Long>(b) {
+            if b.value==Zero::zero() {
+                return env.zero_division_error("Value error in a%b: b==0.");
+            }
             // Todo: ensure floor division
             let value = self.value.clone()%b.value.clone();
             return Ok(Object::Interface(Rc::new(Long {value})));
@@ -258,6 +264,9 @@ impl Interface for Long {
 
     fn rimod(self: Rc<Self>, a: &Object, env: &mut Env) -> FnResult {
         if let Object::Int(a) = *a {
+            if self.value==Zero::zero() {
+                return env.zero_division_error("Value error in a%b: b==0.");
+            }
             // Todo: ensure floor division
             let value = a%self.value.clone();
             return Ok(Object::Interface(Rc::new(Long {value})));
@@ -143,7 +143,7 @@ fn remove(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
             }
             match m.m.remove(&argv[0]) {
                 Some(value) => Ok(value),
-                None => env.index_error("Index error in m.remove(key): key was not in m.")
+                None => env.key_error("Index error in m.remove(key): key was not in m.", &argv[0])
             }
         },
         _ => env.type_error("Type error in m.remove(key): m is not a map.")
@@ -178,6 +178,16 @@ pub fn subset(a: &Map, b: &Map) -> bool {
     a.m.len() < b.m.len() && subseteq(a,b)
 }
 
+const DOCS: &[(&str, &str)] = &[
+    ("update", "m.update(m2)\nInserts all items of m2 into m, overwriting\nitems already in m."),
+    ("extend", "m.extend(m2)\nInserts all items of m2 into m, keeping\nitems already in m."),
+    ("values", "m.values()\nAn iterator over the values of m."),
+    ("items", "m.items()\nAn iterator over the pairs [key, value] of m."),
+    ("clear", "m.clear()\nRemoves all items of m."),
+    ("remove", "m.remove(key)\nRemoves the item of key from m and returns its\nvalue. Raises a key error if key is not in m."),
+    ("add", "m.add(*keys)\nAdds the keys to m, used as a set.")
+];
+
 pub fn init(t: &Class){
     let mut m = t.map.borrow_mut();
     m.insert_fn_plain("update", update, 1, 1);
@@ -187,4 +197,5 @@ pub fn init(t: &Class){
     m.insert_fn_plain("clear", clear, 0, 0);
     m.insert_fn_plain("remove", remove, 0, 0);
     m.insert_fn_plain("add", add, 0, VARIADIC);
+    m.set_docs(DOCS);
 }
@@ -1,10 +1,11 @@
 
 use crate::object::{
-    Object, FnResult, CharString
+    Object, FnResult, CharString, List, Exception
 };
 use crate::vm::Env;
 use crate::data::Bytes;
 use crate::class::Class;
+use crate::iterable;
 
 fn isspace(c: char) -> bool {
     matches!(c, ' ' | '\t' | '\n')
@@ -403,6 +404,336 @@ fn string_encode(env: &mut Env, pself: &Object, argv: &[Object]) -> FnResult {
     }
 }
 
+fn index_bound(x: i32, n: usize) -> usize {
+    if x<0 {
+        let index = x as isize+n as isize;
+        if index<0 {0} else {index as usize}
+    } else if x as usize > n {n} else {x as usize}
+}
+
+// Optional bounds s.f(...,start,end) of the substring s[start..end-1],
+// negative values count from the end, as in slices.
+fn bounds(env: &mut Env, argv: &[Object], n: usize, id: &str)
+-> Result<(usize,usize),Box<Exception>>
+{
+    let start = match argv.first() {
+        None | Some(Object::Null) => 0,
+        Some(&Object::Int(x)) => index_bound(x,n),
+        Some(_) => return Err(env.type_error_plain(&format!(
+            "Type error in s.{}(...,start,end): start is not an integer.", id)))
+    };
+    let end = match argv.get(1) {
+        None | Some(Object::Null) => n,
+        Some(&Object::Int(x)) => index_bound(x,n),
+        Some(_) => return Err(env.type_error_plain(&format!(
+            "Type error in s.{}(...,start,end): end is not an integer.", id)))
+    };
+    Ok((start,end))
+}
+
+fn max_count(env: &mut Env, x: Option<&Object>, id: &str)
+-> Result<Option<usize>,Box<Exception>>
+{
+    match x {
+        None | Some(Object::Null) => Ok(None),
+        Some(&Object::Int(n)) => Ok(if n<0 {None} else {Some(n as usize)}),
+        Some(_) => Err(env.type_error_plain(&format!(
+            "Type error in s.{}(...,max): max is not an integer.", id)))
+    }
+}
+
+fn find(s: &[char], sub: &[char], start: usize, end: usize) -> Option<usize> {
+    if start>end || end-start < sub.len() {return None;}
+    if sub.is_empty() {return Some(start);}
+    s[start..end].windows(sub.len()).position(|w| w==sub).map(|i| i+start)
+}
+
+fn string_find(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        1..=3 => {}, n => return env.argc_error(n,1,3,"find")
+    }
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,"find",s)
+    };
+    let sub = match argv[0] {
+        Object::String(ref sub) => &sub.data,
+        ref sub => return env.type_error1(
+            "Type error in s.find(sub): sub is not a string.", "sub", sub)
+    };
+    let (start,end) = bounds(env,&argv[1..],s.len(),"find")?;
+    Ok(match find(s,sub,start,end) {
+        Some(index) => Object::Int(index as i32),
+        None => Object::Null
+    })
+}
+
+fn string_count(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    let sub = match argv.first() {
+        Some(Object::String(sub)) => &sub.data,
+        _ => return iterable::count(env,pself,argv)
+    };
+    if argv.len()>3 {
+        return env.argc_error(argv.len(),0,3,"count");
+    }
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,"count",s)
+    };
+    let (mut start,end) = bounds(env,&argv[1..],s.len(),"count")?;
+    let mut k: i32 = 0;
+    while let Some(index) = find(s,sub,start,end) {
+        k += 1;
+        if sub.is_empty() {
+            start = index+1;
+        } else {
+            start = index+sub.len();
+        }
+    }
+    Ok(Object::Int(k))
+}
+
+fn affix(env: &mut Env, pself: &Object, argv: &[Object], id: &str)
+-> FnResult
+{
+    match argv.len() {
+        1..=3 => {}, n => return env.argc_error(n,1,3,id)
+    }
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,id,s)
+    };
+    let t = match argv[0] {
+        Object::String(ref t) => &t.data,
+        ref t => return env.type_error1(&format!(
+            "Type error in s.{}(t): t is not a string.", id), "t", t)
+    };
+    let (start,end) = bounds(env,&argv[1..],s.len(),id)?;
+    if start>end {return Ok(Object::Bool(false));}
+    let s = &s[start..end];
+    Ok(Object::Bool(if id == "startswith" {
+        s.starts_with(t)
+    } else {
+        s.ends_with(t)
+    }))
+}
+
+fn startswith(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    affix(env,pself,argv,"startswith")
+}
+
+fn endswith(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    affix(env,pself,argv,"endswith")
+}
+
+fn replace(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        2 | 3 => {}, n => return env.argc_error(n,2,3,"replace")
+    }
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,"replace",s)
+    };
+    let old = match argv[0] {
+        Object::String(ref old) => &old.data,
+        ref old => return env.type_error1(
+            "Type error in s.replace(old,new): old is not a string.",
+            "old", old)
+    };
+    let new = match argv[1] {
+        Object::String(ref new) => &new.data,
+        ref new => return env.type_error1(
+            "Type error in s.replace(old,new): new is not a string.",
+            "new", new)
+    };
+    if old.is_empty() {
+        return env.value_error(
+            "Value error in s.replace(old,new): old is empty.");
+    }
+    let max = max_count(env,argv.get(2),"replace")?;
+    let mut acc: Vec<char> = Vec::with_capacity(s.len());
+    let mut i = 0;
+    let mut k = 0;
+    while !matches!(max, Some(m) if k>=m) {
+        match find(s,old,i,s.len()) {
+            Some(j) => {
+                acc.extend_from_slice(&s[i..j]);
+                acc.extend_from_slice(new);
+                i = j+old.len();
+                k += 1;
+            },
+            None => break
+        }
+    }
+    acc.extend_from_slice(&s[i..]);
+    Ok(CharString::new_object(acc))
+}
+
+fn split_space(s: &[char], max: Option<usize>) -> Vec<Object> {
+    let mut acc: Vec<Object> = Vec::new();
+    let n = s.len();
+    let mut i = 0;
+    loop {
+        while i<n && isspace(s[i]) {i += 1;}
+        if i==n {break;}
+        if max.is_some_and(|m| acc.len()==m) {
+            acc.push(CharString::new_object(Vec::from(&s[i..])));
+            break;
+        }
+        let j = i;
+        while i<n && !isspace(s[i]) {i += 1;}
+        acc.push(CharString::new_object(Vec::from(&s[j..i])));
+    }
+    acc
+}
+
+fn split_sep(s: &[char], sep: &[char], max: Option<usize>) -> Vec<Object> {
+    let mut acc: Vec<Object> = Vec::new();
+    let mut i = 0;
+    while !matches!(max, Some(m) if acc.len()>=m) {
+        match find(s,sep,i,s.len()) {
+            Some(j) => {
+                acc.push(CharString::new_object(Vec::from(&s[i..j])));
+                i = j+sep.len();
+            },
+            None => break
+        }
+    }
+    acc.push(CharString::new_object(Vec::from(&s[i..])));
+    acc
+}
+
+fn split(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        0..=2 => {}, n => return env.argc_error(n,0,2,"split")
+    }
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,"split",s)
+    };
+    let max = max_count(env,argv.get(1),"split")?;
+    let acc = match argv.first() {
+        None | Some(Object::Null) => split_space(s,max),
+        Some(Object::String(sep)) => {
+            if sep.data.is_empty() {
+                return env.value_error(
+                    "Value error in s.split(sep): sep is empty.");
+            }
+            split_sep(s,&sep.data,max)
+        },
+        Some(sep) => return env.type_error1(
+            "Type error in s.split(sep): sep is not a string.",
+            "sep", sep)
+    };
+    Ok(List::new_object(acc))
+}
+
+fn partition(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    match argv.len() {
+        1 => {}, n => return env.argc_error(n,1,1,"partition")
+    }
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,"partition",s)
+    };
+    let sep = match argv[0] {
+        Object::String(ref sep) => &sep.data,
+        ref sep => return env.type_error1(
+            "Type error in s.partition(sep): sep is not a string.",
+            "sep", sep)
+    };
+    if sep.is_empty() {
+        return env.value_error(
+            "Value error in s.partition(sep): sep is empty.");
+    }
+    let v = match find(s,sep,0,s.len()) {
+        Some(i) => vec![
+            CharString::new_object(Vec::from(&s[..i])),
+            argv[0].clone(),
+            CharString::new_object(Vec::from(&s[i+sep.len()..]))
+        ],
+        None => vec![
+            pself.clone(),
+            CharString::new_object_str(""),
+            CharString::new_object_str("")
+        ]
+    };
+    Ok(List::new_object(v))
+}
+
+fn splitlines(env: &mut Env, pself: &Object, argv: &[Object])
+-> FnResult
+{
+    let keepends = match argv.len() {
+        0 => false,
+        1 => match argv[0] {
+            Object::Bool(x) => x,
+            ref x => return env.type_error1(
+                "Type error in s.splitlines(keepends): keepends is not a boolean.",
+                "keepends", x)
+        },
+        n => return env.argc_error(n,0,1,"splitlines")
+    };
+    let s = match *pself {
+        Object::String(ref s) => &s.data,
+        ref s => return type_error0_string(env,"splitlines",s)
+    };
+    let mut acc: Vec<Object> = Vec::new();
+    let n = s.len();
+    let mut i = 0;
+    while i<n {
+        let mut j = i;
+        while j<n && s[j]!='\n' && s[j]!='\r' {j += 1;}
+        let mut k = j;
+        if k<n {
+            k += if s[k]=='\r' && k+1<n && s[k+1]=='\n' {2} else {1};
+        }
+        let line = if keepends {&s[i..k]} else {&s[i..j]};
+        acc.push(CharString::new_object(Vec::from(line)));
+        i = k;
+    }
+    Ok(List::new_object(acc))
+}
+
+const DOCS: &[(&str, &str)] = &[
+    ("isdigit", "s.isdigit(radix=10)\nTrue if s is empty or consists only of digits."),
+    ("isalpha", "s.isalpha()\nTrue if s is empty or consists only of letters."),
+    ("isalnum", "s.isalnum()\nTrue if s is empty or each character is a letter\nor a digit."),
+    ("isspace", "s.isspace()\nTrue if s is empty or consists only of whitespace."),
+    ("islower", "s.islower()\nTrue if s is empty or all characters are lower\ncase letters."),
+    ("isupper", "s.isupper()\nTrue if s is empty or all characters are upper\ncase letters."),
+    ("lower", "s.lower()\nThe string s projected to lower case."),
+    ("upper", "s.upper()\nThe string s projected to upper case."),
+    ("ljust", "s.ljust(n, c=\" \")\nPads s on the right side with c to length n."),
+    ("rjust", "s.rjust(n, c=\" \")\nPads s on the left side with c to length n."),
+    ("ltrim", "s.ltrim(chars=null)\nRemoves whitespace, or the characters of chars,\nfrom the left side."),
+    ("rtrim", "s.rtrim(chars=null)\nRemoves whitespace, or the characters of chars,\nfrom the right side."),
+    ("trim", "s.trim(chars=null)\nRemoves whitespace, or the characters of chars,\nfrom both sides."),
+    ("encode", "s.encode(spec=\"utf-8\")\nEncodes s as binary data by the encoding spec."),
+    ("find", "s.find(t, start=null, end=null)\nThe index of the first occurrence of t in\ns[start..end-1], or null."),
+    ("count", "s.count(t, start=null, end=null)\nThe number of non-overlapping occurrences of t.\nFor a predicate t, the same as Iterable.count."),
+    ("startswith", "s.startswith(t, start=null, end=null)\nTrue if s starts with t."),
+    ("endswith", "s.endswith(t, start=null, end=null)\nTrue if s ends with t."),
+    ("replace", "s.replace(old, new, max=null)\nReplaces each non-overlapping occurrence of old\nby new, but at most max of them."),
+    ("split", "s.split(sep=null, max=null)\nSplits s into parts separated by sep, or by runs\nof whitespace if sep is null."),
+    ("partition", "s.partition(sep)\nSplits s at the first occurrence of sep into\n[head, sep, tail]."),
+    ("splitlines", "s.splitlines(keepends=false)\nSplits s into lines at \"\\n\", \"\\r\\n\" and \"\\r\".")
+];
+
 pub fn init(t: &Class){
     let mut m = t.map.borrow_mut();
     m.insert_fn_plain("isdigit", string_isdigit, 0, 1);
@@ -419,4 +750,13 @@ pub fn init(t: &Class){
     m.insert_fn_plain("rtrim", string_rtrim, 0, 1);
     m.insert_fn_plain("trim", string_trim, 0, 1);
     m.insert_fn_plain("encode", string_encode, 0, 1);
+    m.insert_fn_plain("find", string_find, 1, 3);
+    m.insert_fn_plain("count", string_count, 0, 3);
+    m.insert_fn_plain("startswith", startswith, 1, 3);
+    m.insert_fn_plain("endswith", endswith, 1, 3);
+    m.insert_fn_plain("replace", replace, 2, 3);
+    m.insert_fn_plain("split", split, 0, 2);
+    m.insert_fn_plain("partition", partition, 1, 1);
+    m.insert_fn_plain("splitlines", splitlines, 0, 1);
+    m.set_docs(DOCS);
 }
@@ -5,10 +5,14 @@ use std::io::Write;
 
 use object::{Object,List};
 
+pub type Completer = Box<dyn Fn(&str) -> Vec<String>>;
+
 pub struct History{}
 
 impl History {
     pub fn new() -> Self {Self{}}
+    pub fn persistent() -> Self {Self{}}
+    pub fn set_completer(&mut self, _completer: Completer){}
     pub fn append(&mut self, _s: &str){}
 }
 
@@ -1,6 +1,7 @@
 
 use std::str;
 use std::env::var;
+use std::fs;
 use std::{io, io::Write};
 use std::os::unix::io::RawFd;
 use std::path::PathBuf;
@@ -10,6 +11,8 @@ use termios::{
 use crate::object::{Object, List};
 
 const STDIN_FILENO: i32 = 0;
+const EOT: u8 = 4;
+const BELL: u8 = 7;
 const TAB: u8 = 9;
 const NEWLINE: u8 = 10;
 const ESC: u8 = 27;
@@ -19,13 +22,17 @@ const DOWN: u8 = 66;
 const LEFT: u8 = 68;
 const RIGHT: u8 = 67;
 const BACKSPACE: u8 = 127;
+const CTRL_R: u8 = 18;
+
+// Number of lines kept in the history file.
+const HISTORY_SIZE: usize = 1000;
 
 fn get_win_size() -> (usize, usize) {
     use std::mem::zeroed;
     unsafe {
         let mut size: libc::winsize = zeroed();
         match libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) {
-            0 => (usize::from(size.ws_col), usize::from(size.ws_row)),
+            0 if size.ws_col > 0 => (usize::from(size.ws_col), usize::from(size.ws_row)),
             _ => (80, 24),
         }
     }
@@ -36,12 +43,18 @@ fn get_cols() -> usize {
     cols
 }
 
+// Receives the word in front of the cursor, returns the
+// possible completions of its last dot separated component.
+pub type Completer = Box<dyn Fn(&str) -> Vec<String>>;
+
 struct HistoryNode {
     s: String,
     next: Option<Box<HistoryNode>>
 }
 pub struct History {
-    first: Option<Box<HistoryNode>>
+    first: Option<Box<HistoryNode>>,
+    file: Option<PathBuf>,
+    completer: Option<Completer>
 }
 
 impl History {
@@ -61,22 +74,77 @@ impl History {
         None
     }
     pub fn new() -> History {
-        History {first: None}
+        History {first: None, file: None, completer: None}
     }
-    pub fn append(&mut self, s: &str){
+
+    // A history that is loaded from and saved to ~/.moss/history.
+    pub fn persistent() -> History {
+        let mut h = History::new();
+        let mut path = match var("HOME") {
+            Ok(s) => PathBuf::from(s),
+            _ => return h
+        };
+        path.push(".moss/history");
+        if let Ok(text) = fs::read_to_string(&path) {
+            let lines: Vec<&str> = text.lines().collect();
+            let start = lines.len().saturating_sub(HISTORY_SIZE);
+            for line in &lines[start..] {
+                h.push(line);
+            }
+            if start > 0 {
+                let mut tail = lines[start..].join("\n");
+                tail.push('\n');
+                fs::write(&path, tail).ok();
+            }
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).ok();
+        }
+        h.file = Some(path);
+        h
+    }
+    pub fn set_completer(&mut self, completer: Completer) {
+        self.completer = Some(completer);
+    }
+    fn push(&mut self, s: &str) -> bool {
         if let Some(ref first) = self.first {
-            if first.s == s {return;}
+            if first.s == s {return false;}
         }
         self.first = Some(Box::new(HistoryNode {
             s: String::from(s), next: self.first.take()
         }));
+        true
+    }
+    pub fn append(&mut self, s: &str){
+        if self.push(s) {
+            if let Some(ref path) = self.file {
+                let file = fs::OpenOptions::new().create(true).append(true).open(path);
+                if let Ok(mut file) = file {
+                    writeln!(file, "{}", s).ok();
+                }
+            }
+        }
+    }
+
+    // The first entry from index on that contains pattern.
+    fn find(&self, pattern: &str, index: usize) -> Option<(usize,String)> {
+        let mut p = &self.first;
+        let mut k = 1;
+        while let Some(node) = p {
+            if k >= index && node.s.contains(pattern) {
+                return Some((k, node.s.clone()));
+            }
+            p = &node.next;
+            k += 1;
+        }
+        None
     }
 }
 
 fn getchar() -> u8 {
     use std::convert::TryFrom;
     let c = unsafe{libc::getchar()};
-    u8::try_from(c).unwrap_or(b'?')
+    u8::try_from(c).unwrap_or(EOT)
 }
 
 fn flush() {
@@ -145,6 +213,110 @@ fn complete_u32_char(c: u8) -> char {
     }
 }
 
+fn redraw(prompt: &str, old: usize, a: &[char], i: usize) {
+    clear_input(old);
+    print!("{}", prompt);
+    for x in a {print!("{}", x);}
+    for _ in i..a.len() {print!("\x1b[D");}
+    flush();
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+fn common_prefix(v: &[String]) -> String {
+    let mut prefix: Vec<char> = match v.first() {
+        Some(s) => s.chars().collect(),
+        None => return String::new()
+    };
+    for s in &v[1..] {
+        let n = prefix.iter().zip(s.chars()).take_while(|(x,y)| *x == y).count();
+        prefix.truncate(n);
+    }
+    prefix.into_iter().collect()
+}
+
+// Completes the word in front of the cursor. If the completion
+// is ambiguous, the candidates are listed below the line.
+fn complete(cols: usize, prompt: &str, complete: &Completer,
+    a: &mut Vec<char>, i: &mut usize)
+{
+    let mut start = *i;
+    while start > 0 && is_word_char(a[start-1]) {start -= 1;}
+    let word: String = a[start..*i].iter().collect();
+    let last = match word.rfind('.') {Some(k) => &word[k+1..], None => &word[..]};
+    let candidates = complete(&word);
+    let prefix = common_prefix(&candidates);
+    let lines = number_of_lines(cols, prompt, a);
+    if prefix.chars().count() > last.chars().count() {
+        let rest: Vec<char> = prefix.chars().skip(last.chars().count()).collect();
+        let n = rest.len();
+        a.splice(*i..*i, rest);
+        *i += n;
+        redraw(prompt, lines, a, *i);
+    } else if candidates.len() > 1 {
+        let width = candidates.iter().map(|s| s.chars().count()).max().unwrap_or(0) + 2;
+        let per_line = (cols/width).max(1);
+        for _ in *i..a.len() {print!("\x1b[C");}
+        println!();
+        for (k, s) in candidates.iter().enumerate() {
+            print!("{:1$}", s, width);
+            if (k+1) % per_line == 0 || k+1 == candidates.len() {println!();}
+        }
+        redraw(prompt, 1, a, *i);
+    } else {
+        print_flush("\x07");
+    }
+}
+
+// Reverse incremental search, started by Ctrl-R. Returns the selected
+// line and whether it was confirmed by Enter.
+fn reverse_search(cols: usize, prompt: &str, history: &History, a: &[char])
+-> (Vec<char>, bool)
+{
+    let mut pattern = String::new();
+    let mut index = 1;
+    let mut found: Option<String> = None;
+    let mut lines = number_of_lines(cols, prompt, a);
+    loop {
+        let search_prompt = format!("(reverse-i-search)'{}': ", pattern);
+        let line: Vec<char> = match found {
+            Some(ref s) => s.chars().collect(),
+            None => Vec::new()
+        };
+        redraw(&search_prompt, lines, &line, line.len());
+        lines = number_of_lines(cols, &search_prompt, &line);
+        let c = getchar();
+        if c == NEWLINE {
+            clear_input(lines);
+            return (line, true);
+        } else if c == BELL || c == EOT {
+            clear_input(lines);
+            return (a.to_vec(), false);
+        } else if c == CTRL_R {
+            if let Some((k, s)) = history.find(&pattern, index+1) {
+                index = k; found = Some(s);
+            } else {
+                print_flush("\x07");
+            }
+        } else if c == BACKSPACE {
+            pattern.pop();
+            index = 1;
+            found = history.find(&pattern, index).map(|(k, s)| {index = k; s});
+        } else if c < 32 {
+            if c == ESC {getchar(); getchar();}
+            clear_input(lines);
+            return (line, false);
+        } else {
+            pattern.push(complete_u32_char(c));
+            match history.find(&pattern, index) {
+                Some((k, s)) => {index = k; found = Some(s);},
+                None => print_flush("\x07")
+            }
+        }
+    }
+}
 
 pub fn getline_history(prompt: &str, history: &History) -> io::Result<String> {
     let fd: RawFd = STDIN_FILENO;
@@ -169,6 +341,30 @@ pub fn getline_history(prompt: &str, history: &History) -> io::Result<String> {
         if c == NEWLINE {
             println!();
             break;
+        } else if c == EOT {
+            if n == 0 {
+                tcsetattr(fd, TCSANOW, &tio_backup)?;
+                println!();
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                    "end of input"));
+            }
+            println!();
+            break;
+        } else if c == CTRL_R {
+            let (line, accept) = reverse_search(cols, prompt, history, &a);
+            a = line; n = a.len(); i = n;
+            print_prompt_flush(prompt, &a);
+            if accept {
+                println!();
+                break;
+            }
+            continue;
+        } else if c == TAB && n > 0 && i > 0 && is_word_char(a[i-1]) {
+            if let Some(ref completer) = history.completer {
+                complete(cols, prompt, completer, &mut a, &mut i);
+                n = a.len();
+                continue;
+            }
         } else if c<32 {
             if c == ESC {
                 let c2 = getchar();
@@ -262,7 +458,7 @@ pub fn getline_history(prompt: &str, history: &History) -> io::Result<String> {
 }
 
 pub fn getline(prompt: &str) -> io::Result<String> {
-    let history = History{first: None};
+    let history = History::new();
     getline_history(prompt,&history)
 }
 
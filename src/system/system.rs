@@ -1,7 +1,8 @@
 
+use std::fs;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path,PathBuf,Component};
 
 use crate::object::Object;
 
@@ -16,7 +17,7 @@ mod system_os;
 pub use self::system_os::*;
 
 pub fn open_module_file(search_paths: &[Object], id: &str)
--> Result<(File,bool),String>
+-> Result<(File,bool,PathBuf),String>
 {
     for path_obj in search_paths {
         let mut path = match path_obj {
@@ -30,30 +31,76 @@ pub fn open_module_file(search_paths: &[Object], id: &str)
         bin_path.push(id);
         bin_path.set_extension("bin");
         if let Ok(f) = File::open(&bin_path) {
-            return Ok((f,true));
+            return Ok((f,true,bin_path));
         }
 
         path.push(id);
         path.set_extension("moss");
         if let Ok(f) = File::open(&path) {
-            return Ok((f,false));
+            return Ok((f,false,path));
         }
     }
     Err(format!("Error in load: could not open file '{}.moss'.", id))
 }
 
-pub fn read_file(id: &str) -> Result<String,String> {
+pub fn read_file(id: &str) -> Result<String,(String,Option<i32>)> {
     let mut f = match File::open(id) {
         Ok(f) => f,
-        Err(_) => return Err(format!("Error in read: could not open file '{}'.", id))
+        Err(e) => return Err((format!(
+            "Error in read: could not open file '{}'.", id), e.raw_os_error()))
     };
     let mut s = String::new();
-    if f.read_to_string(&mut s).is_err() {
-        return Err(format!("Error in read: could not read file '{}'.", id));
+    if let Err(e) = f.read_to_string(&mut s) {
+        return Err((format!(
+            "Error in read: could not read file '{}'.", id), e.raw_os_error()));
     }
     Ok(s)
 }
 
+// The absolute form of a path, with symbolic links resolved as far
+// as the path exists. The remaining components are normalized
+// lexically, thus a file that is yet to be created has a canonical
+// path too. A dangling link is resolved to the canonical path of
+// its target, which is where a write through the link would go.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    resolve_path(path, 0)
+}
+
+// Bound for chains of dangling links, as a cycle of links
+// is dangling too.
+const MAX_LINK_DEPTH: usize = 40;
+
+fn resolve_path(path: &Path, depth: usize) -> PathBuf {
+    let mut acc = if path.is_absolute() {
+        PathBuf::new()
+    } else {
+        std::env::current_dir().unwrap_or_default()
+    };
+    let mut exists = true;
+    for c in path.components() {
+        match c {
+            Component::CurDir => continue,
+            Component::ParentDir => {acc.pop();},
+            c => acc.push(c)
+        }
+        if exists {
+            match acc.canonicalize() {
+                Ok(p) => acc = p,
+                Err(_) => {
+                    exists = false;
+                    if let Ok(target) = fs::read_link(&acc) {
+                        if depth < MAX_LINK_DEPTH {
+                            acc.pop();
+                            acc = resolve_path(&acc.join(target), depth+1);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    acc
+}
+
 pub fn library_path() -> String {
     let mut path = match std::env::var("HOME") {
         Ok(s) => PathBuf::from(s),
@@ -0,0 +1,189 @@
+
+// The test runner behind 'moss -test'. A test file is a module
+// whose name starts with "test" and ends with ".moss". Its
+// functions named test_* are called in the order of definition,
+// each one after running the file in a fresh table of global
+// variables, thus a test does not see the globals changed by
+// another one. A file without such functions is a single test,
+// which passes if the module runs without an exception. A call
+// of sys.exit does not leave the runner, it ends the test, which
+// fails unless the exit status is zero. The exception raised by
+// sys.exit cannot be caught by the test itself.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::object::{Object, Map, FnResult, downcast};
+use crate::compiler::{self, outline};
+use crate::vm::{Env, Exit};
+
+struct Summary {
+    passed: usize,
+    failed: Vec<String>
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let name = match path.file_name() {Some(s) => s.to_string_lossy(), None => return false};
+    name.starts_with("test") && name.ends_with(".moss")
+}
+
+fn test_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let entries = fs::read_dir(path).map_err(|e|
+        format!("Error: could not read directory '{}': {}", path.display(), e))?;
+    let mut files: Vec<PathBuf> = entries.filter_map(|x| x.ok())
+        .map(|x| x.path()).filter(|x| x.is_file() && is_test_file(x)).collect();
+    files.sort();
+    Ok(files)
+}
+
+// The names of the test functions in the order they are defined
+// in the source.
+fn test_functions(s: &str, id: &str, gtab: &Rc<RefCell<Map>>) -> Vec<String> {
+    let mut names: Vec<String> = match outline::outline(s, id) {
+        Ok(a) => a.into_iter().map(|d| d.name).collect(),
+        Err(_) => Vec::new()
+    };
+    let mut rest: Vec<String> = gtab.borrow().m.keys().filter_map(|key| match *key {
+        Object::String(ref s) => Some(s.to_string()), _ => None
+    }).filter(|s| !names.contains(s)).collect();
+    rest.sort();
+    names.extend(rest);
+
+    let m = gtab.borrow();
+    names.into_iter().filter(|name| name.starts_with("test_")).filter(|name| {
+        matches!(m.m.get(&Object::from(name.as_str())), Some(Object::Function(_)))
+    }).collect()
+}
+
+fn report(summary: &mut Summary, name: &str, t: Duration, error: Option<String>) {
+    match error {
+        None => {
+            println!("   ok      {} ({} ms)", name, t.as_millis());
+            summary.passed += 1;
+        },
+        Some(text) => {
+            println!("   FAILED  {} ({} ms)", name, t.as_millis());
+            for line in text.lines() {
+                println!("      {}", line);
+            }
+            summary.failed.push(name.to_string());
+        }
+    }
+}
+
+// The error text of a test, or None if it has passed.
+fn outcome(env: &mut Env, result: FnResult) -> Option<String> {
+    match result {
+        Ok(_) => None,
+        Err(e) => match downcast::<Exit>(&e.value) {
+            Some(exit) if exit.status == 0 => None,
+            _ => Some(env.exception_to_string(&e))
+        }
+    }
+}
+
+fn run_file(env: &mut Env, path: &Path, filter: Option<&str>, summary: &mut Summary) {
+    let id = path.to_string_lossy().into_owned();
+    let s = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("{}", id);
+            report(summary, &id, Duration::ZERO,
+                Some(format!("Error: could not read file: {}", e)));
+            return;
+        }
+    };
+    println!("{}", id);
+    let gtab = Map::new();
+    let start = Instant::now();
+    let result = env.eval_string(&s, &id, gtab.clone(), compiler::Value::Optional);
+    let load_time = start.elapsed();
+    if result.is_err() {
+        let error = outcome(env, result);
+        report(summary, &id, load_time, error);
+        return;
+    }
+
+    let selected = |name: &str| match filter {
+        Some(p) => name.contains(p) || id.contains(p),
+        None => true
+    };
+    let tests = test_functions(&s, &id, &gtab);
+    if tests.is_empty() {
+        if selected(&id) {report(summary, &id, load_time, None);}
+        return;
+    }
+    // The first test gets the table of the run above, the
+    // following ones run the file again.
+    let mut loaded = Some(gtab);
+    for name in tests {
+        if !selected(&name) {continue;}
+        let test_id = format!("{}: {}", id, name);
+        let start = Instant::now();
+        let gtab = match loaded.take() {
+            Some(gtab) => gtab,
+            None => {
+                let gtab = Map::new();
+                let result = env.eval_string(&s, &id, gtab.clone(), compiler::Value::Optional);
+                if result.is_err() {
+                    let error = outcome(env, result);
+                    report(summary, &test_id, start.elapsed(), error);
+                    continue;
+                }
+                gtab
+            }
+        };
+        let f = match gtab.borrow().m.get(&Object::from(name.as_str())) {
+            Some(f) => f.clone(),
+            None => Object::Null
+        };
+        let start = Instant::now();
+        let result = env.call(&f, &Object::Null, &[]);
+        let t = start.elapsed();
+        let error = outcome(env, result);
+        report(summary, &test_id, t, error);
+    }
+}
+
+// Runs the tests in a file or in the test files of a directory.
+// If a filter is given, only the tests whose name or file name
+// contains it are run. Returns the exit status.
+pub fn run(env: &mut Env, path: &str, filter: Option<&str>) -> i32 {
+    let files = match test_files(Path::new(path)) {
+        Ok(files) => files,
+        Err(e) => {eprintln!("{}", e); return 2;}
+    };
+    let mut summary = Summary {passed: 0, failed: Vec::new()};
+    env.rte().trap_exit.set(true);
+    let start = Instant::now();
+    for file in &files {
+        if let Some(pattern) = filter {
+            // A file is skipped if neither its name nor the
+            // name of one of its functions matches.
+            let s = fs::read_to_string(file).unwrap_or_default();
+            if !file.to_string_lossy().contains(pattern) && !s.contains(pattern) {
+                continue;
+            }
+        }
+        run_file(env, file, filter, &mut summary);
+    }
+    env.rte().trap_exit.set(false);
+    let t = start.elapsed();
+    println!();
+    if !summary.failed.is_empty() {
+        println!("Failed:");
+        for name in &summary.failed {
+            println!("   {}", name);
+        }
+        println!();
+    }
+    println!("{} passed, {} failed ({:.2} s)",
+        summary.passed, summary.failed.len(), t.as_secs_f64());
+    if summary.failed.is_empty() {0} else {1}
+}
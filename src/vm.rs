@@ -1,5 +1,5 @@
 
-use std::rc::Rc;
+use std::rc::{Rc,Weak};
 use std::cell::{Cell, RefCell};
 use std::mem::replace;
 use std::collections::HashMap;
@@ -8,10 +8,12 @@ use std::hash::{Hash, Hasher};
 use std::fs::File;
 use std::io::Read;
 use std::fmt::Write;
+use std::any::Any;
+use std::path::{Path,PathBuf};
 
 use crate::object::{
     Object, Map, List, Function, EnumFunction, StandardFn,
-    FnResult, OperatorResult, Exception, CharString,
+    FnResult, OperatorResult, Exception, CharString, Interface,
     VARIADIC, Downcast, TypeName, downcast, ptr_eq_plain, float
 };
 use crate::{string,list,function,global,module};
@@ -25,9 +27,9 @@ use crate::rand::Rand;
 use crate::list::cartesian_power;
 use crate::iterable::iter;
 use crate::map::{subseteq,subset};
-use crate::class::{Class,Table};
+use crate::class::{Class,Table,check_abstract,slot_maps,class_mro};
 
-use crate::system::{History,getline_history,init_search_paths};
+use crate::system::{History,getline_history,init_search_paths,canonical_path};
 use crate::compiler;
 use crate::compiler::{CompilerExtra};
 
@@ -39,6 +41,7 @@ pub mod interface_index{
     pub const FILE: usize = 3;
     pub const REGEX: usize = 4;
     pub const HASH: usize = 5;
+    pub const MATCH: usize = 6;
 }
 
 // byte code size
@@ -126,6 +129,10 @@ pub mod bc {
     pub const LONG: u8 = 74;
     pub const TUPLE:u8 = 75;
     pub const APPLY:u8 = 76;
+    pub const CELL: u8 = 77;
+    pub const CELL_GET:u8 = 78;
+    pub const CELL_SET:u8 = 79;
+    pub const DOC:  u8 = 80;
 
     pub fn op_to_str(x: u8) -> &'static str {
         match x {
@@ -203,6 +210,10 @@ pub mod bc {
             TUPLE => "TUPLE",
             HALT => "HALT",
             APPLY => "APPLY",
+            CELL => "CELL",
+            CELL_GET => "CELL_GET",
+            CELL_SET => "CELL_SET",
+            DOC => "DOC",
             _ => "unknown"
         }
     }
@@ -568,6 +579,21 @@ pub fn object_to_repr(env: &mut Env, x: &Object)
     }
 }
 
+// The name of a function in a traceback.
+fn frame_name(f: &Function) -> String {
+    match (&f.f, &f.id) {
+        (EnumFunction::Plain(_), Object::Null) => "main".to_string(),
+        _ => function_id_to_string(f)
+    }
+}
+
+fn same_value(x: &Object, y: &Object) -> bool {
+    match (x, y) {
+        (Object::Interface(x), Object::Interface(y)) => Rc::ptr_eq(x, y),
+        _ => false
+    }
+}
+
 fn function_id_to_string(f: &Function) -> String {
     match f.id {
         Object::Null => "function".to_string(),
@@ -1338,7 +1364,7 @@ fn operator_idiv(env: &mut EnvPart, sp: usize, stack: &mut [Object])
             return match stack[sp-1] {
                 Object::Int(y) => {
                     if y==0 {
-                        return Err(env.value_error_plain("Value error in a//b: b==0."));
+                        return Err(env.zero_division_error_plain("Value error in a//b: b==0."));
                     }
                     if let Some(value) = checked_div_floor(x,y) {
                         stack[sp-2] = Object::Int(value);
@@ -1412,7 +1438,7 @@ fn operator_mod(env: &mut EnvPart, sp: usize, stack: &mut [Object])
             return match stack[sp-1] {
                 Object::Int(y) => {
                     if y==0 {
-                        return Err(env.value_error_plain(
+                        return Err(env.zero_division_error_plain(
                             "Value error in x%y: y==0."));
                     }
                     if let Some(value) = checked_mod_floor(x,y) {
@@ -2603,7 +2629,7 @@ fn operator_index(env: &mut EnvPart, argc: usize,
         Object::List(a) => {
             let a = match a.try_borrow() {
                 Ok(value) => value,
-                Err(_) => return Err(env.std_exception_plain(
+                Err(_) => return Err(env.value_error_plain(
                     "Error in a[i]: a is already borrowed."))
             };
             if let Object::Int(i) = stack[sp-1] {
@@ -2774,8 +2800,9 @@ fn operator_index(env: &mut EnvPart, argc: usize,
                     Ok(())
                 },
                 None => {
-                    let key = stack[sp-1].clone().repr(&mut Env{env,sp,stack})?;
-                    Err(env.index_error_plain(&format!("Index error in m[{}]: not found.",key)))
+                    let key = stack[sp-1].clone();
+                    let s = key.repr(&mut Env{env,sp,stack})?;
+                    Err(env.key_error_plain(&format!("Index error in m[{}]: not found.",s), &key))
                 }
             }
         },
@@ -3280,7 +3307,7 @@ fn mut_fn_aliasing(env: &mut Env, f: &Function) -> Box<Exception> {
         Ok(value) => value,
         Err(e) => {return e;}
     };
-    env.env.std_exception_plain(&format!(
+    env.env.value_error_plain(&format!(
         "Memory error: function '{}' is already borrowed.", s))
 }
 
@@ -3328,6 +3355,60 @@ pub fn interface_types_set(rte: &RTE, index: usize, x: Rc<Class>) {
     }
 }
 
+fn push_string_keys(acc: &mut Vec<String>, m: &Map) {
+    for key in m.m.keys() {
+        if let Object::String(ref s) = *key {
+            acc.push(s.to_string());
+        }
+    }
+}
+
+// Candidates for tab completion in the command line. In a word of
+// the form a.b.c the prefix a.b is resolved by plain lookup in the
+// slot tables, custom getters are not called.
+fn completions(rte: &RTE, gtab: &Rc<RefCell<Map>>, word: &str) -> Vec<String> {
+    let mut path: Vec<&str> = word.split('.').collect();
+    let last = path.pop().unwrap_or("");
+    let mut acc: Vec<String> = Vec::new();
+    if path.is_empty() {
+        push_string_keys(&mut acc, &gtab.borrow());
+        push_string_keys(&mut acc, &rte.gtab.borrow());
+        acc.extend(compiler::keywords().map(String::from));
+    } else {
+        let key = Object::from(path[0]);
+        let mut x = match gtab.borrow().m.get(&key) {
+            Some(x) => x.clone(),
+            None => match rte.gtab.borrow().m.get(&key) {
+                Some(x) => x.clone(),
+                None => return acc
+            }
+        };
+        for id in &path[1..] {
+            let key = Object::from(*id);
+            let y = slot_maps(&x).iter()
+                .find_map(|m| m.borrow().m.get(&key).cloned());
+            x = match y {Some(y) => y, None => return acc};
+        }
+        let types = match x {
+            Object::String(_) => vec![&rte.type_string, &rte.type_iterable],
+            Object::List(_) => vec![&rte.type_list, &rte.type_iterable],
+            Object::Map(_) => vec![&rte.type_map, &rte.type_iterable],
+            Object::Function(_) => vec![&rte.type_function, &rte.type_iterable],
+            _ => Vec::new()
+        };
+        for t in types {
+            push_string_keys(&mut acc, &t.map.borrow());
+        }
+        for m in slot_maps(&x) {
+            push_string_keys(&mut acc, &m.borrow());
+        }
+    }
+    acc.retain(|s| s.starts_with(last));
+    acc.sort();
+    acc.dedup();
+    acc
+}
+
 pub fn secondary_env<'a>(rte: &Rc<RTE>, pstate: &'a mut Option<State>) -> Env<'a> {
     if let Some(state) = pstate {
         get_env(state)
@@ -3343,10 +3424,62 @@ pub fn secondary_env<'a>(rte: &Rc<RTE>, pstate: &'a mut Option<State>) -> Env<'a
     }
 }
 
+// Buffered output that has to be written before the process
+// exits, as process::exit does not run destructors.
+pub trait Flush {
+    fn flush(&self);
+}
+
+// Grants or denies access to everything below a directory.
+pub struct PathRule {
+    pub prefix: PathBuf,
+    pub allow: bool
+}
+
+// Access to a path is decided by the rule with the longest matching
+// prefix, among rules of equal prefix by the last one. If no rule
+// matches, the default read or write applies.
 pub struct Capabilities {
     pub read: bool,
     pub write: bool,
-    pub command: bool
+    pub read_rules: Vec<PathRule>,
+    pub write_rules: Vec<PathRule>,
+    pub command: bool,
+    pub env: bool
+}
+
+fn path_access(rules: &[PathRule], default: bool, path: &str) -> bool {
+    if rules.is_empty() {return default;}
+    let path = canonical_path(Path::new(path));
+    let mut access = default;
+    let mut len = 0;
+    for rule in rules {
+        if path.starts_with(&rule.prefix) {
+            let n = rule.prefix.components().count();
+            if n >= len {
+                len = n;
+                access = rule.allow;
+            }
+        }
+    }
+    access
+}
+
+impl Capabilities {
+    pub fn add_read_rule(&mut self, dir: &str, allow: bool) {
+        let prefix = canonical_path(Path::new(dir));
+        self.read_rules.push(PathRule{prefix, allow});
+    }
+    pub fn add_write_rule(&mut self, dir: &str, allow: bool) {
+        let prefix = canonical_path(Path::new(dir));
+        self.write_rules.push(PathRule{prefix, allow});
+    }
+    pub fn read_access(&self, path: &str) -> bool {
+        path_access(&self.read_rules, self.read, path)
+    }
+    pub fn write_access(&self, path: &str) -> bool {
+        path_access(&self.write_rules, self.write, path)
+    }
 }
 
 // Runtime environment: globally accessible information.
@@ -3366,6 +3499,14 @@ pub struct RTE {
     pub type_type_error: Rc<Class>,
     pub type_value_error: Rc<Class>,
     pub type_index_error: Rc<Class>,
+    pub type_key_error: Rc<Class>,
+    pub type_os_error: Rc<Class>,
+    pub type_io_error: Rc<Class>,
+    pub type_permission_error: Rc<Class>,
+    pub type_zero_division_error: Rc<Class>,
+    pub type_overflow_error: Rc<Class>,
+    pub type_import_error: Rc<Class>,
+    pub type_syntax_error: Rc<Class>,
     pub type_type: Rc<Class>,
     pub exception_obj: Object,
     pub unimplemented_class: Rc<Class>,
@@ -3387,6 +3528,17 @@ pub struct RTE {
     pub char_table: Vec<Object>,
     pub main_module: Cell<bool>,
 
+    // The source text of each compiled module, by module id,
+    // for the source lines shown in tracebacks.
+    pub sources: RefCell<HashMap<String, Rc<str>>>,
+
+    // Open buffered files, flushed by sys.exit.
+    pub buffers: RefCell<Vec<Weak<dyn Flush>>>,
+
+    // Set by the test runner: sys.exit raises an Exit exception
+    // instead of leaving the process.
+    pub trap_exit: Cell<bool>,
+
     pub key_string: Object,
     pub key_iter: Object,
     pub key_call: Object,
@@ -3427,6 +3579,9 @@ impl RTE {
 
         let exception = Class::new("Exeption",null);
         let exception_obj = Object::Interface(exception.clone());
+        let type_value_error = Class::new("ValueError",&exception_obj);
+        let type_index_error = Class::new("IndexError",&exception_obj);
+        let type_os_error = Class::new("OSError",&exception_obj);
 
         Rc::new(RTE {
             type_bool: Class::new("Bool",null),
@@ -3442,8 +3597,18 @@ impl RTE {
             type_long: Class::new("Long",null),
             type_exception: exception,
             type_type_error: Class::new("TypeError",&exception_obj),
-            type_value_error: Class::new("ValueError",&exception_obj),
-            type_index_error: Class::new("IndexError",&exception_obj),
+            type_key_error: Class::new("KeyError",
+                &Object::Interface(type_index_error.clone())),
+            type_io_error: Class::new("IOError",
+                &Object::Interface(type_os_error.clone())),
+            type_permission_error: Class::new("PermissionError",
+                &Object::Interface(type_os_error.clone())),
+            type_zero_division_error: Class::new("ZeroDivisionError",
+                &Object::Interface(type_value_error.clone())),
+            type_overflow_error: Class::new("OverflowError",&exception_obj),
+            type_import_error: Class::new("ImportError",&exception_obj),
+            type_syntax_error: Class::new("SyntaxError",&exception_obj),
+            type_value_error, type_index_error, type_os_error,
             type_type,
             exception_obj,
             unimplemented: Table::new(Object::Null),
@@ -3464,10 +3629,16 @@ impl RTE {
             capabilities: RefCell::new(Capabilities{
                 read: true,
                 write: false,
-                command: false
+                read_rules: Vec::new(),
+                write_rules: Vec::new(),
+                command: false,
+                env: false
             }),
             char_table,
             main_module: Cell::new(true),
+            sources: RefCell::new(HashMap::new()),
+            buffers: RefCell::new(Vec::new()),
+            trap_exit: Cell::new(false),
 
             key_string: CharString::new_object_str("string"),
             key_iter:   CharString::new_object_str("iter"),
@@ -3504,11 +3675,25 @@ impl RTE {
         // function). The gtab may also contain itself.
         self.delay.borrow_mut().push(gtab);
     }
-    pub fn read_access(&self, _path: &str) -> bool {
-        self.capabilities.borrow().read
+    pub fn register_buffer(&self, x: Weak<dyn Flush>) {
+        let mut buffers = self.buffers.borrow_mut();
+        buffers.retain(|x| x.strong_count() > 0);
+        buffers.push(x);
+    }
+    pub fn flush_buffers(&self) {
+        let buffers: Vec<Weak<dyn Flush>> = self.buffers.borrow_mut().drain(..).collect();
+        for x in buffers {
+            if let Some(x) = x.upgrade() {x.flush();}
+        }
+    }
+    pub fn read_access(&self, path: &str) -> bool {
+        self.capabilities.borrow().read_access(path)
     }
-    pub fn write_access(&self, _path: &str) -> bool {
-        self.capabilities.borrow().write
+    pub fn write_access(&self, path: &str) -> bool {
+        self.capabilities.borrow().write_access(path)
+    }
+    pub fn write_rules_active(&self) -> bool {
+        !self.capabilities.borrow().write_rules.is_empty()
     }
     pub fn set(&self, id: &str, x: Object) {
         self.gtab.borrow_mut().insert(id, x);
@@ -3528,6 +3713,7 @@ pub struct Module {
     pub id: String
 }
 
+#[derive(Clone)]
 pub struct Frame {
     ip: usize,
     base_pointer: usize,
@@ -3547,6 +3733,129 @@ pub struct State {
     pub env: EnvPart
 }
 
+// The lifetime of a call of vm_loop. A continuation can only be
+// resumed as long as the call of vm_loop that captured it is alive.
+struct Activation(Rc<Cell<bool>>);
+
+impl Activation {
+    fn new() -> Self {Self(Rc::new(Cell::new(true)))}
+}
+
+impl Drop for Activation {
+    fn drop(&mut self) {self.0.set(false);}
+}
+
+// The state of vm_loop at the point behind a call of callcc.
+struct Snapshot {
+    ip: usize, sp: usize, argv_ptr: usize, bp: usize,
+    module: Rc<Module>,
+    gtab: Rc<RefCell<Map>>,
+    fnself: Rc<Function>,
+    ret: bool, catch: bool,
+    stack: Vec<Object>,
+    frames: Vec<Frame>,
+    catches: Vec<CatchFrame>,
+    // The shared variables on the captured part of the stack,
+    // with their values at the moment of capture.
+    cells: Vec<(Object,Object)>
+}
+
+pub struct Continuation {
+    alive: Rc<Cell<bool>>,
+    state: Rc<Snapshot>
+}
+
+impl Interface for Continuation {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Continuation".to_string()
+    }
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok("continuation".to_string())
+    }
+    fn call(&self, env: &mut Env, _pself: &Object, argv: &[Object]) -> FnResult {
+        let value = match argv.len() {
+            0 => Object::Null,
+            1 => argv[0].clone(),
+            n => return env.argc_error(n,0,1,"continuation")
+        };
+        if !self.alive.get() {
+            return env.value_error(
+                "Error in k(x): the continuation has expired.");
+        }
+        // Unwind to the call of vm_loop that captured the
+        // continuation, bypassing every catch on the way.
+        Err(Box::new(Exception {
+            value: Object::Interface(Rc::new(Resume {
+                alive: self.alive.clone(),
+                state: self.state.clone(),
+                value
+            })),
+            traceback: None, spot: None, context: None
+        }))
+    }
+}
+
+struct Resume {
+    alive: Rc<Cell<bool>>,
+    state: Rc<Snapshot>,
+    value: Object
+}
+
+impl Interface for Resume {
+    fn as_any(&self) -> &dyn Any {self}
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok("resumption of a continuation".to_string())
+    }
+}
+
+// The exception value of sys.exit under the test runner. Like
+// a resumption, it bypasses every catch, thus it reaches the
+// runner even if the test catches all exceptions.
+pub struct Exit {
+    pub status: i32
+}
+
+impl Interface for Exit {
+    fn as_any(&self) -> &dyn Any {self}
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok(format!("Exit: sys.exit({}) was called.", self.status))
+    }
+}
+
+// A variable shared between a function and its closures.
+pub struct VarCell {
+    value: RefCell<Object>
+}
+
+impl Interface for VarCell {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Cell".to_string()
+    }
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok("cell".to_string())
+    }
+}
+
+// The function callcc(f) is recognized by vm_loop,
+// as it has to capture the state of the machine.
+pub struct CallCC;
+
+impl Interface for CallCC {
+    fn as_any(&self) -> &dyn Any {self}
+    fn type_name(&self, _env: &mut Env) -> String {
+        "Function".to_string()
+    }
+    fn to_string(self: Rc<Self>, _env: &mut Env) -> Result<String,Box<Exception>> {
+        Ok("function callcc".to_string())
+    }
+    fn call(&self, env: &mut Env, _pself: &Object, _argv: &[Object]) -> FnResult {
+        env.type_error(
+            "Error in callcc(f): callcc cannot be called by a native function.")
+    }
+}
+
 fn vm_loop(
     state: &mut Env,
     mut ip: usize,
@@ -3566,12 +3875,27 @@ fn vm_loop(
   let mut ret = true;
   let mut catch = false;
 
+  // Set by CRAISE: the traceback already contains this frame.
+  let mut reraise = false;
+
+  // The part of the machine state owned by this call,
+  // to be captured by callcc.
+  let base = argv_ptr;
+  let frame_base = env.frame_stack.len();
+  let catch_base = env.catch_stack.len();
+  let mut activation: Option<Activation> = None;
+
+  // Read once, so that the dispatch loop pays only for a
+  // predictable branch while /time is not active.
+  let counting = env.counting;
+
   // print_stack(&stack[0..10]);
 
   'main: loop { // loop
   loop { // try
     // print_stack(&stack[0..10]);
     // print_op(&a,ip);
+    if counting {env.instructions += 1;}
     match a[ip] as u8 {
       bc::NULL => {
           stack[sp] = Object::Null;
@@ -3889,6 +4213,7 @@ fn vm_loop(
                   } else {
                     let s = fobj.string(&mut Env{env,stack,sp})?;
                     exception = Err(env.argc_error_plain(argc, f.argc_min, f.argc_max, &s));
+                    ip -= BCASIZE;
                     break;
                   }
                 }
@@ -3921,9 +4246,9 @@ fn vm_loop(
                   let mut env = Env{sp: 0, stack: s2, env};
                   match fp(&mut env, &s1[sp-argc-1], &s1[sp-argc..sp]) {
                     Ok(y) => y, Err(mut e) => {
-                        let (line,col) = get_line_col(&a,ip-BCASIZE);
-                        let fids = function_id_to_string(f);
-                        e.push_clm(line,col,&module.id,&fids);
+                        e.traceback_push(&function_id_to_string(f));
+                        // The position of the call, not of the next instruction.
+                        ip -= BCASIZE;
                         exception = Err(e);
                         break;
                     }
@@ -3940,14 +4265,15 @@ fn vm_loop(
                   let pf = &mut *match fp.try_borrow_mut() {
                     Ok(f)=>f, Err(_) => {
                         exception = Err(mut_fn_aliasing(&mut env,f));
+                        ip -= BCASIZE;
                         break;
                     }
                   };
                   match pf(&mut env, &s1[sp-argc-1], &s1[sp-argc..sp]) {
                     Ok(y) => y, Err(mut e) => {
-                        let (line,col) = get_line_col(&a,ip-BCASIZE);
-                        let fids = function_id_to_string(f);
-                        e.push_clm(line,col,&module.id,&fids);
+                        e.traceback_push(&function_id_to_string(f));
+                        // The position of the call, not of the next instruction.
+                        ip -= BCASIZE;
                         exception = Err(e);
                         break;
                     }
@@ -3959,9 +4285,38 @@ fn vm_loop(
               }
             }
           },
+          Object::Interface(ref x) if x.as_any().is::<CallCC>() => {
+              if argc != 1 {
+                  exception = Err(env.argc_error_plain(argc,1,1,"callcc"));
+                  ip -= BCASIZE;
+                  break;
+              }
+              let alive = activation.get_or_insert_with(Activation::new).0.clone();
+              let captured = stack[base..sp-3].to_vec();
+              let cells = captured.iter().filter_map(|x| downcast::<VarCell>(x)
+                  .map(|cell| (x.clone(), cell.value.borrow().clone()))).collect();
+              let state = Rc::new(Snapshot {
+                  ip, sp: sp-2, argv_ptr, bp,
+                  module: module.clone(), gtab: gtab.clone(),
+                  fnself: fnself.clone(), ret, catch,
+                  stack: captured,
+                  frames: env.frame_stack[frame_base..].to_vec(),
+                  catches: env.catch_stack[catch_base..].to_vec(),
+                  cells
+              });
+              // Replace callcc(f) by f(k) and call it.
+              stack[sp-3] = stack[sp-1].take();
+              stack[sp-1] = Object::Interface(Rc::new(Continuation {alive, state}));
+              ip -= BCASIZE;
+              continue;
+          },
           _ => {
               match object_call(env,&fobj,argc,sp,stack) {
-                  Ok(()) => {}, Err(e) => {exception = Err(e); break;}
+                  Ok(()) => {}, Err(e) => {
+                      ip -= BCASIZE;
+                      exception = Err(e);
+                      break;
+                  }
               }
               sp -= argc + 1;
               continue;
@@ -4182,10 +4537,16 @@ fn vm_loop(
       },
       bc::TABLE => {
           sp -= 1;
-          stack[sp-1] = new_table(
-              stack[sp].take(),
-              stack[sp-1].take()
-          );
+          let prototype = stack[sp].take();
+          let map = stack[sp-1].take();
+          if let Object::Map(ref m) = map {
+              if let Err(e) = check_abstract(&mut Env{env,sp,stack},
+                  &prototype, &m.borrow())
+              {
+                  exception = Err(e); break;
+              }
+          }
+          stack[sp-1] = new_table(prototype, map);
           ip += BCSIZE;
       },
       bc::GET => {
@@ -4228,17 +4589,32 @@ fn vm_loop(
               env.catch_stack.pop();
               ip += BCSIZE;
           } else if op == bc::GETEXC {
-              if let Err(ref e) = exception {
+              if let Err(ref mut e) = exception {
+                  if e.traceback.is_some() {
+                      let end = (ip as i32+load_i32(&a,ip+BCSIZE)) as usize;
+                      env.enter_handler(&a, ip, end, e);
+                  }
                   stack[sp] = e.value.clone();
                   sp += 1;
               } else {
                   panic!();
               }
-              ip += BCSIZE;
+              ip += BCASIZE;
           } else if op == bc::CRAISE {
               catch = false;
               env.catch_stack.pop();
+              env.leave_handler(&a, ip);
+              reraise = true;
               break;
+          } else if op == bc::DOC {
+              // Follows FN, thus the function is not shared yet.
+              let index = load_u32(&a,ip+BCSIZE) as usize;
+              if let Object::Function(ref mut f) = stack[sp-1] {
+                  if let Some(f) = Rc::get_mut(f) {
+                      f.doc = module.data[index].clone();
+                  }
+              }
+              ip += BCASIZE;
           } else {
               panic!();
           }
@@ -4247,7 +4623,11 @@ fn vm_loop(
           let index = load_u32(&a,ip+BCSIZE);
           stack[sp] = match Long::to_long(&module.data[index as usize]) {
               Ok(x) => x,
-              Err(()) => unreachable!("to_long")
+              Err(()) => {
+                  exception = Err(env.value_error_plain(
+                      "Value error: integer literal not supported by Long."));
+                  break;
+              }
           };
           sp += 1;
           ip += BCASIZE;
@@ -4264,12 +4644,99 @@ fn vm_loop(
           sp -= 2;
           ip += BCSIZE;
       },
+      bc::CELL => {
+          // A variable shared between a function and its closures
+          // is boxed into a cell.
+          let value = RefCell::new(stack[sp-1].take());
+          stack[sp-1] = Object::Interface(Rc::new(VarCell {value}));
+          ip += BCSIZE;
+      },
+      bc::CELL_GET => {
+          stack[sp-1] = match downcast::<VarCell>(&stack[sp-1]) {
+              Some(cell) => cell.value.borrow().clone(),
+              None => panic!()
+          };
+          ip += BCSIZE;
+      },
+      bc::CELL_SET => {
+          let x = stack[sp-1].take();
+          match downcast::<VarCell>(&x) {
+              Some(cell) => {
+                  *cell.value.borrow_mut() = stack[sp-2].take();
+              },
+              None => panic!()
+          }
+          sp -= 2;
+          ip += BCSIZE;
+      },
       _ => {panic!()}
     }
   }
 
+  // A continuation is resumed by an exception that
+  // is not caught until it reaches its owner.
+  let mut escape = false;
+  if let Err(ref e) = exception {
+      if let Some(r) = downcast::<Resume>(&e.value) {
+          let owner = match activation {
+              Some(ref x) => Rc::ptr_eq(&x.0, &r.alive),
+              None => false
+          };
+          if owner {
+              let state = r.state.clone();
+              let value = r.value.clone();
+              for x in &mut stack[base..sp] {
+                  *x = Object::Null;
+              }
+              stack[base..base+state.stack.len()].clone_from_slice(&state.stack);
+              for (x,value) in &state.cells {
+                  if let Some(cell) = downcast::<VarCell>(x) {
+                      *cell.value.borrow_mut() = value.clone();
+                  }
+              }
+              sp = state.sp;
+              stack[sp-1] = value;
+              env.frame_stack.truncate(frame_base);
+              env.frame_stack.extend(state.frames.iter().cloned());
+              env.catch_stack.truncate(catch_base);
+              env.catch_stack.extend(state.catches.iter().cloned());
+              ip = state.ip;
+              argv_ptr = state.argv_ptr;
+              bp = state.bp;
+              module = state.module.clone();
+              a = module.program.clone();
+              gtab = state.gtab.clone();
+              fnself = state.fnself.clone();
+              ret = state.ret;
+              catch = state.catch;
+              exception = Ok(());
+              continue 'main;
+          }
+          escape = true;
+      } else if downcast::<Exit>(&e.value).is_some() {
+          escape = true;
+      }
+  }
+
+  if !escape && !reraise {
+      if let Err(ref mut e) = exception {
+          let (line,col) = get_line_col(&a,ip);
+          if e.spot.is_none() {
+              e.set_spot(line,col,&module.id);
+          }
+          if e.traceback.is_some() {
+              e.push_clm(line,col,&module.id,&frame_name(&fnself));
+              if e.context.is_none() {
+                  e.context = env.handled_exception(&a, ip)
+                      .filter(|x| !same_value(&x.value, &e.value));
+              }
+          }
+      }
+  }
+  reraise = false;
+
   // catch:
-  if catch {
+  if catch && !escape {
       let cframe = env.catch_stack.last().unwrap();
       ip = cframe.ip;
       for p in &mut stack[cframe.sp..sp] {
@@ -4279,14 +4746,6 @@ fn vm_loop(
   } else {
       state.sp = sp;
       if let Err(ref mut e) = exception {
-          match e.spot {
-              None => {
-                  let (line,col) = get_line_col(&a,ip);
-                  e.set_spot(line,col,&module.id);
-              },
-              Some(_) => {}
-          }
-
           loop {
               if ret {break;}
               let frame = match env.frame_stack.pop() {
@@ -4296,10 +4755,9 @@ fn vm_loop(
               module = frame.module;
               a = module.program.clone();
               let (line,col) = get_line_col(&a,frame.ip-BCASIZE);
-              let fids = function_id_to_string(&*fnself);
-              e.push_clm(line,col,&module.id,&fids);
+              e.push_clm(line,col,&module.id,&frame_name(&frame.f));
               fnself = frame.f;
-              if frame.catch {
+              if frame.catch && !escape {
                   let cframe = env.catch_stack.last().unwrap();
                   ip = cframe.ip;
                   for p in &mut stack[cframe.sp..sp] {
@@ -4337,7 +4795,7 @@ pub fn eval(env: &mut Env,
     let fnself = Rc::new(Function{
         f: EnumFunction::Plain(global::fpanic),
         argc: 0, argc_min: 0, argc_max: 0,
-        id: Object::Null
+        id: Object::Null, doc: Object::Null
     });
     let pgtab_save: Rc<RefCell<Map>>;
     {
@@ -4456,17 +4914,29 @@ fn exception_text_to_string(env: &mut Env, x: &Object) -> String {
     }
 }
 
+fn source_line(env: &mut Env, module: &str, line: usize) -> Option<String> {
+    let sources = env.rte().sources.borrow();
+    let s = sources.get(module)?;
+    let text = s.lines().nth(line.checked_sub(1)?)?.trim();
+    if text.is_empty() {None} else {Some(text.to_string())}
+}
+
 fn exception_to_string(env: &mut Env, e: &Exception) -> String {
     let mut acc = String::new();
-    if let Some(ref traceback) = e.traceback {
-        for x in traceback.v.iter().rev() {
-            match x.string(env) {
-                Ok(x) => {writeln!(&mut acc, "  in {}", x).unwrap();},
-                Err(e) => {
-                    write!(&mut acc, "{}", exception_to_string(env, &e)).unwrap();
-                    writeln!(&mut acc,"[^Another exception occured in str(exception.traceback[k]).]").unwrap();
-                }
-            };
+    if let Some(context) = e.context() {
+        let s = exception_to_string(env, context);
+        writeln!(&mut acc, "{}\n", s.trim_end()).unwrap();
+        writeln!(&mut acc, "Raised while handling the exception above:").unwrap();
+    }
+    for x in e.frames() {
+        if x.line == 0 {
+            writeln!(&mut acc, "  in {}", x.function).unwrap();
+            continue;
+        }
+        writeln!(&mut acc, "  in {}, {}:{}:{}",
+            x.function, x.module, x.line, x.col).unwrap();
+        if let Some(text) = source_line(env, &x.module, x.line) {
+            writeln!(&mut acc, "      {}", text).unwrap();
         }
     }
     if let Some(ref spot) = e.spot {
@@ -4487,21 +4957,102 @@ pub fn stack_clear(a: &mut [Object]) {
     }
 }
 
+#[derive(Clone)]
 pub struct CatchFrame {
     ip: usize,
     sp: usize
 }
 
+// A running catch block, from GETEXC to the end of the block,
+// in the function at the given depth of the frame stack.
+struct Handler {
+    depth: usize,
+    program: Rc<[u32]>,
+    start: usize,
+    end: usize,
+    exception: Rc<Exception>
+}
+
 pub struct EnvPart {
     frame_stack: Vec<Frame>,
     catch_stack: Vec<CatchFrame>,
-    rte: Rc<RTE>
+    handlers: Vec<Handler>,
+    rte: Rc<RTE>,
+
+    // Number of executed instructions, shown by /time.
+    // Counted only while counting is set.
+    instructions: u64,
+    counting: bool
 }
 
 impl EnvPart{
     pub fn new(frame_stack_size: usize, rte: Rc<RTE>) -> Self {
         let frame_stack: Vec<Frame> = Vec::with_capacity(frame_stack_size);
-        Self {frame_stack, catch_stack: Vec::new(), rte}
+        Self {frame_stack, catch_stack: Vec::new(), handlers: Vec::new(),
+            rte, instructions: 0, counting: false}
+    }
+
+    fn handler_active(&self, h: &Handler, program: &Rc<[u32]>, ip: usize) -> bool {
+        let depth = self.frame_stack.len();
+        let (program, ip) = if h.depth == depth {
+            (program, ip)
+        } else if h.depth < depth {
+            let frame = &self.frame_stack[h.depth];
+            (&frame.module.program, frame.ip)
+        } else {
+            return false;
+        };
+        Rc::ptr_eq(&h.program, program) && h.start < ip && ip < h.end
+    }
+
+    // Called by GETEXC. Makes the traceback and the context
+    // available as fields of the exception object.
+    fn enter_handler(&mut self, program: &Rc<[u32]>, ip: usize, end: usize,
+        e: &mut Exception
+    ) {
+        let depth = self.frame_stack.len();
+        self.handlers.retain(|h| h.depth < depth || h.depth == depth
+            && Rc::ptr_eq(&h.program, program) && h.start < ip && ip < h.end);
+        if let Some(t) = downcast::<Table>(&e.value) {
+            let frames: Vec<Object> = e.frames().into_iter().map(|x| {
+                let frame = Table::new(Object::Null);
+                let mut m = frame.map.borrow_mut();
+                m.insert("name", CharString::new_object_str(&x.function));
+                m.insert("module", CharString::new_object_str(&x.module));
+                m.insert("line", Object::Int(x.line as i32));
+                m.insert("col", Object::Int(x.col as i32));
+                drop(m);
+                Object::Interface(frame)
+            }).collect();
+            let mut m = t.map.borrow_mut();
+            m.insert("traceback", List::new_object(frames));
+            m.insert("context", match e.context {
+                Some(ref context) => context.value.clone(),
+                None => Object::Null
+            });
+        }
+        self.handlers.push(Handler {depth, program: program.clone(),
+            start: ip, end, exception: Rc::new(e.clone())});
+    }
+
+    // Called by CRAISE: the catch block was not entered.
+    fn leave_handler(&mut self, program: &Rc<[u32]>, ip: usize) {
+        if let Some(h) = self.handlers.last() {
+            if h.depth == self.frame_stack.len() && self.handler_active(h, program, ip) {
+                self.handlers.pop();
+            }
+        }
+    }
+
+    // The exception handled by the innermost running catch block.
+    fn handled_exception(&mut self, program: &Rc<[u32]>, ip: usize) -> Option<Rc<Exception>> {
+        while let Some(h) = self.handlers.last() {
+            if self.handler_active(h, program, ip) {
+                return Some(h.exception.clone());
+            }
+            self.handlers.pop();
+        }
+        None
     }
 
     pub fn std_exception_plain(&self, s: &str) -> Box<Exception> {
@@ -4520,6 +5071,65 @@ impl EnvPart{
         Exception::new(s, Object::Interface(self.rte.type_index_error.clone()))
     }
 
+    // An exception whose object carries the given slots
+    // in addition to the text.
+    fn exception_with_slots(&self, s: &str, class: &Rc<Class>,
+        slots: &[(&str, Object)]
+    ) -> Box<Exception> {
+        let e = Exception::new(s, Object::Interface(class.clone()));
+        if let Some(t) = downcast::<Table>(&e.value) {
+            let mut m = t.map.borrow_mut();
+            for (key, value) in slots {
+                m.insert(key, value.clone());
+            }
+        }
+        e
+    }
+
+    pub fn key_error_plain(&self, s: &str, key: &Object) -> Box<Exception> {
+        self.exception_with_slots(s, &self.rte.type_key_error,
+            &[("key", key.clone())])
+    }
+
+    // The error number is the one reported by the operating system,
+    // if any. An empty path is stored as null.
+    pub fn os_error_plain(&self, s: &str, errno: Option<i32>, path: &str) -> Box<Exception> {
+        let errno = match errno {Some(n) => Object::Int(n), None => Object::Null};
+        let path = if path.is_empty() {Object::Null} else {Object::from(path)};
+        self.exception_with_slots(s, &self.rte.type_os_error,
+            &[("errno", errno), ("path", path)])
+    }
+
+    // A failed read or write of an open stream.
+    pub fn io_error_plain(&self, s: &str, errno: Option<i32>, path: &str) -> Box<Exception> {
+        let errno = match errno {Some(n) => Object::Int(n), None => Object::Null};
+        let path = if path.is_empty() {Object::Null} else {Object::from(path)};
+        self.exception_with_slots(s, &self.rte.type_io_error,
+            &[("errno", errno), ("path", path)])
+    }
+
+    pub fn permission_error_plain(&self, s: &str, path: &str) -> Box<Exception> {
+        self.exception_with_slots(s, &self.rte.type_permission_error,
+            &[("errno", Object::Null), ("path", Object::from(path))])
+    }
+
+    pub fn zero_division_error_plain(&self, s: &str) -> Box<Exception> {
+        Exception::new(s, Object::Interface(self.rte.type_zero_division_error.clone()))
+    }
+
+    pub fn overflow_error_plain(&self, s: &str) -> Box<Exception> {
+        Exception::new(s, Object::Interface(self.rte.type_overflow_error.clone()))
+    }
+
+    pub fn import_error_plain(&self, s: &str, module: &str) -> Box<Exception> {
+        self.exception_with_slots(s, &self.rte.type_import_error,
+            &[("module", Object::from(module))])
+    }
+
+    pub fn syntax_error_plain(&self, s: &str) -> Box<Exception> {
+        Exception::new(s, Object::Interface(self.rte.type_syntax_error.clone()))
+    }
+
     pub fn argc_error_plain(&self, argc: usize, min: u32, max: u32, id: &str) -> Box<Exception> {
         let t = Object::Interface(self.rte.type_exception.clone());
         if min == max {
@@ -4673,13 +5283,7 @@ pub fn call(&mut self, fobj: &Object,
             self.stack[self.sp] = Object::Null;
             self.sp += 1;
           }
-          match vm_loop(self,fp.address.get(),sp,bp,fp.module.clone(),fp.gtab.clone(),f.clone()) {
-            Ok(()) => {},
-            Err(mut e) => {
-              e.traceback_push(&function_id_to_string(f));
-              return Err(e);
-            }
-          }
+          vm_loop(self,fp.address.get(),sp,bp,fp.module.clone(),fp.gtab.clone(),f.clone())?;
           let y = self.stack[self.sp-1].take();
           for x in &mut self.stack[sp..self.sp-1] {
             *x = Object::Null;
@@ -4722,13 +5326,17 @@ pub fn eval_string(&mut self, s: &str, id: &str, gtab: Rc<RefCell<Map>>,
             eval(self, module, gtab, false)
         },
         Err(e) => {
-            self.std_exception(&compiler::format_error(&e))
+            self.syntax_error(&compiler::format_error(&e, s))
         }
     }
 }
 
 pub fn command_line_session(&mut self, gtab: Rc<RefCell<Map>>){
-    let mut history = History::new();
+    let mut history = History::persistent();
+    let rte = self.rte().clone();
+    let ctab = gtab.clone();
+    history.set_completer(Box::new(move |word| completions(&rte, &ctab, word)));
+    let mut loaded: Option<String> = None;
     loop {
         let mut input = String::new();
         match getline_history("> ", &history) {
@@ -4737,6 +5345,7 @@ pub fn command_line_session(&mut self, gtab: Rc<RefCell<Map>>){
                 history.append(&s);
                 input = s;
             },
+            Err(ref error) if error.kind() == std::io::ErrorKind::UnexpectedEof => break,
             Err(error) => {println!("Error: {}", error);},
         };
         if input=="quit" {break}
@@ -4746,6 +5355,8 @@ pub fn command_line_session(&mut self, gtab: Rc<RefCell<Map>>){
             } else if input=="/c" {
                 print!("\x1b[H\x1b[J");
                 continue;
+            } else if self.meta_command(&input, &gtab, &mut loaded) {
+                continue;
             }
         }
         match compiler::compile(&input, "command line", true,
@@ -4760,12 +5371,188 @@ pub fn command_line_session(&mut self, gtab: Rc<RefCell<Map>>){
                 }
             },
             Err(e) => {
-                println!("{}",compiler::format_error(&e));
+                println!("{}",compiler::format_error(&e, &input));
             }
         }
     }
 }
 
+// The commands /type, /doc, /time, /load, /reload, /vars,
+// /disasm and /help of the command line. Returns false if
+// the command is unknown.
+fn meta_command(&mut self, input: &str, gtab: &Rc<RefCell<Map>>,
+    loaded: &mut Option<String>
+) -> bool
+{
+    let (cmd, arg) = match input.find(' ') {
+        Some(k) => (&input[..k], input[k+1..].trim()),
+        None => (input, "")
+    };
+    let result = match cmd {
+        "/type" => self.eval_string(arg, "command line", gtab.clone(),
+            compiler::Value::Optional
+        ).and_then(|x| {
+            let t = global::ftype(self, &Object::Null, &[x])?;
+            object_to_repr(self, &t)
+        }),
+        "/doc" => self.eval_string(arg, "command line", gtab.clone(),
+            compiler::Value::Optional
+        ).and_then(|x| self.help_text(&x)),
+        "/time" => {
+            self.env.instructions = 0;
+            self.env.counting = true;
+            let clock = std::time::Instant::now();
+            let y = self.eval_string(arg, "command line", gtab.clone(),
+                compiler::Value::Optional);
+            let time = clock.elapsed().as_secs_f64();
+            self.env.counting = false;
+            let count = self.env.instructions;
+            y.and_then(|x| {
+                let mut s = match x {
+                    Object::Null => String::new(),
+                    x => object_to_repr(self, &x)? + "\n"
+                };
+                s.push_str(&format!("Time: {:.6} s, {} instructions.", time, count));
+                Ok(s)
+            })
+        },
+        "/load" | "/reload" => {
+            if cmd == "/load" {
+                *loaded = Some(arg.to_string());
+            }
+            match loaded.clone() {
+                Some(id) => {
+                    self.eval_file(&id, gtab.clone());
+                    return true;
+                },
+                None => Ok(String::from("Error: no file loaded yet."))
+            }
+        },
+        "/vars" => {
+            let mut v: Vec<(String,Object)> = gtab.borrow().m.iter()
+                .map(|(key, value)| (key.to_string(), value.clone())).collect();
+            v.sort_by(|x, y| x.0.cmp(&y.0));
+            let mut acc: Vec<String> = Vec::new();
+            for (key, value) in v {
+                match global::ftype(self, &Object::Null, &[value]) {
+                    Ok(Object::Null) => acc.push(format!("{}: null", key)),
+                    Ok(t) => acc.push(format!("{}: {}", key, object_to_string(self, &t)
+                        .unwrap_or_default())),
+                    Err(_) => acc.push(key)
+                }
+            }
+            Ok(acc.join("\n"))
+        },
+        "/disasm" => self.eval_string(arg, "command line", gtab.clone(),
+            compiler::Value::Optional
+        ).and_then(|x| match x {
+            Object::Function(ref f) => match f.f {
+                EnumFunction::Std(ref sf) => Ok(compiler::function_listing(
+                    &sf.module.program, sf.address.get()).trim_end().to_string()),
+                _ => Ok(String::from("Built-in function, no bytecode."))
+            },
+            _ => self.type_error("Type error in /disasm f: f is not a function.")
+                .map(|_| String::new())
+        }),
+        "/help" => Ok(String::from(concat!(
+            "/type x     type of the expression x\n",
+            "/doc x      description of x\n",
+            "/time x     evaluate x, report time and instruction count\n",
+            "/load id    run the file id in this session\n",
+            "/reload     run the last loaded file again\n",
+            "/vars       list of global variables\n",
+            "/disasm f   bytecode of the function f\n",
+            "/c          clear the screen\n",
+            "/q          quit"))),
+        _ => return false
+    };
+    match result {
+        Ok(s) => if !s.is_empty() {println!("{}", s);},
+        Err(e) => println!("{}", exception_to_string(self, &e))
+    }
+    true
+}
+
+// A short description of an object for /doc.
+fn describe(&mut self, x: &Object) -> Result<String,Box<Exception>> {
+    Ok(match x {
+        Object::Function(f) => {
+            let name = match f.id {
+                Object::String(ref s) => s.to_string(),
+                _ => String::from("anonymous")
+            };
+            let argc = if f.argc_max == VARIADIC {
+                format!("{} or more", f.argc_min)
+            } else if f.argc_min == f.argc_max {
+                format!("{}", f.argc_min)
+            } else {
+                format!("{}..{}", f.argc_min, f.argc_max)
+            };
+            let kind = match f.f {
+                EnumFunction::Std(_) => "function",
+                _ => "built-in function"
+            };
+            format!("{} {}, arguments: {}", kind, name, argc)
+        },
+        Object::Interface(p) if p.as_any().is::<Class>() || p.as_any().is::<Table>() => {
+            let mut keys: Vec<String> = Vec::new();
+            for m in slot_maps(x) {
+                for key in m.borrow().m.keys() {
+                    keys.push(key.to_string());
+                }
+            }
+            keys.sort();
+            keys.dedup();
+            let head = if p.as_any().is::<Class>() {
+                let mro = class_mro(self, &Object::Null, std::slice::from_ref(x))?;
+                format!("class {}, order: {}", object_to_string(self, x)?,
+                    object_to_string(self, &mro)?)
+            } else {
+                let t = p.clone().get_type(self)?;
+                format!("table of type {}", object_to_string(self, &t)?)
+            };
+            format!("{}\nproperties: {}", head, keys.join(", "))
+        },
+        x => {
+            let t = global::ftype(self, &Object::Null, std::slice::from_ref(x))?;
+            format!("{}: {}", object_to_repr(self, x)?, object_to_string(self, &t)?)
+        }
+    })
+}
+
+// The text shown by help(x) and /doc x: the documentation of
+// x if there is some, otherwise the description.
+pub fn help_text(&mut self, x: &Object) -> Result<String,Box<Exception>> {
+    if let Object::Function(ref f) = *x {
+        if let Object::String(ref s) = f.doc {
+            return Ok(s.to_string());
+        }
+    }
+    let mut acc = self.describe(x)?;
+    if let Some(c) = downcast::<Class>(x) {
+        if let Object::String(ref s) = c.doc {
+            write!(acc, "\n\n{}", s.to_string()).unwrap();
+        }
+        let mut methods: Vec<(String, String)> = Vec::new();
+        for (key, value) in &c.map.borrow().m {
+            if let Object::Function(ref f) = *value {
+                if let Object::String(ref s) = f.doc {
+                    methods.push((key.to_string(), s.to_string()));
+                }
+            }
+        }
+        methods.sort();
+        for (_, doc) in methods {
+            let mut lines = doc.lines();
+            write!(acc, "\n\n{}", lines.next().unwrap_or("")).unwrap();
+            if let Some(line) = lines.next() {
+                write!(acc, "\n   {}", line).unwrap();
+            }
+        }
+    }
+    Ok(acc)
+}
+
 pub fn eval(&mut self, s: &str) -> Object {
     let gtab = Map::new();
     match self.eval_string(s, "", gtab, compiler::Value::Optional) {
@@ -4845,6 +5632,46 @@ pub fn index_error(&self, s: &str) -> FnResult {
     Err(self.env.index_error_plain(s))
 }
 
+#[inline(never)]
+pub fn key_error(&self, s: &str, key: &Object) -> FnResult {
+    Err(self.env.key_error_plain(s, key))
+}
+
+#[inline(never)]
+pub fn os_error(&self, s: &str, errno: Option<i32>, path: &str) -> FnResult {
+    Err(self.env.os_error_plain(s, errno, path))
+}
+
+#[inline(never)]
+pub fn io_error(&self, s: &str, errno: Option<i32>, path: &str) -> FnResult {
+    Err(self.env.io_error_plain(s, errno, path))
+}
+
+#[inline(never)]
+pub fn permission_error(&self, s: &str, path: &str) -> FnResult {
+    Err(self.env.permission_error_plain(s, path))
+}
+
+#[inline(never)]
+pub fn zero_division_error(&self, s: &str) -> FnResult {
+    Err(self.env.zero_division_error_plain(s))
+}
+
+#[inline(never)]
+pub fn overflow_error(&self, s: &str) -> FnResult {
+    Err(self.env.overflow_error_plain(s))
+}
+
+#[inline(never)]
+pub fn import_error(&self, s: &str, module: &str) -> FnResult {
+    Err(self.env.import_error_plain(s, module))
+}
+
+#[inline(never)]
+pub fn syntax_error(&self, s: &str) -> FnResult {
+    Err(self.env.syntax_error_plain(s))
+}
+
 #[inline(never)]
 pub fn argc_error(&self,
     argc: usize, min: u32, max: u32, id: &str
@@ -4873,6 +5700,14 @@ pub fn value_error_plain(&self, s: &str) -> Box<Exception> {
     self.env.value_error_plain(s)
 }
 
+pub fn os_error_plain(&self, s: &str, errno: Option<i32>, path: &str) -> Box<Exception> {
+    self.env.os_error_plain(s, errno, path)
+}
+
+pub fn permission_error_plain(&self, s: &str, path: &str) -> Box<Exception> {
+    self.env.permission_error_plain(s, path)
+}
+
 pub fn exception_to_string(&mut self, e: &Exception) -> String {
     exception_to_string(self,e)
 }
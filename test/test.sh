@@ -13,6 +13,10 @@ moss-test test-exceptions
 moss-test test-cf
 moss-test test-la-inv
 moss-test test-long
+moss-test test-continuations
+moss-test -unsafe test-fs
+moss-test -unsafe test-tools
+moss-test -allow-write=sandbox.tmp -deny-read=sandbox.tmp/priv test-sandbox
 
 # moss-test test-la-inv-complex
 # too slow in debug mode